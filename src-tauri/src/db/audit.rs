@@ -0,0 +1,198 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use sha2::{Digest, Sha256};
+use super::types::*;
+
+/// `prev_hash` of the first entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+const COLUMNS: &str = "seq, timestamp, conn_id, connection_name, host, operation, sql, \
+                       rows_affected, outcome, error, prev_hash, hash";
+
+/// What a command knows about a write before it is chained and stored.
+pub struct AuditRecord<'a> {
+    pub conn_id: &'a str,
+    pub connection_name: &'a str,
+    pub host: Option<&'a str>,
+    pub operation: &'a str,
+    pub sql: &'a str,
+    pub result: Result<u64, &'a str>,
+}
+
+fn outcome_str(outcome: AuditOutcome) -> &'static str {
+    match outcome {
+        AuditOutcome::Success => "success",
+        AuditOutcome::Failure => "failure",
+    }
+}
+
+/// SHA-256 over the previous hash and every recorded field, so editing,
+/// dropping or reordering any entry breaks the chain from that point on.
+fn entry_hash(entry: &AuditEntry) -> String {
+    let fields = serde_json::json!([
+        entry.timestamp,
+        entry.conn_id,
+        entry.connection_name,
+        entry.host,
+        entry.operation,
+        entry.sql,
+        entry.rows_affected,
+        outcome_str(entry.outcome),
+        entry.error,
+    ]);
+    let mut hasher = Sha256::new();
+    hasher.update(entry.prev_hash.as_bytes());
+    hasher.update(fields.to_string().as_bytes());
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<AuditEntry> {
+    let outcome: String = row.get(8)?;
+    Ok(AuditEntry {
+        seq: row.get(0)?,
+        timestamp: row.get(1)?,
+        conn_id: row.get(2)?,
+        connection_name: row.get(3)?,
+        host: row.get(4)?,
+        operation: row.get(5)?,
+        sql: row.get(6)?,
+        rows_affected: row.get::<_, Option<i64>>(7)?.map(|n| n as u64),
+        outcome: if outcome == "success" { AuditOutcome::Success } else { AuditOutcome::Failure },
+        error: row.get(9)?,
+        prev_hash: row.get(10)?,
+        hash: row.get(11)?,
+    })
+}
+
+/// Appends `record` to the chain. Callers hold the app store lock, which keeps
+/// the read of the last hash and the insert together.
+pub fn append(conn: &Connection, record: &AuditRecord) -> rusqlite::Result<()> {
+    let prev_hash: String = conn
+        .query_row("SELECT hash FROM audit_log ORDER BY seq DESC LIMIT 1", [], |row| row.get(0))
+        .optional()?
+        .unwrap_or_else(|| GENESIS_HASH.to_string());
+
+    let mut entry = AuditEntry {
+        seq: 0,
+        timestamp: super::store::now_millis(),
+        conn_id: record.conn_id.to_string(),
+        connection_name: record.connection_name.to_string(),
+        host: record.host.map(str::to_string),
+        operation: record.operation.to_string(),
+        sql: record.sql.to_string(),
+        rows_affected: record.result.ok(),
+        outcome: if record.result.is_ok() { AuditOutcome::Success } else { AuditOutcome::Failure },
+        error: record.result.err().map(str::to_string),
+        prev_hash,
+        hash: String::new(),
+    };
+    entry.hash = entry_hash(&entry);
+
+    conn.execute(
+        "INSERT INTO audit_log (timestamp, conn_id, connection_name, host, operation, sql, \
+                                rows_affected, outcome, error, prev_hash, hash) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            entry.timestamp,
+            entry.conn_id,
+            entry.connection_name,
+            entry.host,
+            entry.operation,
+            entry.sql,
+            entry.rows_affected.map(|n| n as i64),
+            outcome_str(entry.outcome),
+            entry.error,
+            entry.prev_hash,
+            entry.hash,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Newest first.
+pub fn list(conn: &Connection, filter: &AuditFilter) -> rusqlite::Result<Vec<AuditEntry>> {
+    let sql = format!(
+        "SELECT {} FROM audit_log \
+         WHERE (?1 IS NULL OR conn_id = ?1) \
+           AND (?2 IS NULL OR timestamp >= ?2) \
+           AND (?3 IS NULL OR timestamp < ?3) \
+           AND (?4 IS NULL OR outcome = ?4) \
+         ORDER BY seq DESC \
+         LIMIT ?5 OFFSET ?6",
+        COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(
+        params![
+            filter.conn_id,
+            filter.since,
+            filter.until,
+            filter.outcome.map(outcome_str),
+            filter.limit.map_or(-1, i64::from),
+            filter.offset.unwrap_or(0),
+        ],
+        from_row,
+    )?;
+    rows.collect()
+}
+
+/// Recomputes every hash in order and checks each entry links to the one before.
+pub fn verify(conn: &Connection) -> rusqlite::Result<AuditVerification> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM audit_log ORDER BY seq", COLUMNS))?;
+    let mut rows = stmt.query([])?;
+
+    let mut expected_prev = GENESIS_HASH.to_string();
+    let mut checked = 0;
+    while let Some(row) = rows.next()? {
+        let entry = from_row(row)?;
+        if entry.prev_hash != expected_prev || entry_hash(&entry) != entry.hash {
+            return Ok(AuditVerification { valid: false, checked, broken_at: Some(entry.seq) });
+        }
+        expected_prev = entry.hash;
+        checked += 1;
+    }
+    Ok(AuditVerification { valid: true, checked, broken_at: None })
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Renders `entries` oldest first, so an export reads in chain order.
+pub fn render(entries: &[AuditEntry], format: AuditExportFormat) -> Result<String, String> {
+    let mut out = String::new();
+    match format {
+        AuditExportFormat::Csv => {
+            out.push_str(&COLUMNS.split(',').map(str::trim).collect::<Vec<_>>().join(","));
+            out.push('\n');
+            for e in entries.iter().rev() {
+                let fields = [
+                    e.seq.to_string(),
+                    e.timestamp.to_string(),
+                    csv_field(&e.conn_id),
+                    csv_field(&e.connection_name),
+                    csv_field(e.host.as_deref().unwrap_or("")),
+                    csv_field(&e.operation),
+                    csv_field(&e.sql),
+                    e.rows_affected.map(|n| n.to_string()).unwrap_or_default(),
+                    outcome_str(e.outcome).to_string(),
+                    csv_field(e.error.as_deref().unwrap_or("")),
+                    e.prev_hash.clone(),
+                    e.hash.clone(),
+                ];
+                out.push_str(&fields.join(","));
+                out.push('\n');
+            }
+        }
+        AuditExportFormat::Jsonl => {
+            for e in entries.iter().rev() {
+                out.push_str(&serde_json::to_string(e).map_err(|e| format!("Export error: {}", e))?);
+                out.push('\n');
+            }
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,59 @@
+use super::connection::DatabaseConnection;
+use super::dialect::{placeholder, quote_ident, quote_table};
+use super::types::{DatabaseType, DbError, DbErrorKind, ExecuteResult, KeyValue, QueryParam};
+
+/// `WHERE` clause matching `key`, with placeholders numbered from `first_index`.
+fn key_predicate(db_type: DatabaseType, key: &[KeyValue], first_index: usize) -> Result<(String, Vec<QueryParam>), DbError> {
+    if key.is_empty() {
+        return Err(DbError::invalid_input("A row key is required"));
+    }
+    let clause = key.iter()
+        .enumerate()
+        .map(|(i, k)| format!("{} = {}", quote_ident(db_type, &k.column), placeholder(db_type, first_index + i)))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    Ok((clause, key.iter().map(|k| k.value.clone()).collect()))
+}
+
+/// Writes the full value of one cell to `path`; returns the number of bytes written.
+pub fn export(conn: &dyn DatabaseConnection, table: &str, column: &str, key: &[KeyValue], path: &str) -> Result<usize, DbError> {
+    let db_type = conn.info().db_type;
+    let (predicate, params) = key_predicate(db_type, key, 1)?;
+    let sql = format!(
+        "SELECT {} FROM {} WHERE {}",
+        quote_ident(db_type, column),
+        quote_table(db_type, table),
+        predicate,
+    );
+    let bytes = conn.query_bytes(&sql, &params)?.ok_or_else(|| DbError::new(DbErrorKind::NotFound, "Cell is NULL"))?;
+    std::fs::write(path, &bytes).map_err(|e| format!("Write error: {}", e))?;
+    Ok(bytes.len())
+}
+
+/// Replaces one cell with the contents of `path`. The key must match exactly one row.
+pub fn import(conn: &dyn DatabaseConnection, table: &str, column: &str, key: &[KeyValue], path: &str) -> Result<ExecuteResult, DbError> {
+    let db_type = conn.info().db_type;
+    let bytes = std::fs::read(path).map_err(|e| format!("Read error: {}", e))?;
+
+    let (predicate, params) = key_predicate(db_type, key, 1)?;
+    let count_sql = format!("SELECT COUNT(*) FROM {} WHERE {}", quote_table(db_type, table), predicate);
+    let count = conn.query_with_params(&count_sql, &params)?
+        .rows.first()
+        .and_then(|row| row.first()?.as_ref()?.as_text()?.parse::<u64>().ok())
+        .unwrap_or(0);
+    if count != 1 {
+        return Err(DbError::invalid_input(format!("Row key must match exactly one row, matched {}", count)));
+    }
+
+    let (predicate, key_params) = key_predicate(db_type, key, 2)?;
+    let sql = format!(
+        "UPDATE {} SET {} = {} WHERE {}",
+        quote_table(db_type, table),
+        quote_ident(db_type, column),
+        placeholder(db_type, 1),
+        predicate,
+    );
+    let mut params = vec![QueryParam::Bytes(bytes)];
+    params.extend(key_params);
+    conn.execute_with_params(&sql, &params)
+}
@@ -0,0 +1,204 @@
+use super::connection::DatabaseConnection;
+use super::dialect::{placeholder, quote_ident, quote_table};
+use super::types::*;
+
+/// Filtered counts stop here and report an estimate instead.
+const COUNT_CAP: u64 = 100_000;
+
+struct Sql {
+    text: String,
+    params: Vec<QueryParam>,
+    db_type: DatabaseType,
+}
+
+impl Sql {
+    fn new(db_type: DatabaseType) -> Self {
+        Sql { text: String::new(), params: Vec::new(), db_type }
+    }
+
+    fn bind(&mut self, value: QueryParam) -> String {
+        self.params.push(value);
+        placeholder(self.db_type, self.params.len())
+    }
+}
+
+fn where_clause(sql: &mut Sql, filters: &[BrowseFilter]) -> Result<String, DbError> {
+    let mut conditions = Vec::new();
+    for filter in filters {
+        let column = quote_ident(sql.db_type, &filter.column);
+        conditions.push(match &filter.op {
+            FilterOp::Eq(v) => format!("{} = {}", column, sql.bind(v.clone())),
+            FilterOp::Like(pattern) => format!("{} LIKE {}", column, sql.bind(QueryParam::Text(pattern.clone()))),
+            FilterOp::In(values) if values.is_empty() => return Err(DbError::invalid_input(format!("Empty IN list for {}", filter.column))),
+            FilterOp::In(values) => {
+                let list: Vec<String> = values.iter().map(|v| sql.bind(v.clone())).collect();
+                format!("{} IN ({})", column, list.join(", "))
+            }
+            FilterOp::Between(low, high) => {
+                let low = sql.bind(low.clone());
+                format!("{} BETWEEN {} AND {}", column, low, sql.bind(high.clone()))
+            }
+            FilterOp::IsNull => format!("{} IS NULL", column),
+            FilterOp::IsNotNull => format!("{} IS NOT NULL", column),
+        });
+    }
+    Ok(conditions.join(" AND "))
+}
+
+/// Whether `db_type` sorts NULLs before other values in this direction:
+/// PostgreSQL treats NULL as the largest value, MySQL and SQLite as the
+/// smallest, and DuckDB puts them last either way.
+fn nulls_first(db_type: DatabaseType, descending: bool) -> bool {
+    match db_type {
+        DatabaseType::Postgres => descending,
+        DatabaseType::Mysql | DatabaseType::Sqlite => !descending,
+        DatabaseType::Duckdb => false,
+    }
+}
+
+/// `(a > ?) OR (a = ? AND b > ?) ...`, honoring each key's direction. Spelled
+/// out rather than as a row comparison so mixed directions work everywhere.
+/// NULL cursor values and NULLs after the cursor are matched with `IS NULL`
+/// tests placed where the dialect sorts NULLs.
+fn keyset_condition(sql: &mut Sql, sort: &[SortKey], after: &[QueryParam]) -> Result<String, DbError> {
+    if sort.is_empty() {
+        return Err(DbError::invalid_input("Keyset paging needs at least one sort column"));
+    }
+    if after.len() != sort.len() {
+        return Err(DbError::invalid_input("Keyset cursor does not match the sort columns"));
+    }
+    let mut branches = Vec::new();
+    for i in 0..sort.len() {
+        let mut terms = Vec::new();
+        for (key, value) in sort.iter().zip(after).take(i) {
+            let column = quote_ident(sql.db_type, &key.column);
+            terms.push(match value {
+                QueryParam::Null => format!("{} IS NULL", column),
+                value => format!("{} = {}", column, sql.bind(value.clone())),
+            });
+        }
+        let column = quote_ident(sql.db_type, &sort[i].column);
+        let op = if sort[i].descending { "<" } else { ">" };
+        let first = nulls_first(sql.db_type, sort[i].descending);
+        terms.push(match &after[i] {
+            // Nothing follows a NULL when NULLs sort last.
+            QueryParam::Null if !first => continue,
+            QueryParam::Null => format!("{} IS NOT NULL", column),
+            value if first => format!("{} {} {}", column, op, sql.bind(value.clone())),
+            value => format!("({} {} {} OR {} IS NULL)", column, op, sql.bind(value.clone()), column),
+        });
+        branches.push(format!("({})", terms.join(" AND ")));
+    }
+    if branches.is_empty() {
+        return Ok("1 = 0".to_string());
+    }
+    Ok(format!("({})", branches.join(" OR ")))
+}
+
+fn row_count_estimate(conn: &dyn DatabaseConnection, table: &str) -> Option<u64> {
+    let db_type = conn.info().db_type;
+    let result = match db_type {
+        // -1 until the table has been analyzed.
+        DatabaseType::Postgres => conn.query_with_params(
+            "SELECT reltuples::bigint FROM pg_class WHERE oid = to_regclass($1) AND reltuples >= 0",
+            &[QueryParam::Text(quote_table(db_type, table))],
+        ),
+        DatabaseType::Mysql => {
+            let (schema, name) = match table.split_once('.') {
+                Some((s, n)) => (QueryParam::Text(s.into()), n),
+                None => (QueryParam::Null, table),
+            };
+            conn.query_with_params(
+                "SELECT table_rows FROM information_schema.tables \
+                 WHERE table_schema = COALESCE(?, DATABASE()) AND table_name = ?",
+                &[schema, QueryParam::Text(name.into())],
+            )
+        }
+        DatabaseType::Sqlite | DatabaseType::Duckdb => return None,
+    };
+    first_u64(&result.ok()?)
+}
+
+fn first_u64(result: &QueryResult) -> Option<u64> {
+    result.rows.first()?.first()?.as_ref()?.as_text()?.parse().ok()
+}
+
+pub fn browse(conn: &dyn DatabaseConnection, spec: &BrowseSpec) -> Result<BrowseResult, DbError> {
+    let db_type = conn.info().db_type;
+    let table = quote_table(db_type, &spec.table);
+    let projection = if spec.columns.is_empty() {
+        "*".to_string()
+    } else {
+        spec.columns.iter().map(|c| quote_ident(db_type, c)).collect::<Vec<_>>().join(", ")
+    };
+
+    let mut sql = Sql::new(db_type);
+    let filters = where_clause(&mut sql, &spec.filters)?;
+    let filter_params = sql.params.clone();
+
+    let (limit, offset, after) = match &spec.page {
+        BrowsePage::Offset { offset, limit } => (*limit, *offset, None),
+        BrowsePage::Keyset { after, limit } => (*limit, 0, Some(after.as_deref())),
+    };
+    let limit = limit.clamp(1, QueryLimits::default().max_rows.unwrap_or(usize::MAX) as u32);
+
+    if after.is_some() && !spec.columns.is_empty() && !spec.sort.iter().all(|k| spec.columns.contains(&k.column)) {
+        return Err(DbError::invalid_input("Keyset paging needs the sort columns in the projection"));
+    }
+
+    let mut conditions = Vec::new();
+    if !filters.is_empty() {
+        conditions.push(filters.clone());
+    }
+    if let Some(Some(after)) = after {
+        conditions.push(keyset_condition(&mut sql, &spec.sort, after)?);
+    }
+
+    sql.text = format!("SELECT {} FROM {}", projection, table);
+    if !conditions.is_empty() {
+        sql.text.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+    }
+    if !spec.sort.is_empty() {
+        let order: Vec<String> = spec.sort.iter()
+            .map(|k| format!("{}{}", quote_ident(db_type, &k.column), if k.descending { " DESC" } else { "" }))
+            .collect();
+        sql.text.push_str(&format!(" ORDER BY {}", order.join(", ")));
+    }
+    sql.text.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset));
+
+    let result = conn.query_limited(&sql.text, &sql.params, &QueryLimits { max_rows: None, ..QueryLimits::default() })?;
+
+    let next_cursor = match after {
+        Some(_) if result.rows.len() as u32 == limit => result.rows.last().map(|row| {
+            spec.sort.iter()
+                .map(|k| {
+                    let i = result.columns.iter().position(|c| *c == k.column);
+                    match i.and_then(|i| row[i].as_ref()).and_then(CellValue::as_text) {
+                        Some(text) => QueryParam::Text(text.to_string()),
+                        None => QueryParam::Null,
+                    }
+                })
+                .collect()
+        }),
+        _ => None,
+    };
+
+    // Statistics are fine for an unfiltered table; with filters, count up to the cap.
+    let (total, total_is_estimate) = match row_count_estimate(conn, &spec.table).filter(|_| filters.is_empty()) {
+        Some(estimate) => (Some(estimate), true),
+        None => {
+            let mut count_sql = format!("SELECT 1 FROM {}", table);
+            if !filters.is_empty() {
+                count_sql.push_str(&format!(" WHERE {}", filters));
+            }
+            let count_sql = format!("SELECT COUNT(*) FROM ({} LIMIT {}) capped", count_sql, COUNT_CAP + 1);
+            match conn.query_with_params(&count_sql, &filter_params).ok().as_ref().and_then(first_u64) {
+                Some(n) if n > COUNT_CAP => (Some(COUNT_CAP), true),
+                Some(n) => (Some(n), false),
+                None => (None, true),
+            }
+        }
+    };
+
+    Ok(BrowseResult { result, total, total_is_estimate, next_cursor })
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use super::types::*;
+
+/// Per-connection schema catalogs, kept until DDL or a manual refresh invalidates them.
+#[derive(Default)]
+pub struct CatalogCache {
+    catalogs: Mutex<HashMap<String, Arc<Catalog>>>,
+}
+
+impl CatalogCache {
+    pub fn get(&self, conn_id: &str) -> Option<Arc<Catalog>> {
+        self.catalogs.lock().ok()?.get(conn_id).cloned()
+    }
+
+    pub fn put(&self, conn_id: &str, catalog: Catalog) -> Arc<Catalog> {
+        let catalog = Arc::new(catalog);
+        if let Ok(mut catalogs) = self.catalogs.lock() {
+            catalogs.insert(conn_id.to_string(), catalog.clone());
+        }
+        catalog
+    }
+
+    pub fn invalidate(&self, conn_id: &str) {
+        if let Ok(mut catalogs) = self.catalogs.lock() {
+            catalogs.remove(conn_id);
+        }
+    }
+}
+
+/// Statements that can change what the catalog describes.
+pub fn is_ddl(sql: &str) -> bool {
+    let first = sql.split_whitespace().next().unwrap_or("").to_uppercase();
+    matches!(first.as_str(), "CREATE" | "ALTER" | "DROP" | "RENAME" | "COMMENT")
+}
+
+/// Assembles a catalog from flat per-column / per-FK rows, keeping first-seen table order.
+#[derive(Default)]
+pub struct CatalogBuilder {
+    tables: Vec<CatalogTable>,
+    index: HashMap<(Option<String>, String), usize>,
+}
+
+impl CatalogBuilder {
+    fn table(&mut self, schema: Option<&str>, table: &str) -> &mut CatalogTable {
+        let key = (schema.map(str::to_string), table.to_string());
+        let idx = match self.index.get(&key) {
+            Some(idx) => *idx,
+            None => {
+                self.tables.push(CatalogTable {
+                    name: match schema {
+                        Some(schema) => format!("{}.{}", schema, table),
+                        None => table.to_string(),
+                    },
+                    schema: key.0.clone(),
+                    columns: Vec::new(),
+                    foreign_keys: Vec::new(),
+                });
+                self.index.insert(key, self.tables.len() - 1);
+                self.tables.len() - 1
+            }
+        };
+        &mut self.tables[idx]
+    }
+
+    pub fn add_column(&mut self, schema: Option<&str>, table: &str, column: ColumnInfo) {
+        self.table(schema, table).columns.push(column);
+    }
+
+    /// Adds a foreign key; rows listing one column pair at a time are merged
+    /// into a composite key when they share the constraint name.
+    pub fn add_foreign_key(&mut self, schema: Option<&str>, table: &str, fk: ForeignKeyInfo) {
+        let t = self.table(schema, table);
+        match t.foreign_keys.last_mut() {
+            Some(last) if fk.name.is_some() && last.name == fk.name && last.referenced_table == fk.referenced_table => {
+                last.columns.extend(fk.columns);
+                last.referenced_columns.extend(fk.referenced_columns);
+            }
+            _ => t.foreign_keys.push(fk),
+        }
+    }
+
+    pub fn build(self) -> Catalog {
+        let mut schemas: Vec<String> = self.tables.iter()
+            .filter_map(|t| t.schema.clone())
+            .collect();
+        schemas.sort();
+        schemas.dedup();
+
+        Catalog {
+            schemas,
+            tables: self.tables,
+            loaded_at: super::store::now_millis(),
+        }
+    }
+}
@@ -0,0 +1,480 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use super::connection::{with_connection, ConnectionPool};
+use super::dialect::{placeholder, quote_ident, quote_literal, quote_table};
+use super::generate::MAX_PARAMS;
+use super::masking;
+use super::types::*;
+
+/// Rows read per round trip.
+const PAGE_SIZE: usize = 5000;
+const DEFAULT_SAMPLES: usize = 20;
+
+fn position(columns: &[String], name: &str) -> Option<usize> {
+    columns.iter().position(|c| c.eq_ignore_ascii_case(name))
+}
+
+/// A cell as compared: text as the driver renders it, binary by its full
+/// contents in lowercase hex.
+#[derive(Hash, PartialEq)]
+enum Value {
+    Null,
+    Text(String),
+    Bytes(String),
+}
+
+/// Key values of a row, as text.
+type Key = Vec<String>;
+
+/// One prepared side of the comparison.
+struct Side {
+    label: &'static str,
+    conn_id: String,
+    db_type: DatabaseType,
+    /// `(...) q`, the source wrapped as a subquery.
+    from: String,
+    columns: Vec<String>,
+    /// Positions of the key columns in `columns`.
+    keys: Vec<usize>,
+    /// Binary columns whose values outgrow the preview drivers keep; they are
+    /// also selected in full as hex, after the source's own columns.
+    hexed: Vec<usize>,
+    /// Where the columns come from, for masking.
+    lineage: masking::Lineage,
+    rules: Vec<MaskingRule>,
+}
+
+/// A page of a side, reduced to what the comparison keeps.
+struct Page {
+    rows: Vec<(Key, u64)>,
+    /// The page came back full, so more may follow.
+    full: bool,
+}
+
+/// Progress of one side through its keys.
+#[derive(Default)]
+struct Scan {
+    /// Key of the last row read; the next page starts at it.
+    after: Option<Key>,
+    done: bool,
+    rows: u64,
+    /// Keys read on this side that the other has not reached yet.
+    waiting: BTreeMap<Key, u64>,
+}
+
+/// Keys present on both sides, by outcome.
+struct Tally {
+    matching: u64,
+    different: u64,
+    /// The differing keys kept for samples and sync SQL.
+    different_keys: Vec<Key>,
+    keep_all: bool,
+    samples: usize,
+}
+
+impl Scan {
+    /// Reads this side's next page and matches its keys against those the
+    /// other side has waiting.
+    async fn advance(&mut self, side: &mut Side, pool: &ConnectionPool, compared: &[usize], other: &mut Scan, tally: &mut Tally) -> Result<(), DbError> {
+        let page = side.hashed_page(pool, self.after.as_ref(), compared).await?;
+        let mut previous = self.after.take();
+        // The page starts at the last key already read, seen once more.
+        let skip = usize::from(page.rows.first().map(|(key, _)| key) == previous.as_ref() && previous.is_some());
+        for (key, hash) in page.rows.into_iter().skip(skip) {
+            // Pages are in key order, so a repeated key is adjacent to itself.
+            if previous.as_ref() == Some(&key) {
+                return Err(DbError::invalid_input(format!("Key columns are not unique on the {} side", side.label)));
+            }
+            self.rows += 1;
+            match other.waiting.remove(&key) {
+                Some(other_hash) if other_hash == hash => tally.matching += 1,
+                Some(_) => {
+                    tally.different += 1;
+                    if tally.keep_all || tally.different_keys.len() < tally.samples {
+                        tally.different_keys.push(key.clone());
+                    }
+                }
+                None => {
+                    self.waiting.insert(key.clone(), hash);
+                }
+            }
+            previous = Some(key);
+        }
+        self.done = !page.full;
+        self.after = previous;
+        Ok(())
+    }
+}
+
+/// Rows fetched by key: masked for display, and their compared values.
+struct Fetched {
+    row: Row,
+    values: Vec<Value>,
+}
+
+impl Side {
+    async fn open(
+        pool: &ConnectionPool,
+        source: &CompareSource,
+        key_columns: &[String],
+        rules: Vec<MaskingRule>,
+        label: &'static str,
+    ) -> Result<Side, DbError> {
+        let db_type = with_connection(pool, &source.conn_id, |conn| Ok(conn.info().db_type)).await?;
+        let (inner, lineage) = match (&source.table, &source.sql) {
+            (Some(table), None) => (format!("SELECT * FROM {}", quote_table(db_type, table)), masking::Lineage::of_table(table)),
+            (None, Some(sql)) => {
+                let sql = sql.trim().trim_end_matches(';');
+                (sql.to_string(), masking::Lineage::of_sql(sql, db_type))
+            }
+            _ => return Err(DbError::invalid_input(format!("The {} side needs either a table or a query", label))),
+        };
+        // Wrapped on its own lines so a trailing `--` comment in a query stays inside.
+        let from = format!("(\n{}\n) q", inner);
+        let probe = format!("SELECT * FROM {} LIMIT 0", from);
+        let columns = with_connection(pool, &source.conn_id, move |conn| conn.query(&probe)).await?.columns;
+
+        let keys = key_columns.iter()
+            .map(|k| position(&columns, k)
+                .ok_or_else(|| DbError::invalid_input(format!("Key column {} is missing on the {} side", k, label))))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Side {
+            label,
+            conn_id: source.conn_id.clone(),
+            db_type,
+            from,
+            columns,
+            keys,
+            hexed: Vec::new(),
+            lineage,
+            rules,
+        })
+    }
+
+    fn col(&self, i: usize) -> String {
+        format!("q.{}", quote_ident(self.db_type, &self.columns[i]))
+    }
+
+    /// The source's columns, then the hex of each `hexed` one.
+    fn projection(&self) -> String {
+        let mut projection = vec!["q.*".to_string()];
+        for &i in &self.hexed {
+            projection.push(match self.db_type {
+                DatabaseType::Postgres => format!("encode({}, 'hex')", self.col(i)),
+                DatabaseType::Mysql | DatabaseType::Sqlite | DatabaseType::Duckdb => format!("hex({})", self.col(i)),
+            });
+        }
+        projection.join(", ")
+    }
+
+    /// A page in key order from `after` on, found by the key itself so each
+    /// page costs the same however deep the scan is. `after` is included so
+    /// a repeat of it shows up.
+    async fn page(&self, pool: &ConnectionPool, after: Option<&Key>) -> Result<QueryResult, DbError> {
+        let key_list = |items: Vec<String>| match items.len() {
+            1 => items.concat(),
+            _ => format!("({})", items.join(", ")),
+        };
+        let mut params = Vec::new();
+        let mut sql = format!("SELECT {} FROM {}", self.projection(), self.from);
+        if let Some(after) = after {
+            let placeholders = after.iter().map(|value| {
+                params.push(QueryParam::Text(value.clone()));
+                placeholder(self.db_type, params.len())
+            }).collect();
+            let columns = self.keys.iter().map(|&i| self.col(i)).collect();
+            sql.push_str(&format!(" WHERE {} >= {}", key_list(columns), key_list(placeholders)));
+        }
+        let order = self.keys.iter().map(|&i| self.col(i)).collect::<Vec<_>>().join(", ");
+        sql.push_str(&format!(" ORDER BY {} LIMIT {}", order, PAGE_SIZE));
+        with_connection(pool, &self.conn_id, move |conn| conn.query_with_params(&sql, &params)).await
+    }
+
+    fn key_of(&self, row: &Row) -> Result<Key, DbError> {
+        self.keys.iter().map(|&i| match &row[i] {
+            Some(CellValue::Text(text)) => Ok(text.clone()),
+            None => Err(DbError::invalid_input(format!("Key column {} has NULL values on the {} side", self.columns[i], self.label))),
+            Some(CellValue::Tagged(_)) => Err(DbError::invalid_input(format!("Key column {} is binary on the {} side", self.columns[i], self.label))),
+        }).collect()
+    }
+
+    fn key_values(&self, key: &Key) -> Vec<KeyValue> {
+        self.keys.iter().zip(key).map(|(&i, value)| KeyValue {
+            column: self.columns[i].clone(),
+            value: QueryParam::Text(value.clone()),
+        }).collect()
+    }
+
+    /// The compared value of column `i`; `Err(i)` for a binary value longer
+    /// than its preview while the column is not yet `hexed`.
+    fn value(&self, row: &Row, i: usize) -> Result<Value, usize> {
+        if let Some(k) = self.hexed.iter().position(|&h| h == i) {
+            return Ok(match &row[self.columns.len() + k] {
+                Some(CellValue::Text(hex)) => Value::Bytes(hex.to_lowercase()),
+                _ => Value::Null,
+            });
+        }
+        match &row[i] {
+            None => Ok(Value::Null),
+            Some(CellValue::Text(text)) => Ok(Value::Text(text.clone())),
+            Some(CellValue::Tagged(TaggedValue::Binary { length, preview_hex })) if preview_hex.len() == length * 2 => {
+                Ok(Value::Bytes(preview_hex.clone()))
+            }
+            Some(CellValue::Tagged(_)) => Err(i),
+        }
+    }
+
+    fn values(&self, row: &Row, compared: &[usize]) -> Result<Vec<Value>, usize> {
+        compared.iter().map(|&i| self.value(row, i)).collect()
+    }
+
+    /// Reads the page after `after` and hashes the `compared` columns of each
+    /// row. A binary column found truncated is added to `hexed` and the page
+    /// read again; values hashed before are unaffected, since whole values
+    /// hash the same either way.
+    async fn hashed_page(&mut self, pool: &ConnectionPool, after: Option<&Key>, compared: &[usize]) -> Result<Page, DbError> {
+        'read: loop {
+            let page = self.page(pool, after).await?;
+            let mut rows = Vec::with_capacity(page.rows.len());
+            for row in &page.rows {
+                let values = match self.values(row, compared) {
+                    Ok(values) => values,
+                    Err(i) => {
+                        self.hexed.push(i);
+                        continue 'read;
+                    }
+                };
+                let mut hasher = DefaultHasher::new();
+                values.hash(&mut hasher);
+                rows.push((self.key_of(row)?, hasher.finish()));
+            }
+            return Ok(Page { rows, full: page.rows.len() == PAGE_SIZE });
+        }
+    }
+
+    /// Rows with the `wanted` keys, fetched by key in batches.
+    async fn rows(&mut self, pool: &ConnectionPool, wanted: &[&Key], compared: &[usize]) -> Result<HashMap<Key, Fetched>, DbError> {
+        let mut found = HashMap::new();
+        let per_batch = (MAX_PARAMS / self.keys.len()).max(1);
+        for batch in wanted.chunks(per_batch) {
+            let mut params = Vec::new();
+            let predicates: Vec<String> = batch.iter().map(|key| {
+                let terms: Vec<String> = self.keys.iter().zip(key.iter()).map(|(&i, value)| {
+                    params.push(QueryParam::Text(value.clone()));
+                    format!("{} = {}", self.col(i), placeholder(self.db_type, params.len()))
+                }).collect();
+                format!("({})", terms.join(" AND "))
+            }).collect();
+            let predicates = predicates.join(" OR ");
+            let mut page = loop {
+                let sql = format!("SELECT {} FROM {} WHERE {}", self.projection(), self.from, predicates);
+                let params = params.clone();
+                let page = with_connection(pool, &self.conn_id, move |conn| conn.query_with_params(&sql, &params)).await?;
+                match page.rows.iter().find_map(|row| self.values(row, compared).err()) {
+                    Some(i) => self.hexed.push(i),
+                    None => break page,
+                }
+            };
+            // Keys and values are taken before masking, which may cover them too.
+            let mut keyed = Vec::with_capacity(page.rows.len());
+            for row in &mut page.rows {
+                keyed.push((self.key_of(row)?, self.values(row, compared).unwrap_or_default()));
+                row.truncate(self.columns.len());
+            }
+            page.columns.truncate(self.columns.len());
+            masking::apply(&self.rules, &mut page, &self.lineage);
+            for ((key, values), row) in keyed.into_iter().zip(page.rows) {
+                found.insert(key, Fetched { row, values });
+            }
+        }
+        Ok(found)
+    }
+}
+
+fn literal(db_type: DatabaseType, cell: &Option<CellValue>) -> Result<String, DbError> {
+    match cell {
+        None => Ok("NULL".into()),
+        Some(CellValue::Text(text)) => Ok(quote_literal(db_type, text)),
+        Some(CellValue::Tagged(_)) => Err(DbError::invalid_input("Sync SQL cannot include binary values")),
+    }
+}
+
+/// `WHERE` clause for the key of `row`, read at `positions`, on the columns `names`.
+fn key_predicate(db_type: DatabaseType, names: &[&str], row: &Row, positions: &[usize]) -> Result<String, DbError> {
+    let parts = names.iter().zip(positions).map(|(name, &i)| {
+        let col = quote_ident(db_type, name);
+        Ok(match &row[i] {
+            None => format!("{} IS NULL", col),
+            cell => format!("{} = {}", col, literal(db_type, cell)?),
+        })
+    }).collect::<Result<Vec<_>, DbError>>()?;
+    Ok(parts.join(" AND "))
+}
+
+/// Compares the rows of `left` and `right` by `key_columns`. Both sides are
+/// read in key order, a page at a time with keyset paging, and reduced to one
+/// hash per row. Rows are matched by key value as the pages arrive: a key
+/// waits only until the other side reaches it, so while both connections
+/// order keys alike just about one page per side is held. Differing
+/// collations make more keys wait but do not change the outcome.
+/// Rows for samples and sync SQL are fetched by key in a second pass, only
+/// when some differ. Values compare as the drivers render them as text;
+/// binary values by their full contents.
+pub async fn compare(
+    pool: &ConnectionPool,
+    left: &CompareSource,
+    left_rules: Vec<MaskingRule>,
+    right: &CompareSource,
+    right_rules: Vec<MaskingRule>,
+    key_columns: &[String],
+    options: &CompareDataOptions,
+) -> Result<DataComparison, DbError> {
+    let start = std::time::Instant::now();
+    if key_columns.is_empty() {
+        return Err(DbError::invalid_input("At least one key column is required"));
+    }
+    let target = match (options.sync_sql, &right.table) {
+        (false, _) => None,
+        (true, Some(table)) => Some(table.clone()),
+        (true, None) => return Err(DbError::invalid_input("Sync SQL needs a table on the right side")),
+    };
+    let mut left = Side::open(pool, left, key_columns, left_rules, "left").await?;
+    let mut right = Side::open(pool, right, key_columns, right_rules, "right").await?;
+
+    let is_key = |c: &str| key_columns.iter().any(|k| k.eq_ignore_ascii_case(c));
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+    let mut left_only_columns = Vec::new();
+    for (i, column) in left.columns.iter().enumerate().filter(|(_, c)| !is_key(c)) {
+        match position(&right.columns, column) {
+            Some(j) => pairs.push((i, j)),
+            None => left_only_columns.push(column.clone()),
+        }
+    }
+    let right_only_columns: Vec<String> = right.columns.iter()
+        .filter(|c| !is_key(c) && position(&left.columns, c).is_none())
+        .cloned()
+        .collect();
+    // Sync SQL built from masked values would write them to the right side.
+    let masked_columns = masking::masked(&left.rules, &left.columns, &left.lineage);
+    let masked = left.keys.iter().chain(pairs.iter().map(|(i, _)| i)).any(|&i| masked_columns[i]);
+    if target.is_some() && masked {
+        return Err(DbError::invalid_input("Sync SQL is unavailable while masking rules apply to compared columns"));
+    }
+
+    let left_compared: Vec<usize> = pairs.iter().map(|(i, _)| *i).collect();
+    let right_compared: Vec<usize> = pairs.iter().map(|(_, j)| *j).collect();
+
+    // Sync SQL needs every differing key; samples only the first few.
+    let samples = options.max_samples.unwrap_or(DEFAULT_SAMPLES);
+
+    let mut tally = Tally {
+        matching: 0,
+        different: 0,
+        different_keys: Vec::new(),
+        keep_all: target.is_some(),
+        samples,
+    };
+    let (mut left_scan, mut right_scan) = (Scan::default(), Scan::default());
+    while !(left_scan.done && right_scan.done) {
+        if !left_scan.done {
+            left_scan.advance(&mut left, pool, &left_compared, &mut right_scan, &mut tally).await?;
+        }
+        if !right_scan.done {
+            right_scan.advance(&mut right, pool, &right_compared, &mut left_scan, &mut tally).await?;
+        }
+    }
+    // Whatever still waits has no counterpart.
+    let only_left: Vec<&Key> = left_scan.waiting.keys().collect();
+    let only_right: Vec<&Key> = right_scan.waiting.keys().collect();
+    let mut different = tally.different_keys;
+    different.sort();
+
+    let kept = |keys: &[&Key]| -> usize {
+        if target.is_some() { keys.len() } else { keys.len().min(samples) }
+    };
+    let wanted_left: Vec<&Key> = only_left[..kept(&only_left)].iter().copied().chain(&different).collect();
+    let wanted_right: Vec<&Key> = only_right[..kept(&only_right)].iter().copied().chain(&different).collect();
+    let left_fetched = left.rows(pool, &wanted_left, &left_compared).await?;
+    let right_fetched = right.rows(pool, &wanted_right, &right_compared).await?;
+
+    let left_positions: Vec<usize> = left.keys.iter().copied().chain(left_compared.iter().copied()).collect();
+    let right_positions: Vec<usize> = right.keys.iter().copied().chain(right_compared.iter().copied()).collect();
+    let sample = |side: &Side, key: &Key, fetched: &Fetched, positions: &[usize]| RowSample {
+        key: side.key_values(key),
+        values: positions.iter().map(|&i| fetched.row[i].clone()).collect(),
+    };
+    // Positions in `pairs` whose values differ.
+    let changed = |l: &Fetched, r: &Fetched| -> Vec<(usize, usize)> {
+        pairs.iter().copied().enumerate()
+            .filter(|&(k, _)| l.values[k] != r.values[k])
+            .map(|(_, pair)| pair)
+            .collect()
+    };
+
+    let only_left_samples = only_left.iter().take(samples)
+        .filter_map(|k| Some((*k, left_fetched.get(*k)?)))
+        .map(|(key, fetched)| sample(&left, key, fetched, &left_positions))
+        .collect();
+    let only_right_samples = only_right.iter().take(samples)
+        .filter_map(|k| Some((*k, right_fetched.get(*k)?)))
+        .map(|(key, fetched)| sample(&right, key, fetched, &right_positions))
+        .collect();
+    let different_samples = different.iter().take(samples)
+        .filter_map(|k| Some((k, left_fetched.get(k)?, right_fetched.get(k)?)))
+        .map(|(key, l, r)| RowDiff {
+            key: left.key_values(key),
+            columns: changed(l, r).into_iter().map(|(i, j)| CellDiff {
+                column: left.columns[i].clone(),
+                left: l.row[i].clone(),
+                right: r.row[j].clone(),
+            }).collect(),
+        })
+        .collect();
+
+    let sync_sql = match target {
+        None => None,
+        Some(table) => {
+            let db_type = right.db_type;
+            let t = quote_table(db_type, &table);
+            let key_names: Vec<&str> = right.keys.iter().map(|&i| right.columns[i].as_str()).collect();
+            let mut statements = Vec::new();
+            for fetched in only_left.iter().filter_map(|k| left_fetched.get(*k)) {
+                let names: Vec<String> = right_positions.iter().map(|&i| quote_ident(db_type, &right.columns[i])).collect();
+                let values = left_positions.iter().map(|&i| literal(db_type, &fetched.row[i])).collect::<Result<Vec<_>, _>>()?;
+                statements.push(format!("INSERT INTO {} ({}) VALUES ({})", t, names.join(", "), values.join(", ")));
+            }
+            for (l, r) in different.iter().filter_map(|k| Some((left_fetched.get(k)?, right_fetched.get(k)?))) {
+                let assignments = changed(l, r).into_iter()
+                    .map(|(i, j)| Ok(format!("{} = {}", quote_ident(db_type, &right.columns[j]), literal(db_type, &l.row[i])?)))
+                    .collect::<Result<Vec<_>, DbError>>()?;
+                if !assignments.is_empty() {
+                    let predicate = key_predicate(db_type, &key_names, &l.row, &left.keys)?;
+                    statements.push(format!("UPDATE {} SET {} WHERE {}", t, assignments.join(", "), predicate));
+                }
+            }
+            for fetched in only_right.iter().filter_map(|k| right_fetched.get(*k)) {
+                statements.push(format!("DELETE FROM {} WHERE {}", t, key_predicate(db_type, &key_names, &fetched.row, &right.keys)?));
+            }
+            Some(statements)
+        }
+    };
+
+    Ok(DataComparison {
+        columns: left_positions.iter().map(|&i| left.columns[i].clone()).collect(),
+        left_only_columns,
+        right_only_columns,
+        left_rows: left_scan.rows,
+        right_rows: right_scan.rows,
+        matching: tally.matching,
+        only_left: only_left.len() as u64,
+        only_right: only_right.len() as u64,
+        different: tally.different,
+        only_left_samples,
+        only_right_samples,
+        different_samples,
+        sync_sql,
+        execution_time_ms: start.elapsed().as_millis() as u64,
+    })
+}
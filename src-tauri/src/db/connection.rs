@@ -1,25 +1,149 @@
 use std::collections::HashMap;
-use std::sync::Mutex;
-use super::types::{ConnectionConfig, ConnectionInfo, DatabaseType, QueryResult, ExecuteResult, TableInfo, ColumnInfo};
+use std::sync::{Arc, Mutex};
+use super::types::{ConnectionConfig, ConnectionInfo, DatabaseType, QueryResult, ExecuteResult, TableInfo, ColumnInfo, QueryParam, QueryLimits, ResultSet, Catalog, DatabaseList, DbNotification, DbError, DbErrorKind};
 
-pub type ConnectionPool = Mutex<HashMap<String, Box<dyn DatabaseConnection + Send>>>;
+pub type SharedConnection = Arc<Mutex<Box<dyn DatabaseConnection + Send>>>;
+pub type NotificationReceiver = tokio::sync::mpsc::UnboundedReceiver<DbNotification>;
+
+/// Open connections by id. The map lock is only held to look a connection up;
+/// each connection has its own lock, so queries on different connections run
+/// in parallel.
+#[derive(Default)]
+pub struct ConnectionPool {
+    connections: Mutex<HashMap<String, SharedConnection>>,
+}
+
+impl ConnectionPool {
+    pub fn get(&self, conn_id: &str) -> Result<SharedConnection, DbError> {
+        let connections = self.connections.lock().map_err(|_| "Lock error")?;
+        connections.get(conn_id)
+            .cloned()
+            .ok_or_else(|| DbError::new(DbErrorKind::Connection, "Connection not found"))
+    }
+
+    pub fn insert(&self, conn_id: String, conn: Box<dyn DatabaseConnection + Send>) -> Option<SharedConnection> {
+        let mut connections = self.connections.lock().ok()?;
+        connections.insert(conn_id, Arc::new(Mutex::new(conn)))
+    }
+
+    pub fn remove(&self, conn_id: &str) -> Option<SharedConnection> {
+        self.connections.lock().ok()?.remove(conn_id)
+    }
+}
+
+/// Runs blocking driver work for one connection on the shared runtime's
+/// blocking pool, holding only that connection's lock.
+pub async fn with_connection<T, F>(pool: &ConnectionPool, conn_id: &str, f: F) -> Result<T, DbError>
+where
+    F: FnOnce(&dyn DatabaseConnection) -> Result<T, DbError> + Send + 'static,
+    T: Send + 'static,
+{
+    let conn = pool.get(conn_id)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = conn.lock().map_err(|_| "Lock error")?;
+        f(conn.as_ref())
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+/// Blocking work that isn't tied to a pooled connection (e.g. opening one).
+pub async fn run_blocking<T, F>(f: F) -> Result<T, DbError>
+where
+    F: FnOnce() -> Result<T, DbError> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
 
 pub trait DatabaseConnection {
     fn info(&self) -> ConnectionInfo;
-    fn query(&self, sql: &str) -> Result<QueryResult, String>;
-    fn execute(&self, sql: &str) -> Result<ExecuteResult, String>;
-    fn get_tables(&self) -> Result<Vec<TableInfo>, String>;
-    fn get_columns(&self, table: &str) -> Result<Vec<ColumnInfo>, String>;
+    /// Runs a query, stopping once `max_rows` rows have been read. Cell size
+    /// limits are applied by the caller.
+    fn query_limited(&self, sql: &str, params: &[QueryParam], limits: &QueryLimits) -> Result<QueryResult, DbError>;
+    fn execute_with_params(&self, sql: &str, params: &[QueryParam]) -> Result<ExecuteResult, DbError>;
+    fn query(&self, sql: &str) -> Result<QueryResult, DbError> {
+        self.query_with_params(sql, &[])
+    }
+    fn execute(&self, sql: &str) -> Result<ExecuteResult, DbError> {
+        self.execute_with_params(sql, &[])
+    }
+    fn query_with_params(&self, sql: &str, params: &[QueryParam]) -> Result<QueryResult, DbError> {
+        self.query_limited(sql, params, &QueryLimits::UNLIMITED)
+    }
+    /// Runs each statement of a batch under `limits` and returns the result
+    /// sets they produce, in order. Backends whose statements only ever return
+    /// one set get one per statement.
+    fn query_multi(&self, statements: &[&str], limits: &QueryLimits) -> Result<Vec<ResultSet>, DbError> {
+        statements.iter().enumerate()
+            .map(|(statement, sql)| Ok(ResultSet {
+                statement,
+                result: self.query_limited(sql, &[], limits)?,
+                rows_affected: 0,
+            }))
+            .collect()
+    }
+    /// Runs `statements` in one transaction, rolling back on the first failure.
+    /// `rows_affected` is summed over all statements.
+    fn execute_in_transaction(&self, statements: &[(String, Vec<QueryParam>)]) -> Result<ExecuteResult, DbError> {
+        let start = std::time::Instant::now();
+        self.execute("BEGIN")?;
+        let mut rows_affected = 0;
+        for (sql, params) in statements {
+            match self.execute_with_params(sql, params) {
+                Ok(result) => rows_affected += result.rows_affected,
+                Err(e) => {
+                    let _ = self.execute("ROLLBACK");
+                    return Err(e);
+                }
+            }
+        }
+        self.execute("COMMIT")?;
+        Ok(ExecuteResult {
+            rows_affected,
+            last_insert_id: None,
+            execution_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+    /// First column of the first row as raw bytes; `None` for SQL NULL.
+    fn query_bytes(&self, sql: &str, params: &[QueryParam]) -> Result<Option<Vec<u8>>, DbError>;
+    fn get_tables(&self) -> Result<Vec<TableInfo>, DbError>;
+    fn get_columns(&self, table: &str) -> Result<Vec<ColumnInfo>, DbError>;
+    /// Schemas, tables, columns and foreign keys in a handful of bulk queries.
+    fn get_catalog(&self) -> Result<Catalog, DbError>;
+    /// Databases on the server and, where the backend has them, the schemas of
+    /// the current one.
+    fn list_databases(&self) -> Result<DatabaseList, DbError> {
+        Err(DbError::invalid_input(format!("Switching databases is not supported for {:?}", self.info().db_type)))
+    }
+    /// Points unqualified names and `get_tables` at `database` and/or `schema`
+    /// on the live connection. Backends that need a new session to change
+    /// database (PostgreSQL) reject `database`; the caller reconnects instead.
+    fn use_database(&self, _database: Option<&str>, _schema: Option<&str>) -> Result<(), DbError> {
+        Err(DbError::invalid_input(format!("Switching databases is not supported for {:?}", self.info().db_type)))
+    }
+    /// `NOTIFY` messages for the channels this connection listens on. Handed
+    /// out once; `None` afterwards and for backends without LISTEN/NOTIFY.
+    fn take_notifications(&self) -> Option<NotificationReceiver> {
+        None
+    }
+    /// Server ids of every session this connection holds open, for backends
+    /// that pool several (MySQL). Empty where the backend can tell by itself.
+    fn own_session_ids(&self) -> Vec<String> {
+        Vec::new()
+    }
     fn is_alive(&self) -> bool;
     fn close(&self);
 }
 
-pub fn create_connection(config: &ConnectionConfig) -> Result<Box<dyn DatabaseConnection + Send>, String> {
+pub fn create_connection(config: &ConnectionConfig) -> Result<Box<dyn DatabaseConnection + Send>, DbError> {
     match config.db_type {
         DatabaseType::Sqlite => {
             let path = config.file_path.as_ref()
                 .ok_or("SQLite requires file_path")?;
-            super::sqlite::SqliteConnection::new(path)
+            super::sqlite::SqliteConnection::new(path, config.read_only)
         },
         DatabaseType::Postgres => {
             let host = config.host.as_ref().ok_or("PostgreSQL requires host")?;
@@ -27,7 +151,11 @@ pub fn create_connection(config: &ConnectionConfig) -> Result<Box<dyn DatabaseCo
             let db = config.database.as_ref().ok_or("PostgreSQL requires database")?;
             let user = config.username.as_ref().ok_or("PostgreSQL requires username")?;
             let pass = config.password.as_deref().unwrap_or("");
-            super::postgres::PostgresConnection::new(host, port, db, user, pass, true)
+            let conn = super::postgres::PostgresConnection::new(host, port, db, user, pass, true, config.read_only)?;
+            if let Some(schema) = &config.schema {
+                conn.use_database(None, Some(schema))?;
+            }
+            Ok(conn)
         },
         DatabaseType::Mysql => {
             let host = config.host.as_ref().ok_or("MySQL requires host")?;
@@ -35,7 +163,10 @@ pub fn create_connection(config: &ConnectionConfig) -> Result<Box<dyn DatabaseCo
             let db = config.database.as_ref().ok_or("MySQL requires database")?;
             let user = config.username.as_ref().ok_or("MySQL requires username")?;
             let pass = config.password.as_deref().unwrap_or("");
-            super::mysql::MysqlConnection::new(host, port, db, user, pass)
+            super::mysql::MysqlConnection::new(host, port, db, user, pass, config.read_only)
+        },
+        DatabaseType::Duckdb => {
+            super::duckdb::DuckdbConnection::new(config.file_path.as_deref(), config.read_only)
         },
     }
 }
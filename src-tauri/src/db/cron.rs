@@ -0,0 +1,126 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone, Timelike};
+
+const MONTHS: &[&str] = &["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAYS: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+/// How far ahead to look for a match; impossible dates such as `0 0 30 2 *` never match.
+const HORIZON_DAYS: i64 = 366 * 5;
+
+/// A five-field cron expression (`minute hour day-of-month month day-of-week`)
+/// in local time. Fields take `*`, numbers, names (`jan`, `mon`), ranges,
+/// lists and `/step`; `@hourly`, `@daily`, `@weekly` and `@monthly` are
+/// shorthands. As in cron, when both day fields are restricted a day matching
+/// either one matches.
+#[derive(Debug, Clone, Copy)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+fn has(mask: u64, n: u32) -> bool {
+    mask & (1 << n) != 0
+}
+
+fn value(text: &str, names: &[&str], min: u32) -> Result<u32, String> {
+    let lower = text.to_lowercase();
+    match names.iter().position(|n| *n == lower) {
+        Some(i) => Ok(min + i as u32),
+        None => text.parse().map_err(|_| format!("Invalid cron value '{}'", text)),
+    }
+}
+
+/// Bit mask of the values `spec` selects in `[min, max]`.
+fn field(spec: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in spec.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step.parse::<u32>().ok().filter(|s| *s > 0)
+                    .ok_or_else(|| format!("Invalid cron step in '{}'", part))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (lo, hi) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((lo, hi)) => (value(lo, names, min)?, value(hi, names, min)?),
+            // `5/15` runs from 5 to the end of the range.
+            None if step > 1 => (value(range, names, min)?, max),
+            None => {
+                let v = value(range, names, min)?;
+                (v, v)
+            }
+        };
+        if lo < min || hi > max || lo > hi {
+            return Err(format!("Cron field '{}' is out of range {}-{}", part, min, max));
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<CronSchedule, String> {
+        let expr = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("Cron expression '{}' must have five fields", expr));
+        };
+        // 7 is Sunday as well as 0.
+        let weekdays = field(weekday, 0, 7, WEEKDAYS)?;
+        Ok(CronSchedule {
+            minutes: field(minute, 0, 59, &[])?,
+            hours: field(hour, 0, 23, &[])?,
+            days: field(day, 1, 31, &[])?,
+            months: field(month, 1, 12, MONTHS)?,
+            weekdays: (weekdays | (weekdays >> 7)) & 0x7f,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+
+    fn day_matches(&self, t: &NaiveDateTime) -> bool {
+        let day = has(self.days, t.day());
+        let weekday = has(self.weekdays, t.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// First matching minute strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)?;
+        let limit = start + Duration::days(HORIZON_DAYS);
+        let mut t = start + Duration::minutes(1);
+        while t < limit {
+            let midnight = t.date().and_hms_opt(0, 0, 0)?;
+            if !has(self.months, t.month()) || !self.day_matches(&t) {
+                t = midnight + Duration::days(1);
+            } else if !has(self.hours, t.hour()) {
+                t = midnight + Duration::hours(t.hour() as i64 + 1);
+            } else if !has(self.minutes, t.minute()) {
+                t += Duration::minutes(1);
+            } else {
+                // Times skipped by a DST change don't exist locally.
+                match Local.from_local_datetime(&t).earliest() {
+                    Some(local) => return Some(local),
+                    None => t += Duration::minutes(1),
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,619 @@
+use super::connection::DatabaseConnection;
+use super::dialect::{quote_ident, quote_table, sql_dialect};
+use super::guard::DestructiveOp;
+use super::types::*;
+use sqlparser::parser::Parser;
+use sqlparser::tokenizer::{Token, Tokenizer, Whitespace};
+
+/// Type names are interpolated into DDL, so only what type syntax needs gets through.
+fn check_type(data_type: &str) -> Result<(), DbError> {
+    let valid = !data_type.trim().is_empty()
+        && data_type.chars().all(|c| c.is_alphanumeric() || " _(),[].".contains(c));
+    if valid {
+        Ok(())
+    } else {
+        Err(DbError::invalid_input(format!("Invalid column type: {}", data_type)))
+    }
+}
+
+/// Defaults are interpolated too; they must be one expression, with no
+/// statement separators or comments to smuggle anything after it.
+fn check_default(db_type: DatabaseType, default: Option<&str>) -> Result<(), DbError> {
+    let Some(default) = default else { return Ok(()) };
+    let invalid = || DbError::invalid_input(format!("Invalid column default: {}", default));
+    let dialect = sql_dialect(db_type);
+    let tokens = Tokenizer::new(dialect.as_ref(), default).tokenize().map_err(|_| invalid())?;
+    let smuggled = tokens.iter().any(|t| matches!(
+        t,
+        Token::SemiColon | Token::Whitespace(Whitespace::SingleLineComment { .. } | Whitespace::MultiLineComment(_))
+    ));
+    if smuggled {
+        return Err(invalid());
+    }
+    let mut parser = Parser::new(dialect.as_ref()).with_tokens(tokens);
+    parser.parse_expr().map_err(|_| invalid())?;
+    if parser.peek_token().token != Token::EOF {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+fn check_name(kind: &str, name: &str) -> Result<(), DbError> {
+    if name.trim().is_empty() {
+        return Err(DbError::invalid_input(format!("{} name must not be empty", kind)));
+    }
+    Ok(())
+}
+
+fn referential_action(action: ReferentialAction) -> &'static str {
+    match action {
+        ReferentialAction::NoAction => "NO ACTION",
+        ReferentialAction::Restrict => "RESTRICT",
+        ReferentialAction::Cascade => "CASCADE",
+        ReferentialAction::SetNull => "SET NULL",
+        ReferentialAction::SetDefault => "SET DEFAULT",
+    }
+}
+
+fn quote_list(db_type: DatabaseType, names: &[String]) -> String {
+    names.iter().map(|n| quote_ident(db_type, n)).collect::<Vec<_>>().join(", ")
+}
+
+fn column_sql(db_type: DatabaseType, name: &str, data_type: &str, nullable: bool, default: Option<&str>) -> String {
+    let mut sql = quote_ident(db_type, name);
+    if !data_type.is_empty() {
+        sql.push(' ');
+        sql.push_str(data_type);
+    }
+    if !nullable {
+        sql.push_str(" NOT NULL");
+    }
+    if let Some(default) = default {
+        sql.push_str(" DEFAULT ");
+        sql.push_str(default);
+    }
+    sql
+}
+
+fn foreign_key_sql(
+    db_type: DatabaseType,
+    name: Option<&str>,
+    columns: &[String],
+    referenced_table: &str,
+    referenced_columns: &[String],
+    on_delete: Option<ReferentialAction>,
+) -> String {
+    let mut sql = String::new();
+    if let Some(name) = name {
+        sql.push_str(&format!("CONSTRAINT {} ", quote_ident(db_type, name)));
+    }
+    sql.push_str(&format!(
+        "FOREIGN KEY ({}) REFERENCES {} ({})",
+        quote_list(db_type, columns),
+        quote_table(db_type, referenced_table),
+        quote_list(db_type, referenced_columns),
+    ));
+    if let Some(action) = on_delete {
+        sql.push_str(" ON DELETE ");
+        sql.push_str(referential_action(action));
+    }
+    sql
+}
+
+/// `schema.table` keeps its schema when renamed.
+fn renamed(table: &str, to: &str, db_type: DatabaseType) -> String {
+    match table.split_once('.') {
+        Some((schema, _)) if db_type != DatabaseType::Sqlite => format!("{}.{}", schema, to),
+        _ => to.to_string(),
+    }
+}
+
+fn find_column<'a>(columns: &'a mut [ColumnInfo], name: &str) -> Result<&'a mut ColumnInfo, DbError> {
+    columns.iter_mut()
+        .find(|c| c.name == name)
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("Column '{}' not found", name)))
+}
+
+fn ensure_new_column(columns: &[ColumnInfo], name: &str) -> Result<(), DbError> {
+    check_name("Column", name)?;
+    if columns.iter().any(|c| c.name == name) {
+        return Err(DbError::invalid_input(format!("Column '{}' already exists", name)));
+    }
+    Ok(())
+}
+
+fn ensure_columns(columns: &[ColumnInfo], names: &[String]) -> Result<(), DbError> {
+    if names.is_empty() {
+        return Err(DbError::invalid_input("At least one column is required"));
+    }
+    for name in names {
+        if !columns.iter().any(|c| &c.name == name) {
+            return Err(DbError::new(DbErrorKind::NotFound, format!("Column '{}' not found", name)));
+        }
+    }
+    Ok(())
+}
+
+/// Ops SQLite's `ALTER TABLE` cannot express.
+fn needs_rebuild(op: &AlterOp) -> bool {
+    matches!(
+        op,
+        AlterOp::RetypeColumn { .. }
+            | AlterOp::DropColumn { .. }
+            | AlterOp::AddForeignKey { .. }
+            | AlterOp::DropForeignKey { .. }
+    )
+}
+
+/// Generates the DDL for `alteration` against the live schema.
+pub fn plan(conn: &dyn DatabaseConnection, alteration: &TableAlteration) -> Result<DdlPlan, DbError> {
+    if alteration.ops.is_empty() {
+        return Err(DbError::invalid_input("No changes to apply"));
+    }
+    let db_type = conn.info().db_type;
+    // Fresh rather than cached: DDL must see the schema as it is now.
+    let catalog = conn.get_catalog()?;
+    let table = catalog.tables.iter()
+        .find(|t| t.name == alteration.table)
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("Table '{}' not found", alteration.table)))?;
+
+    if db_type == DatabaseType::Sqlite && alteration.ops.iter().any(needs_rebuild) {
+        sqlite_rebuild(conn, table, &alteration.ops)
+    } else {
+        alter_statements(db_type, table, &alteration.ops)
+    }
+}
+
+fn alter_statements(db_type: DatabaseType, table: &CatalogTable, ops: &[AlterOp]) -> Result<DdlPlan, DbError> {
+    let q = |name: &str| quote_ident(db_type, name);
+    let mut current = table.name.clone();
+    let mut columns = table.columns.clone();
+    let mut statements = Vec::new();
+    let mut warnings = Vec::new();
+
+    for op in ops {
+        let t = quote_table(db_type, &current);
+        match op {
+            AlterOp::AddColumn { column } => {
+                ensure_new_column(&columns, &column.name)?;
+                check_type(&column.data_type)?;
+                check_default(db_type, column.default.as_deref())?;
+                if db_type == DatabaseType::Sqlite && !column.nullable && column.default.is_none() {
+                    return Err(DbError::invalid_input("SQLite cannot add a NOT NULL column without a default"));
+                }
+                statements.push(format!(
+                    "ALTER TABLE {} ADD COLUMN {}",
+                    t,
+                    column_sql(db_type, &column.name, &column.data_type, column.nullable, column.default.as_deref()),
+                ));
+                columns.push(ColumnInfo {
+                    name: column.name.clone(),
+                    data_type: column.data_type.clone(),
+                    nullable: column.nullable,
+                    default: column.default.clone(),
+                    primary_key: false,
+                });
+            }
+            AlterOp::RenameColumn { from, to } => {
+                ensure_new_column(&columns, to)?;
+                find_column(&mut columns, from)?.name = to.clone();
+                statements.push(format!("ALTER TABLE {} RENAME COLUMN {} TO {}", t, q(from), q(to)));
+            }
+            AlterOp::RetypeColumn { column, data_type, nullable } => {
+                check_type(data_type)?;
+                let col = find_column(&mut columns, column)?;
+                match db_type {
+                    DatabaseType::Mysql => {
+                        // MODIFY restates the whole definition.
+                        let nullable = nullable.unwrap_or(col.nullable);
+                        statements.push(format!(
+                            "ALTER TABLE {} MODIFY COLUMN {}",
+                            t,
+                            column_sql(db_type, column, data_type, nullable, None),
+                        ));
+                        if col.default.is_some() {
+                            warnings.push(format!("MODIFY COLUMN drops the default of '{}'; add it back if needed", column));
+                        }
+                    }
+                    _ => {
+                        let using = match db_type {
+                            DatabaseType::Postgres => format!(" USING {}::{}", q(column), data_type),
+                            _ => String::new(),
+                        };
+                        statements.push(format!("ALTER TABLE {} ALTER COLUMN {} TYPE {}{}", t, q(column), data_type, using));
+                        match nullable {
+                            Some(true) if !col.nullable => {
+                                statements.push(format!("ALTER TABLE {} ALTER COLUMN {} DROP NOT NULL", t, q(column)));
+                            }
+                            Some(false) if col.nullable => {
+                                statements.push(format!("ALTER TABLE {} ALTER COLUMN {} SET NOT NULL", t, q(column)));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                col.data_type = data_type.clone();
+                col.nullable = nullable.unwrap_or(col.nullable);
+            }
+            AlterOp::DropColumn { column } => {
+                find_column(&mut columns, column)?;
+                columns.retain(|c| &c.name != column);
+                statements.push(format!("ALTER TABLE {} DROP COLUMN {}", t, q(column)));
+            }
+            AlterOp::AddIndex { name, columns: index_columns, unique } => {
+                check_name("Index", name)?;
+                ensure_columns(&columns, index_columns)?;
+                statements.push(format!(
+                    "CREATE {}INDEX {} ON {} ({})",
+                    if *unique { "UNIQUE " } else { "" },
+                    q(name),
+                    t,
+                    quote_list(db_type, index_columns),
+                ));
+            }
+            AlterOp::DropIndex { name } => {
+                check_name("Index", name)?;
+                statements.push(match (db_type, &table.schema) {
+                    (DatabaseType::Mysql, _) => format!("DROP INDEX {} ON {}", q(name), t),
+                    (DatabaseType::Postgres, Some(schema)) => format!("DROP INDEX {}.{}", q(schema), q(name)),
+                    _ => format!("DROP INDEX {}", q(name)),
+                });
+            }
+            AlterOp::AddForeignKey { name, columns: fk_columns, referenced_table, referenced_columns, on_delete } => {
+                if db_type == DatabaseType::Duckdb {
+                    return Err(DbError::invalid_input("DuckDB cannot add foreign keys to an existing table"));
+                }
+                ensure_columns(&columns, fk_columns)?;
+                if referenced_columns.len() != fk_columns.len() {
+                    return Err(DbError::invalid_input("Foreign key needs as many referenced columns as columns"));
+                }
+                statements.push(format!(
+                    "ALTER TABLE {} ADD {}",
+                    t,
+                    foreign_key_sql(db_type, name.as_deref(), fk_columns, referenced_table, referenced_columns, *on_delete),
+                ));
+            }
+            AlterOp::DropForeignKey { name, .. } => {
+                if db_type == DatabaseType::Duckdb {
+                    return Err(DbError::invalid_input("DuckDB cannot drop foreign keys from an existing table"));
+                }
+                let name = name.as_deref().ok_or_else(|| DbError::invalid_input("Foreign key name is required"))?;
+                statements.push(match db_type {
+                    DatabaseType::Mysql => format!("ALTER TABLE {} DROP FOREIGN KEY {}", t, q(name)),
+                    _ => format!("ALTER TABLE {} DROP CONSTRAINT {}", t, q(name)),
+                });
+            }
+            AlterOp::RenameTable { to } => {
+                check_name("Table", to)?;
+                let next = renamed(&current, to, db_type);
+                statements.push(match db_type {
+                    DatabaseType::Mysql => format!("RENAME TABLE {} TO {}", t, quote_table(db_type, &next)),
+                    _ => format!("ALTER TABLE {} RENAME TO {}", t, q(to)),
+                });
+                current = next;
+            }
+        }
+    }
+
+    let transactional = db_type != DatabaseType::Mysql;
+    if !transactional && statements.len() > 1 {
+        warnings.push("MySQL commits each DDL statement on its own; a failure part-way leaves earlier statements applied".into());
+    }
+    Ok(DdlPlan { statements, transactional, rebuild: false, warnings })
+}
+
+/// A column of the rebuilt table and the old column its data comes from.
+struct RebuildColumn {
+    info: ColumnInfo,
+    source: Option<String>,
+}
+
+struct ExistingIndex {
+    name: String,
+    unique: bool,
+    /// `None` entries are expressions.
+    columns: Vec<Option<String>>,
+    /// `c` for CREATE INDEX, `u` for UNIQUE constraints, `pk` for the primary key.
+    origin: String,
+    sql: Option<String>,
+}
+
+fn existing_indexes(conn: &dyn DatabaseConnection, table: &str) -> Result<Vec<ExistingIndex>, DbError> {
+    let list = conn.query_with_params(
+        "SELECT il.name, il.\"unique\", il.origin, m.sql \
+         FROM pragma_index_list(?1) il \
+         LEFT JOIN sqlite_master m ON m.type = 'index' AND m.name = il.name",
+        &[QueryParam::Text(table.to_string())],
+    )?;
+    let mut indexes = Vec::new();
+    for row in &list.rows {
+        let name = cell_text(row, 0).unwrap_or_default();
+        let info = conn.query_with_params(
+            "SELECT name FROM pragma_index_info(?1) ORDER BY seqno",
+            &[QueryParam::Text(name.clone())],
+        )?;
+        indexes.push(ExistingIndex {
+            columns: info.rows.iter().map(|r| cell_text(r, 0)).collect(),
+            unique: cell_text(row, 1).as_deref() == Some("1"),
+            origin: cell_text(row, 2).unwrap_or_default(),
+            sql: cell_text(row, 3),
+            name,
+        });
+    }
+    Ok(indexes)
+}
+
+/// SQLite's documented rebuild: create the new shape under a temporary name,
+/// copy the rows across, drop the old table, rename, then restore indexes and
+/// triggers. Runs with foreign keys off so the drop does not cascade.
+fn sqlite_rebuild(conn: &dyn DatabaseConnection, table: &CatalogTable, ops: &[AlterOp]) -> Result<DdlPlan, DbError> {
+    let db_type = DatabaseType::Sqlite;
+    let q = |name: &str| quote_ident(db_type, name);
+    let mut columns: Vec<RebuildColumn> = table.columns.iter()
+        .map(|c| RebuildColumn { info: c.clone(), source: Some(c.name.clone()) })
+        .collect();
+    // Catalog foreign keys are in pragma id order; their actions come from the same pragma.
+    let actions = conn.query_with_params(
+        "SELECT DISTINCT id, on_update, on_delete FROM pragma_foreign_key_list(?1) ORDER BY id",
+        &[QueryParam::Text(table.name.clone())],
+    )?;
+    let mut foreign_keys: Vec<(ForeignKeyInfo, String)> = table.foreign_keys.iter()
+        .zip(actions.rows.iter())
+        .map(|(fk, row)| {
+            let mut suffix = String::new();
+            for (clause, i) in [("ON UPDATE", 1), ("ON DELETE", 2)] {
+                if let Some(action) = cell_text(row, i).filter(|a| a != "NO ACTION") {
+                    suffix.push_str(&format!(" {} {}", clause, action));
+                }
+            }
+            (fk.clone(), suffix)
+        })
+        .collect();
+    let mut final_name = table.name.clone();
+    let mut index_ops = Vec::new();
+    let mut warnings = Vec::new();
+
+    for op in ops {
+        let infos: Vec<ColumnInfo> = columns.iter().map(|c| c.info.clone()).collect();
+        match op {
+            AlterOp::AddColumn { column } => {
+                ensure_new_column(&infos, &column.name)?;
+                check_type(&column.data_type)?;
+                check_default(db_type, column.default.as_deref())?;
+                columns.push(RebuildColumn {
+                    info: ColumnInfo {
+                        name: column.name.clone(),
+                        data_type: column.data_type.clone(),
+                        nullable: column.nullable,
+                        default: column.default.clone(),
+                        primary_key: false,
+                    },
+                    source: None,
+                });
+            }
+            AlterOp::RenameColumn { from, to } => {
+                ensure_new_column(&infos, to)?;
+                let col = columns.iter_mut()
+                    .find(|c| &c.info.name == from)
+                    .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("Column '{}' not found", from)))?;
+                col.info.name = to.clone();
+                for (fk, _) in &mut foreign_keys {
+                    for c in fk.columns.iter_mut().filter(|c| *c == from) {
+                        *c = to.clone();
+                    }
+                }
+            }
+            AlterOp::RetypeColumn { column, data_type, nullable } => {
+                check_type(data_type)?;
+                let col = columns.iter_mut()
+                    .find(|c| &c.info.name == column)
+                    .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("Column '{}' not found", column)))?;
+                col.info.data_type = data_type.clone();
+                col.info.nullable = nullable.unwrap_or(col.info.nullable);
+            }
+            AlterOp::DropColumn { column } => {
+                ensure_columns(&infos, std::slice::from_ref(column))?;
+                columns.retain(|c| &c.info.name != column);
+                foreign_keys.retain(|(fk, _)| !fk.columns.contains(column));
+            }
+            AlterOp::AddForeignKey { name, columns: fk_columns, referenced_table, referenced_columns, on_delete } => {
+                ensure_columns(&infos, fk_columns)?;
+                if referenced_columns.len() != fk_columns.len() {
+                    return Err(DbError::invalid_input("Foreign key needs as many referenced columns as columns"));
+                }
+                let suffix = on_delete
+                    .map(|action| format!(" ON DELETE {}", referential_action(action)))
+                    .unwrap_or_default();
+                foreign_keys.push((ForeignKeyInfo {
+                    name: name.clone(),
+                    columns: fk_columns.clone(),
+                    referenced_schema: None,
+                    referenced_table: referenced_table.clone(),
+                    referenced_columns: referenced_columns.clone(),
+                }, suffix));
+            }
+            AlterOp::DropForeignKey { name, columns: fk_columns } => {
+                let before = foreign_keys.len();
+                foreign_keys.retain(|(fk, _)| match name {
+                    Some(name) => fk.name.as_ref() != Some(name),
+                    None => &fk.columns != fk_columns,
+                });
+                if foreign_keys.len() == before {
+                    return Err(DbError::new(DbErrorKind::NotFound, "Foreign key not found"));
+                }
+            }
+            AlterOp::RenameTable { to } => {
+                check_name("Table", to)?;
+                final_name = to.clone();
+            }
+            AlterOp::AddIndex { .. } | AlterOp::DropIndex { .. } => index_ops.push(op),
+        }
+    }
+    if columns.is_empty() {
+        return Err(DbError::invalid_input("A table needs at least one column"));
+    }
+
+    let original_sql = conn.query_with_params(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        &[QueryParam::Text(table.name.clone())],
+    )?.rows.first().and_then(|r| cell_text(r, 0)).unwrap_or_default().to_uppercase();
+    if original_sql.contains("CHECK") {
+        warnings.push("CHECK constraints are not carried over by the rebuild".into());
+    }
+
+    // Only columns the rebuild kept or renamed can carry over dependent objects.
+    let new_name_of = |old: &str| -> Option<String> {
+        columns.iter().find(|c| c.source.as_deref() == Some(old)).map(|c| c.info.name.clone())
+    };
+    let unchanged = final_name == table.name
+        && table.columns.iter().all(|c| new_name_of(&c.name).as_deref() == Some(c.name.as_str()));
+
+    let primary_key: Vec<&str> = columns.iter()
+        .filter(|c| c.info.primary_key)
+        .map(|c| c.info.name.as_str())
+        .collect();
+    let mut definitions: Vec<String> = columns.iter().map(|c| {
+        let mut sql = column_sql(db_type, &c.info.name, &c.info.data_type, c.info.nullable, c.info.default.as_deref());
+        if primary_key.len() == 1 && c.info.primary_key {
+            sql.push_str(" PRIMARY KEY");
+            if original_sql.contains("AUTOINCREMENT") {
+                sql.push_str(" AUTOINCREMENT");
+            }
+        }
+        sql
+    }).collect();
+    if primary_key.len() > 1 {
+        let names: Vec<String> = primary_key.iter().map(|n| n.to_string()).collect();
+        definitions.push(format!("PRIMARY KEY ({})", quote_list(db_type, &names)));
+    }
+    for (fk, actions) in &foreign_keys {
+        let sql = foreign_key_sql(db_type, fk.name.as_deref(), &fk.columns, &fk.referenced_table, &fk.referenced_columns, None);
+        definitions.push(sql + actions);
+    }
+
+    let temp = format!("{}__rebuild", final_name);
+    let copied: Vec<&RebuildColumn> = columns.iter().filter(|c| c.source.is_some()).collect();
+    let mut statements = vec![
+        format!("CREATE TABLE {} (\n  {}\n)", q(&temp), definitions.join(",\n  ")),
+        format!(
+            "INSERT INTO {} ({}) SELECT {} FROM {}",
+            q(&temp),
+            copied.iter().map(|c| q(&c.info.name)).collect::<Vec<_>>().join(", "),
+            copied.iter().map(|c| q(c.source.as_deref().unwrap_or_default())).collect::<Vec<_>>().join(", "),
+            q(&table.name),
+        ),
+        format!("DROP TABLE {}", q(&table.name)),
+        format!("ALTER TABLE {} RENAME TO {}", q(&temp), q(&final_name)),
+    ];
+
+    let dropped_indexes: Vec<&String> = index_ops.iter()
+        .filter_map(|op| match op {
+            AlterOp::DropIndex { name } => Some(name),
+            _ => None,
+        })
+        .collect();
+    for index in existing_indexes(conn, &table.name)? {
+        if index.origin == "pk" || dropped_indexes.contains(&&index.name) {
+            continue;
+        }
+        let mapped: Option<Vec<String>> = index.columns.iter()
+            .map(|c| c.as_deref().and_then(new_name_of))
+            .collect();
+        let partial = index.sql.as_deref().is_some_and(|s| s.to_uppercase().contains(" WHERE "));
+        match (mapped, &index.sql) {
+            (_, Some(sql)) if unchanged => statements.push(sql.clone()),
+            (Some(mapped), _) if !partial => {
+                let name = match index.origin.as_str() {
+                    "u" => format!("{}_{}_key", final_name, mapped.join("_")),
+                    _ => index.name.clone(),
+                };
+                statements.push(format!(
+                    "CREATE {}INDEX {} ON {} ({})",
+                    if index.unique { "UNIQUE " } else { "" },
+                    q(&name),
+                    q(&final_name),
+                    quote_list(db_type, &mapped),
+                ));
+            }
+            _ => warnings.push(format!("Index '{}' could not be recreated", index.name)),
+        }
+    }
+    for op in index_ops {
+        if let AlterOp::AddIndex { name, columns: index_columns, unique } = op {
+            check_name("Index", name)?;
+            let infos: Vec<ColumnInfo> = columns.iter().map(|c| c.info.clone()).collect();
+            ensure_columns(&infos, index_columns)?;
+            statements.push(format!(
+                "CREATE {}INDEX {} ON {} ({})",
+                if *unique { "UNIQUE " } else { "" },
+                q(name),
+                q(&final_name),
+                quote_list(db_type, index_columns),
+            ));
+        }
+    }
+
+    let triggers = conn.query_with_params(
+        "SELECT name, sql FROM sqlite_master WHERE type = 'trigger' AND tbl_name = ?1",
+        &[QueryParam::Text(table.name.clone())],
+    )?;
+    for row in &triggers.rows {
+        match (unchanged, cell_text(row, 1)) {
+            (true, Some(sql)) => statements.push(sql),
+            _ => warnings.push(format!("Trigger '{}' could not be recreated", cell_text(row, 0).unwrap_or_default())),
+        }
+    }
+
+    Ok(DdlPlan { statements, transactional: true, rebuild: true, warnings })
+}
+
+/// Data-destroying part of an alteration, for the confirmation prompt.
+pub fn destructive(alteration: &TableAlteration) -> Option<DestructiveOp> {
+    alteration.ops.iter()
+        .any(|op| matches!(op, AlterOp::DropColumn { .. }))
+        .then(|| DestructiveOp {
+            operation: "DROP COLUMN".into(),
+            tables: vec![alteration.table.clone()],
+        })
+}
+
+/// Runs a plan from `plan`. Warnings found while applying (foreign key
+/// violations after a SQLite rebuild) are added to it.
+pub fn apply(conn: &dyn DatabaseConnection, plan: &mut DdlPlan) -> Result<ExecuteResult, DbError> {
+    let statements: Vec<(String, Vec<QueryParam>)> = plan.statements.iter()
+        .map(|sql| (sql.clone(), Vec::new()))
+        .collect();
+
+    if plan.rebuild {
+        // `PRAGMA foreign_keys` is a no-op inside a transaction, so it is toggled around it.
+        let enforced = conn.query("PRAGMA foreign_keys")?
+            .rows.first().and_then(|r| cell_text(r, 0)).as_deref() == Some("1");
+        if enforced {
+            conn.execute("PRAGMA foreign_keys = OFF")?;
+        }
+        let result = conn.execute_in_transaction(&statements);
+        if enforced {
+            if result.is_ok() {
+                let violations = conn.query("PRAGMA foreign_key_check").map(|r| r.rows.len()).unwrap_or(0);
+                if violations > 0 {
+                    plan.warnings.push(format!("{} rows now violate foreign keys", violations));
+                }
+            }
+            conn.execute("PRAGMA foreign_keys = ON")?;
+        }
+        return result;
+    }
+
+    if plan.transactional {
+        return conn.execute_in_transaction(&statements);
+    }
+    let start = std::time::Instant::now();
+    for (sql, _) in &statements {
+        conn.execute(sql)?;
+    }
+    Ok(ExecuteResult {
+        rows_affected: 0,
+        last_insert_id: None,
+        execution_time_ms: start.elapsed().as_millis() as u64,
+    })
+}
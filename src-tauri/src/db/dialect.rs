@@ -0,0 +1,96 @@
+use super::types::DatabaseType;
+
+/// Positional placeholder for the `index`-th (1-based) bound parameter.
+pub fn placeholder(db_type: DatabaseType, index: usize) -> String {
+    match db_type {
+        DatabaseType::Postgres => format!("${}", index),
+        DatabaseType::Sqlite | DatabaseType::Mysql | DatabaseType::Duckdb => "?".to_string(),
+    }
+}
+
+/// Quotes a single identifier for `db_type`, doubling embedded quote characters.
+pub fn quote_ident(db_type: DatabaseType, name: &str) -> String {
+    match db_type {
+        DatabaseType::Mysql => format!("`{}`", name.replace('`', "``")),
+        DatabaseType::Postgres | DatabaseType::Sqlite | DatabaseType::Duckdb => format!("\"{}\"", name.replace('"', "\"\"")),
+    }
+}
+
+/// Quotes a table name, treating `schema.table` as qualified except on SQLite
+/// where a dot is part of the name.
+pub fn quote_table(db_type: DatabaseType, table: &str) -> String {
+    match (db_type, table.split_once('.')) {
+        (DatabaseType::Sqlite, _) | (_, None) => quote_ident(db_type, table),
+        (_, Some((schema, name))) => format!("{}.{}", quote_ident(db_type, schema), quote_ident(db_type, name)),
+    }
+}
+
+/// Quotes a string literal for `db_type`. MySQL treats backslashes in strings
+/// as escapes by default, so they are doubled there.
+pub fn quote_literal(db_type: DatabaseType, value: &str) -> String {
+    match db_type {
+        DatabaseType::Mysql => format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''")),
+        DatabaseType::Postgres | DatabaseType::Sqlite | DatabaseType::Duckdb => format!("'{}'", value.replace('\'', "''")),
+    }
+}
+
+/// Parser dialect for statements sent to `db_type`.
+pub fn sql_dialect(db_type: DatabaseType) -> Box<dyn sqlparser::dialect::Dialect> {
+    use sqlparser::dialect::{DuckDbDialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect};
+    match db_type {
+        DatabaseType::Sqlite => Box::new(SQLiteDialect {}),
+        DatabaseType::Postgres => Box::new(PostgreSqlDialect {}),
+        DatabaseType::Mysql => Box::new(MySqlDialect {}),
+        DatabaseType::Duckdb => Box::new(DuckDbDialect {}),
+    }
+}
+
+/// Splits a batch on top-level semicolons, skipping over quoted strings and
+/// identifiers, comments, and dollar quotes on PostgreSQL. Statements come back
+/// trimmed; empty ones are dropped.
+pub fn split_statements(sql: &str, db_type: DatabaseType) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let skip_to = |from: usize, end: &str| sql[from..].find(end).map_or(bytes.len(), |i| from + i + end.len());
+
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    // MySQL escapes quotes with a backslash by default.
+                    if bytes[i] == b'\\' && db_type == DatabaseType::Mysql {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => i = skip_to(i, "\n"),
+            b'#' if db_type == DatabaseType::Mysql => i = skip_to(i, "\n"),
+            b'/' if bytes.get(i + 1) == Some(&b'*') => i = skip_to(i + 2, "*/"),
+            b'$' if db_type == DatabaseType::Postgres => {
+                let tag_len = sql[i + 1..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .filter(|&n| sql[i + 1 + n..].starts_with('$') && !sql[i + 1..].starts_with(|c: char| c.is_ascii_digit()));
+                match tag_len {
+                    Some(n) => {
+                        let tag = &sql[i..i + n + 2];
+                        i = skip_to(i + tag.len(), tag);
+                    }
+                    None => i += 1,
+                }
+            }
+            b';' => {
+                statements.push(&sql[start..i]);
+                i += 1;
+                start = i;
+            }
+            _ => i += 1,
+        }
+    }
+    statements.push(&sql[start.min(bytes.len())..]);
+    statements.into_iter().map(str::trim).filter(|s| !s.is_empty()).collect()
+}
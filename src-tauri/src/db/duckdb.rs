@@ -0,0 +1,434 @@
+use std::path::Path;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use duckdb::{AccessMode, Config, Connection, params_from_iter};
+use duckdb::types::{TimeUnit, ToSql, ToSqlOutput, Value as DuckValue};
+use super::catalog::CatalogBuilder;
+use super::connection::DatabaseConnection;
+use super::dialect::quote_ident;
+use super::types::*;
+
+/// Files in a data folder that get a view, with the reader that loads them.
+const FOLDER_READERS: &[(&str, &str)] = &[
+    ("csv", "read_csv_auto"),
+    ("tsv", "read_csv_auto"),
+    ("parquet", "read_parquet"),
+];
+
+pub struct DuckdbConnection {
+    id: String,
+    name: String,
+    conn: Connection,
+    version: String,
+    read_only: bool,
+}
+
+impl DuckdbConnection {
+    /// `path` may be a database file, a folder of CSV/Parquet files (opened as an
+    /// in-memory database with one view per file), or empty/`:memory:` for a
+    /// scratch in-memory database.
+    ///
+    /// In-memory databases cannot be opened read-only, so for those `read_only`
+    /// is only enforced by the command layer.
+    pub fn new(path: Option<&str>, read_only: bool) -> Result<Box<dyn DatabaseConnection + Send>, DbError> {
+        let path = path.map(str::trim).filter(|p| !p.is_empty() && *p != ":memory:");
+        let folder = path.filter(|p| Path::new(p).is_dir());
+
+        let conn = match path {
+            Some(file) if folder.is_none() => {
+                let mode = if read_only { AccessMode::ReadOnly } else { AccessMode::ReadWrite };
+                let config = Config::default()
+                    .access_mode(mode)
+                    .map_err(|e| DbError::invalid_input(format!("DuckDB connection error: {}", e)))?;
+                Connection::open_with_flags(file, config)
+            }
+            _ => Connection::open_in_memory(),
+        }.map_err(|e| DbError { kind: DbErrorKind::Connection, ..e.into() })?;
+
+        if let Some(dir) = folder {
+            attach_folder(&conn, dir)?;
+        }
+
+        let version = conn.query_row("SELECT version()", [], |row| row.get::<_, String>(0))
+            .unwrap_or_else(|_| "unknown".to_string());
+
+        Ok(Box::new(DuckdbConnection {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: path.unwrap_or(":memory:").to_string(),
+            conn,
+            version,
+            read_only,
+        }))
+    }
+}
+
+/// Resolves relative paths in `read_csv_auto`/`read_parquet` against `dir` and
+/// exposes each data file in it as a view named after the file.
+fn attach_folder(conn: &Connection, dir: &str) -> Result<(), DbError> {
+    let quote_literal = |s: &str| format!("'{}'", s.replace('\'', "''"));
+
+    conn.execute_batch(&format!("SET file_search_path = {}", quote_literal(dir)))?;
+
+    let entries = std::fs::read_dir(dir).map_err(|e| DbError::new(DbErrorKind::Connection, format!("Cannot read folder: {}", e)))?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let (Some(stem), Some(ext)) = (
+            path.file_stem().and_then(|s| s.to_str()),
+            path.extension().and_then(|s| s.to_str()),
+        ) else { continue };
+        let Some((_, reader)) = FOLDER_READERS.iter().find(|(e, _)| e.eq_ignore_ascii_case(ext)) else { continue };
+
+        let sql = format!(
+            "CREATE VIEW {} AS SELECT * FROM {}({})",
+            quote_ident(DatabaseType::Duckdb, stem),
+            reader,
+            quote_literal(&path.to_string_lossy()),
+        );
+        // A malformed file should not keep the rest of the folder from opening.
+        if let Err(e) = conn.execute_batch(&sql) {
+            log::warn!("Skipping {}: {}", path.display(), e);
+        }
+    }
+    Ok(())
+}
+
+impl ToSql for QueryParam {
+    fn to_sql(&self) -> duckdb::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Owned(match self {
+            QueryParam::Null => DuckValue::Null,
+            QueryParam::Bool(b) => DuckValue::Boolean(*b),
+            QueryParam::Int(n) => DuckValue::BigInt(*n),
+            QueryParam::Float(n) => DuckValue::Double(*n),
+            QueryParam::Text(s) => DuckValue::Text(s.clone()),
+            QueryParam::Bytes(b) => DuckValue::Blob(b.clone()),
+        }))
+    }
+}
+
+/// DuckDB has no error codes beyond a generic failure; the message starts with
+/// the error class (`Parser Error: ...`, `Catalog Error: ...`).
+impl From<duckdb::Error> for DbError {
+    fn from(e: duckdb::Error) -> Self {
+        let message = e.to_string();
+        let class = message.split_once(" Error:").map_or("", |(class, _)| class);
+        let (kind, retryable) = match class {
+            "Parser" | "Syntax" => (DbErrorKind::Syntax, false),
+            "Catalog" => (DbErrorKind::NotFound, false),
+            "Binder" if message.contains("not found") || message.contains("does not exist") => (DbErrorKind::NotFound, false),
+            "Binder" => (DbErrorKind::Syntax, false),
+            "Constraint" => (DbErrorKind::Constraint, false),
+            "Permission" => (DbErrorKind::Permission, false),
+            "Conversion" | "Invalid Input" | "Out of Range" | "Mismatch Type" => (DbErrorKind::InvalidInput, false),
+            "TransactionContext" | "Transaction" if message.contains("Conflict") => (DbErrorKind::Conflict, true),
+            "Interrupt" => (DbErrorKind::Cancelled, false),
+            _ => (DbErrorKind::Other, false),
+        };
+        DbError { retryable, ..DbError::new(kind, message) }
+    }
+}
+
+fn to_cell(row: &duckdb::Row<'_>, i: usize) -> Option<CellValue> {
+    let value: DuckValue = row.get(i).ok()?;
+    let text = match value {
+        DuckValue::Null => return None,
+        DuckValue::Blob(b) => return Some(CellValue::binary(&b)),
+        DuckValue::Text(s) => s,
+        DuckValue::Boolean(b) => b.to_string(),
+        DuckValue::TinyInt(n) => n.to_string(),
+        DuckValue::SmallInt(n) => n.to_string(),
+        DuckValue::Int(n) => n.to_string(),
+        DuckValue::BigInt(n) => n.to_string(),
+        DuckValue::HugeInt(n) => n.to_string(),
+        DuckValue::UTinyInt(n) => n.to_string(),
+        DuckValue::USmallInt(n) => n.to_string(),
+        DuckValue::UInt(n) => n.to_string(),
+        DuckValue::UBigInt(n) => n.to_string(),
+        DuckValue::Float(n) => n.to_string(),
+        DuckValue::Double(n) => n.to_string(),
+        DuckValue::Decimal(d) => d.to_string(),
+        DuckValue::Date32(_) => row.get::<_, NaiveDate>(i).ok()?.to_string(),
+        DuckValue::Time64(..) => row.get::<_, NaiveTime>(i).ok()?.to_string(),
+        DuckValue::Timestamp(..) => row.get::<_, NaiveDateTime>(i).ok()?.to_string(),
+        DuckValue::Interval { months, days, nanos } => interval_text(months, days, nanos),
+        DuckValue::Enum(s) => s,
+        DuckValue::Union(inner) => match *inner {
+            DuckValue::Null => return None,
+            DuckValue::Text(s) | DuckValue::Enum(s) => s,
+            other => literal(&other),
+        },
+        other => literal(&other),
+    };
+    Some(text.into())
+}
+
+fn micros(unit: TimeUnit, value: i64) -> i64 {
+    match unit {
+        TimeUnit::Second => value * 1_000_000,
+        TimeUnit::Millisecond => value * 1_000,
+        TimeUnit::Microsecond => value,
+        TimeUnit::Nanosecond => value / 1_000,
+    }
+}
+
+/// Interval in DuckDB's own text form, e.g. `1 year 2 months 3 days 04:05:06`.
+fn interval_text(months: i32, days: i32, nanos: i64) -> String {
+    let plural = |n: i32, unit: &str| format!("{} {}{}", n, unit, if n.abs() == 1 { "" } else { "s" });
+    let mut parts = Vec::new();
+    if months / 12 != 0 {
+        parts.push(plural(months / 12, "year"));
+    }
+    if months % 12 != 0 {
+        parts.push(plural(months % 12, "month"));
+    }
+    if days != 0 {
+        parts.push(plural(days, "day"));
+    }
+    if nanos != 0 || parts.is_empty() {
+        let total = (nanos / 1_000).unsigned_abs();
+        let (seconds, fraction) = (total / 1_000_000, total % 1_000_000);
+        let mut time = format!(
+            "{}{:02}:{:02}:{:02}",
+            if nanos < 0 { "-" } else { "" },
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+        );
+        if fraction != 0 {
+            time.push_str(format!(".{:06}", fraction).trim_end_matches('0'));
+        }
+        parts.push(time);
+    }
+    parts.join(" ")
+}
+
+/// A value as a DuckDB literal, for elements of nested values: strings and
+/// temporal values quoted, lists in brackets, structs and maps in braces.
+fn literal(value: &DuckValue) -> String {
+    let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+    let list = |items: &[DuckValue]| format!("[{}]", items.iter().map(literal).collect::<Vec<_>>().join(", "));
+    match value {
+        DuckValue::Null => "NULL".into(),
+        DuckValue::Boolean(b) => b.to_string(),
+        DuckValue::TinyInt(n) => n.to_string(),
+        DuckValue::SmallInt(n) => n.to_string(),
+        DuckValue::Int(n) => n.to_string(),
+        DuckValue::BigInt(n) => n.to_string(),
+        DuckValue::HugeInt(n) => n.to_string(),
+        DuckValue::UTinyInt(n) => n.to_string(),
+        DuckValue::USmallInt(n) => n.to_string(),
+        DuckValue::UInt(n) => n.to_string(),
+        DuckValue::UBigInt(n) => n.to_string(),
+        DuckValue::Float(n) => n.to_string(),
+        DuckValue::Double(n) => n.to_string(),
+        DuckValue::Decimal(d) => d.to_string(),
+        DuckValue::Text(s) | DuckValue::Enum(s) => quote(s),
+        DuckValue::Blob(b) => quote(&b.iter().map(|b| format!("\\x{:02X}", b)).collect::<String>()),
+        DuckValue::Date32(days) => match NaiveDate::from_num_days_from_ce_opt(days + 719_163) {
+            Some(date) => quote(&date.to_string()),
+            None => days.to_string(),
+        },
+        DuckValue::Timestamp(unit, value) => match chrono::DateTime::from_timestamp_micros(micros(*unit, *value)) {
+            Some(timestamp) => quote(&timestamp.naive_utc().to_string()),
+            None => value.to_string(),
+        },
+        DuckValue::Time64(unit, value) => {
+            let m = micros(*unit, *value);
+            match NaiveTime::from_num_seconds_from_midnight_opt((m / 1_000_000) as u32, (m % 1_000_000) as u32 * 1_000) {
+                Some(time) => quote(&time.to_string()),
+                None => value.to_string(),
+            }
+        }
+        DuckValue::Interval { months, days, nanos } => quote(&interval_text(*months, *days, *nanos)),
+        DuckValue::List(items) | DuckValue::Array(items) => list(items),
+        DuckValue::Struct(fields) => format!(
+            "{{{}}}",
+            fields.iter().map(|(k, v)| format!("{}: {}", quote(k), literal(v))).collect::<Vec<_>>().join(", "),
+        ),
+        DuckValue::Map(entries) => format!(
+            "MAP {{{}}}",
+            entries.iter().map(|(k, v)| format!("{}: {}", literal(k), literal(v))).collect::<Vec<_>>().join(", "),
+        ),
+        DuckValue::Union(inner) => literal(inner),
+    }
+}
+
+fn text_list(value: DuckValue) -> Vec<String> {
+    match value {
+        DuckValue::List(items) => items.into_iter()
+            .filter_map(|v| match v {
+                DuckValue::Text(s) => Some(s),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl DatabaseConnection for DuckdbConnection {
+    fn info(&self) -> ConnectionInfo {
+        ConnectionInfo {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            db_type: DatabaseType::Duckdb,
+            version: self.version.clone(),
+            connected: true,
+            read_only: self.read_only,
+        }
+    }
+
+    fn query_limited(&self, sql: &str, params: &[QueryParam], limits: &QueryLimits) -> Result<QueryResult, DbError> {
+        let start = std::time::Instant::now();
+        let mut stmt = self.conn.prepare(sql)
+            .map_err(|e| DbError::from(e).locate(sql))?;
+
+        let mut rows = stmt.query(params_from_iter(params))
+            .map_err(|e| DbError::from(e).locate(sql))?;
+        // DuckDB only knows the result columns once the statement has run.
+        let column_names: Vec<String> = rows.as_ref()
+            .map(|s| s.column_names())
+            .unwrap_or_default();
+
+        let mut result_rows: Vec<Row> = Vec::new();
+        let mut truncated = false;
+        while let Some(row) = rows.next()? {
+            if limits.max_rows.is_some_and(|max| result_rows.len() >= max) {
+                truncated = true;
+                break;
+            }
+            result_rows.push((0..column_names.len()).map(|i| to_cell(row, i)).collect());
+        }
+
+        Ok(QueryResult::new(column_names, result_rows, truncated, start))
+    }
+
+    fn execute_with_params(&self, sql: &str, params: &[QueryParam]) -> Result<ExecuteResult, DbError> {
+        let start = std::time::Instant::now();
+        let result = self.conn.execute(sql, params_from_iter(params))
+            .map_err(|e| DbError::from(e).locate(sql))?;
+
+        Ok(ExecuteResult {
+            rows_affected: result as u64,
+            last_insert_id: None,
+            execution_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    fn query_bytes(&self, sql: &str, params: &[QueryParam]) -> Result<Option<Vec<u8>>, DbError> {
+        self.conn.query_row(sql, params_from_iter(params), |row| {
+            Ok(match row.get::<_, DuckValue>(0)? {
+                DuckValue::Null => None,
+                DuckValue::Blob(b) => Some(b),
+                DuckValue::Text(s) => Some(s.into_bytes()),
+                _ => to_cell(row, 0).and_then(|c| c.as_text().map(|s| s.as_bytes().to_vec())),
+            })
+        }).map_err(|e| DbError::from(e).locate(sql))
+    }
+
+    fn get_tables(&self) -> Result<Vec<TableInfo>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT table_schema, table_name \
+             FROM information_schema.tables \
+             WHERE table_catalog = current_database() \
+             ORDER BY table_schema, table_name"
+        )?;
+
+        let tables = stmt.query_map([], |row| {
+            let schema: String = row.get(0)?;
+            let table_name: String = row.get(1)?;
+            Ok(TableInfo {
+                name: format!("{}.{}", schema, table_name),
+                schema: Some(schema),
+                row_count: None,
+            })
+        })?;
+
+        Ok(tables.filter_map(|t| t.ok()).collect())
+    }
+
+    fn get_columns(&self, table: &str) -> Result<Vec<ColumnInfo>, DbError> {
+        let (schema_name, table_name) = table.split_once('.').unwrap_or(("main", table));
+
+        let mut stmt = self.conn.prepare(
+            "SELECT c.column_name, c.data_type, c.is_nullable, c.column_default, \
+                    EXISTS (SELECT 1 FROM duckdb_constraints() k \
+                            WHERE k.constraint_type = 'PRIMARY KEY' \
+                              AND k.database_name = c.table_catalog \
+                              AND k.schema_name = c.table_schema AND k.table_name = c.table_name \
+                              AND list_contains(k.constraint_column_names, c.column_name)) \
+             FROM information_schema.columns c \
+             WHERE c.table_catalog = current_database() AND c.table_schema = ? AND c.table_name = ? \
+             ORDER BY c.ordinal_position"
+        )?;
+
+        let columns = stmt.query_map([schema_name, table_name], |row| {
+            Ok(ColumnInfo {
+                name: row.get(0)?,
+                data_type: row.get(1)?,
+                nullable: row.get::<_, String>(2)? == "YES",
+                default: row.get(3)?,
+                primary_key: row.get(4)?,
+            })
+        })?;
+
+        Ok(columns.filter_map(|c| c.ok()).collect())
+    }
+
+    fn get_catalog(&self) -> Result<Catalog, DbError> {
+        let mut builder = CatalogBuilder::default();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT c.table_schema, c.table_name, c.column_name, c.data_type, c.is_nullable, c.column_default, \
+                    EXISTS (SELECT 1 FROM duckdb_constraints() k \
+                            WHERE k.constraint_type = 'PRIMARY KEY' \
+                              AND k.database_name = c.table_catalog \
+                              AND k.schema_name = c.table_schema AND k.table_name = c.table_name \
+                              AND list_contains(k.constraint_column_names, c.column_name)) \
+             FROM information_schema.columns c \
+             WHERE c.table_catalog = current_database() \
+             ORDER BY c.table_schema, c.table_name, c.ordinal_position"
+        )?;
+        let columns = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, ColumnInfo {
+                name: row.get(2)?,
+                data_type: row.get(3)?,
+                nullable: row.get::<_, String>(4)? == "YES",
+                default: row.get(5)?,
+                primary_key: row.get(6)?,
+            }))
+        })?;
+        for (schema, table, column) in columns.filter_map(|c| c.ok()) {
+            builder.add_column(Some(&schema), &table, column);
+        }
+
+        // DuckDB only allows foreign keys within a schema.
+        let mut stmt = self.conn.prepare(
+            "SELECT schema_name, table_name, constraint_name, constraint_column_names, \
+                    referenced_table, referenced_column_names \
+             FROM duckdb_constraints() \
+             WHERE constraint_type = 'FOREIGN KEY' AND database_name = current_database() \
+             ORDER BY schema_name, table_name, constraint_index"
+        )?;
+        let fks = stmt.query_map([], |row| {
+            let schema: String = row.get(0)?;
+            Ok((schema.clone(), row.get::<_, String>(1)?, ForeignKeyInfo {
+                name: row.get(2)?,
+                columns: text_list(row.get(3)?),
+                referenced_schema: Some(schema),
+                referenced_table: row.get(4)?,
+                referenced_columns: text_list(row.get(5)?),
+            }))
+        })?;
+        for (schema, table, fk) in fks.filter_map(|f| f.ok()) {
+            builder.add_foreign_key(Some(&schema), &table, fk);
+        }
+
+        Ok(builder.build())
+    }
+
+    fn is_alive(&self) -> bool {
+        self.conn.query_row("SELECT 1", [], |_| Ok(())).is_ok()
+    }
+
+    fn close(&self) {
+        // Connection closes on drop
+    }
+}
@@ -0,0 +1,529 @@
+use std::collections::{HashMap, HashSet};
+use chrono::{Duration, Utc};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use super::connection::DatabaseConnection;
+use super::dialect::{placeholder, quote_ident, quote_table};
+use super::types::*;
+
+const MAX_ROWS: u64 = 100_000;
+/// Bound parameters per INSERT, under SQLite's historic limit of 999.
+pub(super) const MAX_PARAMS: usize = 900;
+const MAX_BATCH_ROWS: usize = 500;
+/// Parent keys sampled per foreign key.
+const PARENT_SAMPLE: usize = 1000;
+/// Share of NULLs in nullable columns filled by a guess.
+const NULL_RATIO: f64 = 0.1;
+const UNIQUE_ATTEMPTS: usize = 50;
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada", "Alan", "Amara", "Ben", "Carla", "Chen", "Diego", "Elena", "Farah", "Grace",
+    "Hugo", "Ines", "Jonas", "Kenji", "Lena", "Mateo", "Nadia", "Omar", "Priya", "Quinn",
+    "Rosa", "Sam", "Tariq", "Uma", "Victor", "Wen", "Yara", "Zoe",
+];
+const LAST_NAMES: &[&str] = &[
+    "Alvarez", "Becker", "Costa", "Dubois", "Eriksen", "Fischer", "Garcia", "Hughes", "Ito",
+    "Jensen", "Kowalski", "Lopez", "Moreau", "Nakamura", "Okafor", "Patel", "Rossi", "Schmidt",
+    "Tanaka", "Usman", "Varga", "Walsh", "Yilmaz", "Zhang",
+];
+const COMPANIES: &[&str] = &[
+    "Acme", "Globex", "Initech", "Umbrella", "Stark Industries", "Wayne Enterprises", "Hooli",
+    "Vandelay Industries", "Soylent", "Tyrell", "Cyberdyne", "Wonka", "Pied Piper", "Massive Dynamic",
+];
+const CITIES: &[&str] = &[
+    "Amsterdam", "Berlin", "Buenos Aires", "Cairo", "Chicago", "Lagos", "Lisbon", "London",
+    "Madrid", "Mumbai", "Osaka", "Paris", "Seoul", "Sydney", "Toronto", "Vienna",
+];
+const COUNTRIES: &[&str] = &[
+    "Argentina", "Australia", "Brazil", "Canada", "Egypt", "France", "Germany", "India", "Japan",
+    "Netherlands", "Nigeria", "Portugal", "South Korea", "Spain", "United Kingdom", "United States",
+];
+const STREETS: &[&str] = &["Main St", "Oak Ave", "Station Rd", "High St", "Park Lane", "Mill Rd", "Church St"];
+const WORDS: &[&str] = &[
+    "alpha", "amber", "beacon", "cedar", "delta", "ember", "falcon", "garnet", "harbor", "indigo",
+    "juniper", "kestrel", "lumen", "maple", "nimbus", "onyx", "pioneer", "quartz", "river", "summit",
+    "tundra", "vertex", "willow", "zenith",
+];
+const STATUSES: &[&str] = &["active", "inactive", "pending"];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TypeClass {
+    Integer,
+    Decimal,
+    Boolean,
+    Date,
+    Timestamp,
+    Time,
+    Uuid,
+    Json,
+    Binary,
+    Text,
+}
+
+pub fn classify(data_type: &str) -> TypeClass {
+    let t = data_type.to_lowercase();
+    if t.starts_with("bool") || t == "bit" || t.starts_with("tinyint(1)") {
+        TypeClass::Boolean
+    } else if (t.contains("int") && !t.starts_with("interval")) || t.contains("serial") {
+        TypeClass::Integer
+    } else if ["numeric", "decimal", "real", "double", "float", "money"].iter().any(|k| t.contains(k)) {
+        TypeClass::Decimal
+    } else if t.contains("timestamp") || t.contains("datetime") {
+        TypeClass::Timestamp
+    } else if t.starts_with("date") {
+        TypeClass::Date
+    } else if t.starts_with("time") {
+        TypeClass::Time
+    } else if t.contains("uuid") {
+        TypeClass::Uuid
+    } else if t.contains("json") {
+        TypeClass::Json
+    } else if t.contains("blob") || t.contains("bytea") || t.contains("binary") {
+        TypeClass::Binary
+    } else {
+        TypeClass::Text
+    }
+}
+
+/// `n` from `varchar(n)` / `char(n)`.
+pub fn max_length(data_type: &str) -> Option<usize> {
+    let t = data_type.to_lowercase();
+    if !t.contains("char") {
+        return None;
+    }
+    let inner = t.split_once('(')?.1.split_once(')')?.0;
+    inner.trim().parse().ok()
+}
+
+/// A rule guessed from the column's name, then its type.
+fn guess(column: &ColumnInfo, class: TypeClass) -> GeneratorRule {
+    let name = column.name.to_lowercase();
+    let has = |parts: &[&str]| parts.iter().any(|p| name.contains(p));
+    let fake = |fake| GeneratorRule::Fake { fake };
+    let range = |min: f64, max: f64, decimals| GeneratorRule::Range { min, max, decimals };
+
+    match class {
+        TypeClass::Boolean => fake(FakeKind::Boolean),
+        TypeClass::Date => fake(FakeKind::Date),
+        TypeClass::Timestamp => fake(FakeKind::Timestamp),
+        TypeClass::Uuid => fake(FakeKind::Uuid),
+        TypeClass::Integer if name == "age" => range(18.0, 90.0, 0),
+        TypeClass::Integer if has(&["year"]) => range(1990.0, 2025.0, 0),
+        TypeClass::Integer if has(&["qty", "quantity", "count", "stock"]) => range(0.0, 100.0, 0),
+        TypeClass::Integer if has(&["price", "amount", "total", "cost", "salary"]) => range(1.0, 5000.0, 0),
+        TypeClass::Integer => range(1.0, 10_000.0, 0),
+        TypeClass::Decimal if has(&["price", "amount", "total", "cost", "balance", "salary", "fee"]) => fake(FakeKind::Amount),
+        TypeClass::Decimal => range(0.0, 1000.0, 2),
+        TypeClass::Text if has(&["email"]) => fake(FakeKind::Email),
+        TypeClass::Text if has(&["first"]) && has(&["name"]) => fake(FakeKind::FirstName),
+        TypeClass::Text if has(&["last", "surname"]) && has(&["name"]) => fake(FakeKind::LastName),
+        TypeClass::Text if has(&["user", "login", "handle"]) => fake(FakeKind::Username),
+        TypeClass::Text if has(&["phone", "mobile", "tel"]) => fake(FakeKind::Phone),
+        TypeClass::Text if has(&["company", "organization", "organisation", "employer"]) => fake(FakeKind::Company),
+        TypeClass::Text if has(&["city", "town"]) => fake(FakeKind::City),
+        TypeClass::Text if has(&["country"]) => fake(FakeKind::Country),
+        TypeClass::Text if has(&["address", "street"]) => fake(FakeKind::Address),
+        TypeClass::Text if has(&["url", "website", "homepage", "link"]) => fake(FakeKind::Url),
+        TypeClass::Text if has(&["uuid", "guid"]) => fake(FakeKind::Uuid),
+        TypeClass::Text if name == "name" || has(&["full_name", "fullname", "display_name", "contact"]) => fake(FakeKind::FullName),
+        TypeClass::Text if name == "status" || name == "state" => GeneratorRule::OneOf {
+            values: STATUSES.iter().map(|s| s.to_string()).collect(),
+        },
+        TypeClass::Text if has(&["description", "note", "comment", "bio", "summary", "body", "content", "title", "subject"]) => fake(FakeKind::Sentence),
+        TypeClass::Text if has(&["_at", "date"]) => fake(FakeKind::Timestamp),
+        TypeClass::Json => GeneratorRule::Constant { value: Some("{}".into()) },
+        _ => fake(FakeKind::Word),
+    }
+}
+
+fn pick<'a>(rng: &mut impl Rng, list: &[&'a str]) -> &'a str {
+    list.choose(rng).copied().unwrap_or_default()
+}
+
+fn fake_value(rng: &mut impl Rng, fake: FakeKind) -> QueryParam {
+    let first = pick(rng, FIRST_NAMES);
+    let last = pick(rng, LAST_NAMES);
+    let text = match fake {
+        FakeKind::Email => format!("{}.{}{}@example.com", first.to_lowercase(), last.to_lowercase(), rng.gen_range(1..10_000)),
+        FakeKind::FirstName => first.to_string(),
+        FakeKind::LastName => last.to_string(),
+        FakeKind::FullName => format!("{} {}", first, last),
+        FakeKind::Username => format!("{}{}{}", first.to_lowercase(), &last[..1].to_lowercase(), rng.gen_range(1..10_000)),
+        FakeKind::Phone => format!("+1-555-{:03}-{:04}", rng.gen_range(100..1000), rng.gen_range(0..10_000)),
+        FakeKind::Company => pick(rng, COMPANIES).to_string(),
+        FakeKind::City => pick(rng, CITIES).to_string(),
+        FakeKind::Country => pick(rng, COUNTRIES).to_string(),
+        FakeKind::Address => format!("{} {}", rng.gen_range(1..500), pick(rng, STREETS)),
+        FakeKind::Url => format!("https://{}.example.com", pick(rng, WORDS)),
+        FakeKind::Uuid => uuid::Uuid::new_v4().to_string(),
+        FakeKind::Date | FakeKind::Timestamp => {
+            // Spread over the last three years.
+            let at = Utc::now() - Duration::seconds(rng.gen_range(0..3 * 365 * 24 * 3600));
+            let format = if fake == FakeKind::Date { "%Y-%m-%d" } else { "%Y-%m-%d %H:%M:%S" };
+            at.format(format).to_string()
+        }
+        FakeKind::Amount => return QueryParam::Float((rng.gen_range(100..500_000) as f64) / 100.0),
+        FakeKind::Integer => return QueryParam::Int(rng.gen_range(1..10_000)),
+        FakeKind::Boolean => return QueryParam::Bool(rng.gen_bool(0.5)),
+        FakeKind::Word => pick(rng, WORDS).to_string(),
+        FakeKind::Sentence => {
+            let words: Vec<&str> = (0..rng.gen_range(4..10)).map(|_| pick(rng, WORDS)).collect();
+            let sentence = words.join(" ");
+            let mut chars = sentence.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars).collect::<String>() + ".").unwrap_or_default()
+        }
+    };
+    QueryParam::Text(text)
+}
+
+fn rule_value(rng: &mut impl Rng, rule: &GeneratorRule) -> QueryParam {
+    match rule {
+        GeneratorRule::Skip => QueryParam::Null,
+        GeneratorRule::Constant { value } => value.clone().map_or(QueryParam::Null, QueryParam::Text),
+        GeneratorRule::OneOf { values } => values.choose(rng).cloned().map_or(QueryParam::Null, QueryParam::Text),
+        GeneratorRule::Range { min, max, decimals } => {
+            let value = if max > min { rng.gen_range(*min..=*max) } else { *min };
+            if *decimals == 0 {
+                QueryParam::Int(value.round() as i64)
+            } else {
+                let scale = 10f64.powi(*decimals as i32);
+                QueryParam::Float((value * scale).round() / scale)
+            }
+        }
+        GeneratorRule::Fake { fake } => fake_value(rng, *fake),
+    }
+}
+
+/// `text` with a numeric suffix, shortened first so the suffix survives the
+/// column's declared length.
+fn suffixed(text: &str, n: u64, max_length: Option<usize>) -> QueryParam {
+    let suffix = n.to_string();
+    let keep = max_length.map_or(usize::MAX, |max| max.saturating_sub(suffix.len()));
+    QueryParam::Text(text.chars().take(keep).chain(suffix.chars()).collect())
+}
+
+/// Truncates text to the column's declared length.
+fn fit(value: QueryParam, max_length: Option<usize>) -> QueryParam {
+    match (value, max_length) {
+        (QueryParam::Text(text), Some(max)) if text.chars().count() > max => {
+            QueryParam::Text(text.chars().take(max).collect())
+        }
+        (value, _) => value,
+    }
+}
+
+enum Fill {
+    Rule { rule: GeneratorRule, guessed: bool },
+    /// Taken from the sampled parent row of foreign key `group`.
+    Foreign { group: usize, position: usize },
+    /// Counts up from the column's current maximum; for unique integers.
+    Sequence(i64),
+    Time,
+}
+
+struct Column {
+    name: String,
+    nullable: bool,
+    unique: bool,
+    max_length: Option<usize>,
+    fill: Fill,
+}
+
+fn bare_name(table: &CatalogTable) -> &str {
+    table.schema.as_deref()
+        .and_then(|schema| table.name.strip_prefix(schema)?.strip_prefix('.'))
+        .unwrap_or(&table.name)
+}
+
+fn texts(result: QueryResult) -> HashSet<String> {
+    result.rows.iter().filter_map(|row| cell_text(row, 0)).collect()
+}
+
+/// Columns the database fills itself: identities, sequences, auto-increments
+/// and generated columns.
+fn auto_columns(conn: &dyn DatabaseConnection, table: &CatalogTable) -> Result<HashSet<String>, DbError> {
+    let db_type = conn.info().db_type;
+    let schema = table.schema.clone().map_or(QueryParam::Null, QueryParam::Text);
+    let name = QueryParam::Text(bare_name(table).to_string());
+    let mut auto: HashSet<String> = table.columns.iter()
+        .filter(|c| c.default.as_deref().is_some_and(|d| d.contains("nextval")))
+        .map(|c| c.name.clone())
+        .collect();
+
+    match db_type {
+        DatabaseType::Sqlite => {
+            // A lone INTEGER PRIMARY KEY is the rowid.
+            let pk: Vec<&ColumnInfo> = table.columns.iter().filter(|c| c.primary_key).collect();
+            if let [column] = pk.as_slice() {
+                if column.data_type.eq_ignore_ascii_case("integer") {
+                    auto.insert(column.name.clone());
+                }
+            }
+        }
+        DatabaseType::Postgres => auto.extend(texts(conn.query_with_params(
+            "SELECT column_name::text FROM information_schema.columns \
+             WHERE table_schema = COALESCE($1, current_schema()) AND table_name = $2 \
+               AND (is_identity = 'YES' OR is_generated = 'ALWAYS')",
+            &[schema, name],
+        )?)),
+        DatabaseType::Mysql => auto.extend(texts(conn.query_with_params(
+            "SELECT column_name FROM information_schema.columns \
+             WHERE table_schema = COALESCE(?, DATABASE()) AND table_name = ? \
+               AND (extra LIKE '%auto_increment%' OR extra LIKE '%GENERATED%')",
+            &[schema, name],
+        )?)),
+        DatabaseType::Duckdb => {}
+    }
+    Ok(auto)
+}
+
+/// Columns covered on their own by a primary key or unique constraint.
+fn unique_columns(conn: &dyn DatabaseConnection, table: &CatalogTable) -> Result<HashSet<String>, DbError> {
+    let db_type = conn.info().db_type;
+    let schema = table.schema.clone().map_or(QueryParam::Null, QueryParam::Text);
+    let name = QueryParam::Text(bare_name(table).to_string());
+    let mut unique = HashSet::new();
+    let pk: Vec<&ColumnInfo> = table.columns.iter().filter(|c| c.primary_key).collect();
+    if let [column] = pk.as_slice() {
+        unique.insert(column.name.clone());
+    }
+
+    unique.extend(texts(match db_type {
+        DatabaseType::Sqlite => conn.query_with_params(
+            "SELECT ii.name FROM pragma_index_list(?1) il JOIN pragma_index_info(il.name) ii \
+             WHERE il.\"unique\" = 1 GROUP BY il.name HAVING COUNT(*) = 1",
+            &[name],
+        )?,
+        DatabaseType::Postgres => conn.query_with_params(
+            "SELECT a.attname::text FROM pg_index i \
+             JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = i.indkey[0] \
+             WHERE i.indrelid = $1::regclass AND i.indisunique AND i.indnatts = 1",
+            &[QueryParam::Text(quote_table(db_type, &table.name))],
+        )?,
+        DatabaseType::Mysql => conn.query_with_params(
+            "SELECT MIN(column_name) FROM information_schema.statistics \
+             WHERE table_schema = COALESCE(?, DATABASE()) AND table_name = ? AND non_unique = 0 \
+             GROUP BY index_name HAVING COUNT(*) = 1",
+            &[schema, name],
+        )?,
+        DatabaseType::Duckdb => conn.query_with_params(
+            "SELECT constraint_column_names[1] FROM duckdb_constraints() \
+             WHERE schema_name = COALESCE(?, current_schema()) AND table_name = ? \
+               AND constraint_type IN ('UNIQUE', 'PRIMARY KEY') AND len(constraint_column_names) = 1",
+            &[schema, name],
+        )?,
+    }));
+    Ok(unique)
+}
+
+/// Distinct key tuples of the parent table, to pick child references from.
+fn sample_parent(conn: &dyn DatabaseConnection, db_type: DatabaseType, fk: &ForeignKeyInfo) -> Result<Vec<Vec<Option<String>>>, DbError> {
+    let parent = match &fk.referenced_schema {
+        Some(schema) => format!("{}.{}", schema, fk.referenced_table),
+        None => fk.referenced_table.clone(),
+    };
+    let columns: Vec<String> = fk.referenced_columns.iter().map(|c| quote_ident(db_type, c)).collect();
+    let not_null: Vec<String> = columns.iter().map(|c| format!("{} IS NOT NULL", c)).collect();
+    let sql = format!(
+        "SELECT DISTINCT {} FROM {} WHERE {} LIMIT {}",
+        columns.join(", "),
+        quote_table(db_type, &parent),
+        not_null.join(" AND "),
+        PARENT_SAMPLE,
+    );
+    let result = conn.query(&sql)?;
+    Ok(result.rows.iter()
+        .map(|row| (0..columns.len()).map(|i| cell_text(row, i)).collect())
+        .collect())
+}
+
+/// Values `column` already holds, so unique columns do not repeat them.
+fn existing_values(conn: &dyn DatabaseConnection, db_type: DatabaseType, table: &str, column: &str) -> Result<HashSet<String>, DbError> {
+    let col = quote_ident(db_type, column);
+    let sql = format!("SELECT DISTINCT {} FROM {} WHERE {} IS NOT NULL", col, quote_table(db_type, table), col);
+    Ok(texts(conn.query(&sql)?))
+}
+
+fn current_max(conn: &dyn DatabaseConnection, db_type: DatabaseType, table: &str, column: &str) -> i64 {
+    let sql = format!("SELECT MAX({}) FROM {}", quote_ident(db_type, column), quote_table(db_type, table));
+    conn.query(&sql).ok()
+        .and_then(|r| r.rows.first().and_then(|row| cell_text(row, 0)))
+        .and_then(|max| max.parse::<f64>().ok())
+        .map_or(0, |max| max as i64)
+}
+
+/// Inserts `count` rows of generated data into `table` in one transaction.
+pub fn generate(
+    conn: &dyn DatabaseConnection,
+    catalog: &Catalog,
+    table: &str,
+    count: u64,
+    rules: &HashMap<String, GeneratorRule>,
+) -> Result<GenerateResult, DbError> {
+    if count == 0 || count > MAX_ROWS {
+        return Err(DbError::invalid_input(format!("Row count must be between 1 and {}", MAX_ROWS)));
+    }
+    let db_type = conn.info().db_type;
+    let table = catalog.tables.iter()
+        .find(|t| t.name == table)
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("Table '{}' not found", table)))?;
+    if let Some(name) = rules.keys().find(|name| !table.columns.iter().any(|c| &c.name == *name)) {
+        return Err(DbError::new(DbErrorKind::NotFound, format!("Column '{}' not found", name)));
+    }
+
+    let auto = auto_columns(conn, table)?;
+    let unique = unique_columns(conn, table)?;
+
+    let mut parents = Vec::new();
+    let mut foreign: HashMap<&str, (usize, usize)> = HashMap::new();
+    for fk in &table.foreign_keys {
+        if fk.columns.iter().any(|c| rules.contains_key(c)) {
+            continue;
+        }
+        for (position, column) in fk.columns.iter().enumerate() {
+            foreign.insert(column, (parents.len(), position));
+        }
+        parents.push(sample_parent(conn, db_type, fk)?);
+    }
+
+    let mut skipped_columns = Vec::new();
+    let mut columns = Vec::new();
+    for info in &table.columns {
+        let class = classify(&info.data_type);
+        let fill = match rules.get(&info.name) {
+            Some(GeneratorRule::Skip) => None,
+            Some(rule) => Some(Fill::Rule { rule: rule.clone(), guessed: false }),
+            None if auto.contains(&info.name) => None,
+            None => match foreign.get(info.name.as_str()) {
+                Some(&(group, position)) => {
+                    if parents[group].is_empty() && !info.nullable {
+                        return Err(DbError::invalid_input(format!("Column '{}' references a table with no rows", info.name)));
+                    }
+                    Some(Fill::Foreign { group, position })
+                }
+                None if unique.contains(&info.name) && class == TypeClass::Integer => {
+                    Some(Fill::Sequence(current_max(conn, db_type, &table.name, &info.name)))
+                }
+                None if class == TypeClass::Time => Some(Fill::Time),
+                // Defaults beat made-up bytes.
+                None if class == TypeClass::Binary && (info.nullable || info.default.is_some()) => None,
+                None => Some(Fill::Rule { rule: guess(info, class), guessed: true }),
+            },
+        };
+        match fill {
+            Some(fill) => columns.push(Column {
+                name: info.name.clone(),
+                nullable: info.nullable,
+                unique: unique.contains(&info.name),
+                max_length: max_length(&info.data_type),
+                fill,
+            }),
+            None => skipped_columns.push(info.name.clone()),
+        }
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut seen: Vec<HashSet<String>> = columns.iter().map(|column| match column.fill {
+        Fill::Rule { .. } if column.unique => existing_values(conn, db_type, &table.name, &column.name),
+        _ => Ok(HashSet::new()),
+    }).collect::<Result<_, _>>()?;
+    // Parent keys behind a unique column are drawn without replacement, leaving
+    // out the ones already referenced.
+    let mut exhaustive = vec![false; parents.len()];
+    for column in columns.iter().filter(|c| c.unique) {
+        if let Fill::Foreign { group, position } = column.fill {
+            let used = existing_values(conn, db_type, &table.name, &column.name)?;
+            parents[group].retain(|tuple| !matches!(&tuple[position], Some(key) if used.contains(key)));
+            parents[group].shuffle(&mut rng);
+            exhaustive[group] = true;
+        }
+    }
+    let mut suffixes = vec![0u64; columns.len()];
+    let mut rows: Vec<Vec<QueryParam>> = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let picks: Vec<Option<Vec<Option<String>>>> = parents.iter_mut().zip(&exhaustive).map(|(p, &exhaustive)| match exhaustive {
+            true => p.pop(),
+            false => p.choose(&mut rng).cloned(),
+        }).collect();
+        let mut row = Vec::with_capacity(columns.len());
+        for (i, column) in columns.iter_mut().enumerate() {
+            let value = match &mut column.fill {
+                Fill::Foreign { group, position } => match &picks[*group] {
+                    Some(tuple) => tuple[*position].clone().map_or(QueryParam::Null, QueryParam::Text),
+                    None if exhaustive[*group] && !column.nullable => {
+                        return Err(DbError::invalid_input(format!("Not enough unused parent rows for unique '{}'", column.name)));
+                    }
+                    None => QueryParam::Null,
+                },
+                Fill::Sequence(last) => {
+                    *last += 1;
+                    QueryParam::Int(*last)
+                }
+                Fill::Time => QueryParam::Text(format!(
+                    "{:02}:{:02}:{:02}",
+                    rng.gen_range(0..24), rng.gen_range(0..60), rng.gen_range(0..60),
+                )),
+                Fill::Rule { guessed: true, .. } if column.nullable && !column.unique && rng.gen_bool(NULL_RATIO) => QueryParam::Null,
+                Fill::Rule { rule, guessed } => {
+                    let mut value = fit(rule_value(&mut rng, rule), column.max_length);
+                    if column.unique {
+                        let mut attempts = 1;
+                        while value.to_text().is_some_and(|key| seen[i].contains(&key)) {
+                            if attempts == UNIQUE_ATTEMPTS {
+                                return Err(DbError::invalid_input(format!("Could not generate enough unique values for '{}'", column.name)));
+                            }
+                            value = match value {
+                                // Guessed text such as single words runs out of values quickly.
+                                QueryParam::Text(text) if *guessed => {
+                                    suffixes[i] += 1;
+                                    suffixed(&text, suffixes[i], column.max_length)
+                                }
+                                _ => fit(rule_value(&mut rng, rule), column.max_length),
+                            };
+                            attempts += 1;
+                        }
+                    }
+                    value
+                }
+            };
+            if column.unique {
+                if let Some(key) = value.to_text() {
+                    seen[i].insert(key);
+                }
+            }
+            row.push(value);
+        }
+        rows.push(row);
+    }
+
+    let target = quote_table(db_type, &table.name);
+    let statements: Vec<(String, Vec<QueryParam>)> = if columns.is_empty() {
+        let sql = match db_type {
+            DatabaseType::Mysql => format!("INSERT INTO {} () VALUES ()", target),
+            _ => format!("INSERT INTO {} DEFAULT VALUES", target),
+        };
+        (0..count).map(|_| (sql.clone(), Vec::new())).collect()
+    } else {
+        let names: Vec<String> = columns.iter().map(|c| quote_ident(db_type, &c.name)).collect();
+        let batch_rows = (MAX_PARAMS / columns.len()).clamp(1, MAX_BATCH_ROWS);
+        rows.chunks(batch_rows).map(|batch| {
+            let mut index = 0;
+            let tuples: Vec<String> = batch.iter().map(|row| {
+                let marks: Vec<String> = row.iter().map(|_| {
+                    index += 1;
+                    placeholder(db_type, index)
+                }).collect();
+                format!("({})", marks.join(", "))
+            }).collect();
+            let sql = format!("INSERT INTO {} ({}) VALUES {}", target, names.join(", "), tuples.join(", "));
+            (sql, batch.concat())
+        }).collect()
+    };
+
+    let result = conn.execute_in_transaction(&statements)?;
+    Ok(GenerateResult {
+        inserted: result.rows_affected,
+        skipped_columns,
+        execution_time_ms: result.execution_time_ms,
+    })
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use sqlparser::ast::{AlterTableOperation, FromTable, ObjectType, Statement, TableFactor, TableWithJoins};
+use sqlparser::parser::Parser;
+use super::connection::DatabaseConnection;
+use super::dialect::sql_dialect;
+use super::types::DatabaseType;
+
+/// How long a confirmation token stays valid.
+const TOKEN_TTL: Duration = Duration::from_secs(300);
+
+/// A statement that destroys data wholesale and needs explicit confirmation.
+#[derive(Debug, Clone)]
+pub struct DestructiveOp {
+    /// Short description, e.g. `DELETE without WHERE`.
+    pub operation: String,
+    /// Tables whose rows are affected; empty for objects without rows.
+    pub tables: Vec<String>,
+}
+
+fn table_name(table: &TableWithJoins) -> Option<String> {
+    match &table.relation {
+        TableFactor::Table { name, .. } => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// Classifies `sql`. Statements the parser cannot read are only flagged when
+/// they start with DROP or TRUNCATE; everything else is left to the driver.
+pub fn analyze(sql: &str, db_type: DatabaseType) -> Option<DestructiveOp> {
+    let statements = match Parser::parse_sql(sql_dialect(db_type).as_ref(), sql) {
+        Ok(statements) => statements,
+        Err(_) => {
+            let first = sql.split_whitespace().next().unwrap_or_default().to_uppercase();
+            return matches!(first.as_str(), "DROP" | "TRUNCATE")
+                .then(|| DestructiveOp { operation: first, tables: Vec::new() });
+        }
+    };
+
+    let op = |operation: &str, tables: Vec<String>| Some(DestructiveOp { operation: operation.into(), tables });
+    match statements.first()? {
+        Statement::Delete(delete) if delete.selection.is_none() => {
+            let from = match &delete.from {
+                FromTable::WithFromKeyword(t) | FromTable::WithoutKeyword(t) => t,
+            };
+            op("DELETE without WHERE", from.iter().filter_map(table_name).collect())
+        }
+        Statement::Update { table, selection: None, .. } => {
+            op("UPDATE without WHERE", table_name(table).into_iter().collect())
+        }
+        Statement::Truncate { table_names, .. } => {
+            op("TRUNCATE", table_names.iter().map(|t| t.name.to_string()).collect())
+        }
+        Statement::Drop { object_type, names, .. } => {
+            let tables = match object_type {
+                ObjectType::Table => names.iter().map(|n| n.to_string()).collect(),
+                _ => Vec::new(),
+            };
+            op(&format!("DROP {}", object_type), tables)
+        }
+        Statement::AlterTable { name, operations, .. }
+            if operations.iter().any(|o| matches!(o, AlterTableOperation::DropColumn { .. })) =>
+        {
+            op("ALTER TABLE ... DROP COLUMN", vec![name.to_string()])
+        }
+        _ => None,
+    }
+}
+
+/// Rows the operation would touch, summed over its tables. `None` when there
+/// is nothing to count or a count fails.
+pub fn estimate_rows(conn: &dyn DatabaseConnection, op: &DestructiveOp) -> Option<u64> {
+    if op.tables.is_empty() {
+        return None;
+    }
+    op.tables.iter().try_fold(0u64, |total, table| {
+        // Names come from the parsed statement, already quoted as the user wrote them.
+        let result = conn.query(&format!("SELECT COUNT(*) FROM {}", table)).ok()?;
+        let count = result.rows.first()?.first()?.as_ref()?.as_text()?.parse::<u64>().ok()?;
+        Some(total + count)
+    })
+}
+
+struct Pending {
+    conn_id: String,
+    sql: String,
+    issued: Instant,
+}
+
+/// One-time tokens that let a specific destructive statement run once.
+#[derive(Default)]
+pub struct ConfirmationRegistry {
+    pending: Mutex<HashMap<String, Pending>>,
+}
+
+impl ConfirmationRegistry {
+    pub fn issue(&self, conn_id: &str, sql: &str) -> String {
+        let token = uuid::Uuid::new_v4().to_string();
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.retain(|_, p| p.issued.elapsed() < TOKEN_TTL);
+            pending.insert(token.clone(), Pending {
+                conn_id: conn_id.to_string(),
+                sql: sql.to_string(),
+                issued: Instant::now(),
+            });
+        }
+        token
+    }
+
+    /// Consumes `token`; true only if it was issued for this exact statement on
+    /// this connection and has not expired.
+    pub fn redeem(&self, token: &str, conn_id: &str, sql: &str) -> bool {
+        let Ok(mut pending) = self.pending.lock() else { return false };
+        match pending.remove(token) {
+            Some(p) => p.conn_id == conn_id && p.sql == sql && p.issued.elapsed() < TOKEN_TTL,
+            None => false,
+        }
+    }
+}
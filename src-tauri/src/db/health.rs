@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use super::catalog::CatalogCache;
+use super::connection::{ConnectionPool, DatabaseConnection, create_connection};
+use super::listen::{self, ListenRegistry};
+use super::types::{ConnectionConfig, DatabaseType};
+
+pub const CONNECTION_STATE_EVENT: &str = "db:connection-state";
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+/// A ping slower than this marks the connection degraded.
+const SLOW_PING: Duration = Duration::from_secs(2);
+const MAX_RECONNECT_ATTEMPTS: u32 = 3;
+const RECONNECT_BACKOFF: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthState {
+    Connected,
+    Degraded,
+    Lost,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionStateEvent {
+    pub conn_id: String,
+    pub state: HealthState,
+    pub message: Option<String>,
+}
+
+struct Monitored {
+    config: ConnectionConfig,
+    state: HealthState,
+    /// A reconnect thread is working on this connection.
+    reconnecting: bool,
+}
+
+/// Configs of open connections, kept so a dropped connection can be rebuilt.
+#[derive(Default)]
+pub struct HealthMonitor {
+    entries: Mutex<HashMap<String, Monitored>>,
+}
+
+impl HealthMonitor {
+    pub fn track(&self, config: ConnectionConfig) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(config.id.clone(), Monitored { config, state: HealthState::Connected, reconnecting: false });
+        }
+    }
+
+    pub fn untrack(&self, conn_id: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(conn_id);
+        }
+    }
+
+    pub fn states(&self) -> Vec<ConnectionStateEvent> {
+        self.entries.lock()
+            .map(|entries| entries.iter()
+                .map(|(id, m)| ConnectionStateEvent {
+                    conn_id: id.clone(),
+                    state: m.state,
+                    message: None,
+                })
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// Config the connection was opened with.
+    pub fn config(&self, conn_id: &str) -> Option<ConnectionConfig> {
+        self.entries.lock().ok()?.get(conn_id).map(|m| m.config.clone())
+    }
+
+    fn is_tracked(&self, conn_id: &str) -> bool {
+        self.entries.lock().map(|e| e.contains_key(conn_id)).unwrap_or(false)
+    }
+
+    /// Marks a reconnect as started or finished; starting returns false when
+    /// one is already running.
+    fn set_reconnecting(&self, conn_id: &str, reconnecting: bool) -> bool {
+        let Ok(mut entries) = self.entries.lock() else { return false };
+        match entries.get_mut(conn_id) {
+            Some(m) if m.reconnecting != reconnecting => {
+                m.reconnecting = reconnecting;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Records the new state; returns whether it changed.
+    fn set_state(&self, conn_id: &str, state: HealthState) -> bool {
+        let Ok(mut entries) = self.entries.lock() else { return false };
+        match entries.get_mut(conn_id) {
+            Some(m) if m.state != state => {
+                m.state = state;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Starts the background thread that pings every tracked connection.
+pub fn spawn_health_checker(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECK_INTERVAL);
+        check_all(&app);
+    });
+}
+
+fn emit_if_changed(app: &AppHandle, conn_id: &str, state: HealthState, message: Option<String>) {
+    if app.state::<HealthMonitor>().set_state(conn_id, state) {
+        let _ = app.emit(CONNECTION_STATE_EVENT, ConnectionStateEvent {
+            conn_id: conn_id.to_string(),
+            state,
+            message,
+        });
+    }
+}
+
+fn check_all(app: &AppHandle) {
+    let monitor = app.state::<HealthMonitor>();
+    let targets: Vec<ConnectionConfig> = match monitor.entries.lock() {
+        // Lost connections stay lost until the user connects again.
+        Ok(entries) => entries.values()
+            .filter(|m| m.state != HealthState::Lost && !m.reconnecting)
+            .map(|m| m.config.clone())
+            .collect(),
+        Err(_) => return,
+    };
+
+    for config in targets {
+        let Ok(conn) = app.state::<ConnectionPool>().get(&config.id) else {
+            monitor.untrack(&config.id);
+            continue;
+        };
+        // A connection busy with a query is evidently alive; check it next round.
+        let ping = match conn.try_lock() {
+            Ok(conn) => {
+                let start = Instant::now();
+                Some((conn.is_alive(), start.elapsed()))
+            }
+            Err(_) => None,
+        };
+
+        match ping {
+            None => {}
+            Some((true, elapsed)) if elapsed > SLOW_PING => emit_if_changed(
+                app,
+                &config.id,
+                HealthState::Degraded,
+                Some(format!("Ping took {} ms", elapsed.as_millis())),
+            ),
+            Some((true, _)) => emit_if_changed(app, &config.id, HealthState::Connected, None),
+            Some((false, _)) if is_in_memory(&config) => emit_if_changed(
+                app,
+                &config.id,
+                HealthState::Lost,
+                Some("In-memory database closed; its data cannot be restored".into()),
+            ),
+            // Retries sleep between attempts; other connections are checked meanwhile.
+            Some((false, _)) if monitor.set_reconnecting(&config.id, true) => {
+                let app = app.clone();
+                std::thread::spawn(move || {
+                    reconnect(&app, &config);
+                    app.state::<HealthMonitor>().set_reconnecting(&config.id, false);
+                });
+            }
+            Some((false, _)) => {}
+        }
+    }
+}
+
+/// Puts `conn` in the pool under `conn_id`, listening on the channels the old
+/// connection listened on. Anything still holding the old connection finishes
+/// against it.
+pub(super) fn replace_connection(app: &AppHandle, conn_id: &str, conn: Box<dyn DatabaseConnection + Send>) {
+    let channels = app.state::<ListenRegistry>().channels(conn_id);
+    listen::restore(app, conn_id, conn.as_ref(), &channels);
+    app.state::<ConnectionPool>().insert(conn_id.to_string(), conn);
+    app.state::<CatalogCache>().invalidate(conn_id);
+}
+
+/// A fresh connection to an in-memory database would be a different, empty database.
+fn is_in_memory(config: &ConnectionConfig) -> bool {
+    let path = config.file_path.as_deref().map(str::trim).unwrap_or("");
+    matches!(config.db_type, DatabaseType::Sqlite | DatabaseType::Duckdb)
+        && (path.is_empty() || path == ":memory:")
+}
+
+fn reconnect(app: &AppHandle, config: &ConnectionConfig) {
+    emit_if_changed(app, &config.id, HealthState::Degraded, Some("Connection lost, reconnecting".into()));
+
+    let mut last_error = String::new();
+    for attempt in 1..=MAX_RECONNECT_ATTEMPTS {
+        match create_connection(config) {
+            Ok(conn) => {
+                if !app.state::<HealthMonitor>().is_tracked(&config.id) {
+                    // Disconnected by the user while we were reconnecting.
+                    conn.close();
+                    return;
+                }
+                replace_connection(app, &config.id, conn);
+                emit_if_changed(app, &config.id, HealthState::Connected, Some("Reconnected".into()));
+                return;
+            }
+            Err(e) => last_error = e.message,
+        }
+        if attempt < MAX_RECONNECT_ATTEMPTS {
+            std::thread::sleep(RECONNECT_BACKOFF * attempt);
+        }
+    }
+
+    emit_if_changed(
+        app,
+        &config.id,
+        HealthState::Lost,
+        Some(format!("Reconnect failed after {} attempts: {}", MAX_RECONNECT_ATTEMPTS, last_error)),
+    );
+}
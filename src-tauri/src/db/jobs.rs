@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Cancellation flags for long-running commands, keyed by a caller-chosen job id.
+#[derive(Default)]
+pub struct CancelRegistry {
+    jobs: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+/// Handed to the job; unregisters it when dropped.
+pub struct CancelFlag<'a> {
+    registry: &'a CancelRegistry,
+    job_id: String,
+    flag: Arc<AtomicBool>,
+}
+
+impl CancelFlag<'_> {
+    /// A shareable view of the flag for work running on another thread.
+    pub fn token(&self) -> CancelToken {
+        CancelToken(self.flag.clone())
+    }
+}
+
+impl Drop for CancelFlag<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut jobs) = self.registry.jobs.lock() {
+            jobs.remove(&self.job_id);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl CancelRegistry {
+    pub fn register(&self, job_id: &str) -> Result<CancelFlag<'_>, String> {
+        let mut jobs = self.jobs.lock().map_err(|_| "Lock error")?;
+        if jobs.contains_key(job_id) {
+            return Err(format!("Job {} is already running", job_id));
+        }
+        let flag = Arc::new(AtomicBool::new(false));
+        jobs.insert(job_id.to_string(), flag.clone());
+        Ok(CancelFlag { registry: self, job_id: job_id.to_string(), flag })
+    }
+
+    /// Returns whether a running job with this id was found.
+    pub fn cancel(&self, job_id: &str) -> bool {
+        self.jobs.lock()
+            .ok()
+            .and_then(|jobs| jobs.get(job_id).map(|flag| flag.store(true, Ordering::Relaxed)))
+            .is_some()
+    }
+}
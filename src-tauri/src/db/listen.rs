@@ -0,0 +1,95 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use super::connection::DatabaseConnection;
+use super::dialect::quote_ident;
+use super::types::*;
+
+pub const NOTIFICATION_EVENT: &str = "db:notification";
+
+/// Channels each connection listens on, so they can be re-subscribed after the
+/// health checker replaces a dropped connection.
+#[derive(Default)]
+pub struct ListenRegistry {
+    channels: Mutex<HashMap<String, HashSet<String>>>,
+}
+
+impl ListenRegistry {
+    pub fn add(&self, conn_id: &str, channel: &str) {
+        if let Ok(mut channels) = self.channels.lock() {
+            channels.entry(conn_id.to_string()).or_default().insert(channel.to_string());
+        }
+    }
+
+    /// Removes one channel, or all of them when `channel` is `None`.
+    pub fn remove(&self, conn_id: &str, channel: Option<&str>) {
+        if let Ok(mut channels) = self.channels.lock() {
+            match channel {
+                Some(channel) => {
+                    if let Some(set) = channels.get_mut(conn_id) {
+                        set.remove(channel);
+                    }
+                }
+                None => {
+                    channels.remove(conn_id);
+                }
+            }
+        }
+    }
+
+    pub fn channels(&self, conn_id: &str) -> Vec<String> {
+        self.channels.lock()
+            .ok()
+            .and_then(|channels| channels.get(conn_id).map(|set| set.iter().cloned().collect()))
+            .unwrap_or_default()
+    }
+}
+
+fn ensure_supported(conn: &dyn DatabaseConnection) -> Result<(), DbError> {
+    if conn.info().db_type != DatabaseType::Postgres {
+        return Err(DbError::invalid_input("LISTEN/NOTIFY is only supported on PostgreSQL"));
+    }
+    Ok(())
+}
+
+/// Starts forwarding the connection's notifications as `db:notification`
+/// events, unless that already happened. The task ends with the connection.
+fn forward(app: &AppHandle, conn_id: &str, conn: &dyn DatabaseConnection) {
+    let Some(mut receiver) = conn.take_notifications() else {
+        return;
+    };
+    let app = app.clone();
+    let conn_id = conn_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        while let Some(n) = receiver.recv().await {
+            let _ = app.emit(NOTIFICATION_EVENT, NotificationEvent {
+                conn_id: conn_id.clone(),
+                channel: n.channel,
+                payload: n.payload,
+                process_id: n.process_id,
+            });
+        }
+    });
+}
+
+pub fn listen(app: &AppHandle, conn_id: &str, conn: &dyn DatabaseConnection, channel: &str) -> Result<(), DbError> {
+    ensure_supported(conn)?;
+    conn.execute(&format!("LISTEN {}", quote_ident(DatabaseType::Postgres, channel)))?;
+    forward(app, conn_id, conn);
+    Ok(())
+}
+
+pub fn unlisten(conn: &dyn DatabaseConnection, channel: Option<&str>) -> Result<(), DbError> {
+    ensure_supported(conn)?;
+    let target = channel.map_or_else(|| "*".to_string(), |c| quote_ident(DatabaseType::Postgres, c));
+    conn.execute(&format!("UNLISTEN {}", target)).map(|_| ())
+}
+
+/// Re-subscribes a freshly reconnected connection to its previous channels.
+pub fn restore(app: &AppHandle, conn_id: &str, conn: &dyn DatabaseConnection, channels: &[String]) {
+    for channel in channels {
+        if let Err(e) = listen(app, conn_id, conn, channel) {
+            log::warn!("Could not re-listen on {} for {}: {}", channel, conn_id, e);
+        }
+    }
+}
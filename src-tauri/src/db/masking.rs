@@ -0,0 +1,638 @@
+use std::ops::ControlFlow;
+use rusqlite::{params, Connection, Row};
+use sha2::{Digest, Sha256};
+use sqlparser::ast::{
+    visit_relations, Expr, FunctionArg, FunctionArgExpr, FunctionArguments, Ident, ObjectName, Query,
+    SelectItem, SetExpr, Statement, TableAlias, TableAliasColumnDef, TableFactor, TableWithJoins, Visit, Visitor,
+};
+use sqlparser::parser::Parser;
+use super::dialect::sql_dialect;
+use super::types::*;
+
+/// Case-insensitive glob with `*` and `?`.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.to_lowercase().chars().collect();
+    let n: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ni));
+            pi += 1;
+        } else if let Some((bp, bn)) = backtrack {
+            pi = bp + 1;
+            ni = bn + 1;
+            backtrack = Some((bp, bn + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+fn normalize_name(name: &str) -> String {
+    name.chars().filter(|c| !matches!(c, '"' | '`' | '[' | ']')).collect::<String>().to_lowercase()
+}
+
+/// `schema.table` matches a rule for `table` and vice versa.
+fn same_table(a: &str, b: &str) -> bool {
+    let (a, b) = (normalize_name(a), normalize_name(b));
+    let last = |s: &str| s.rsplit('.').next().unwrap_or_default().to_string();
+    a == b || last(&a) == last(&b)
+}
+
+/// Where a result column's values come from.
+#[derive(Debug, Clone)]
+enum Origin {
+    Column { table: String, column: String },
+    /// Could not be traced; table-specific rules on any table the statement
+    /// reads apply to it.
+    Opaque,
+}
+
+/// One projection item of a query.
+#[derive(Debug, Clone)]
+enum Item {
+    /// A single output column; `name` is `None` for unaliased expressions,
+    /// whose result name is up to the database.
+    Column { name: Option<String>, origins: Vec<Origin> },
+    /// Zero or more columns expanded from `*`, traced by name.
+    Expand(Vec<Rel>),
+}
+
+/// A relation in a `FROM` clause.
+#[derive(Debug, Clone)]
+enum Rel {
+    Table(String),
+    Derived(Vec<Item>),
+    Opaque,
+}
+
+impl Rel {
+    /// Origins of the column `name` read from this relation.
+    fn lookup(&self, name: &str) -> Vec<Origin> {
+        match self {
+            Rel::Table(table) => vec![Origin::Column { table: table.clone(), column: name.to_string() }],
+            Rel::Derived(items) => {
+                let mut found = Vec::new();
+                let mut matched = false;
+                for item in items {
+                    match item {
+                        Item::Column { name: Some(n), origins } if normalize_name(n) == normalize_name(name) => {
+                            found.extend(origins.iter().cloned());
+                            matched = true;
+                        }
+                        Item::Column { .. } => {}
+                        Item::Expand(rels) => found.extend(rels.iter().flat_map(|r| r.lookup(name))),
+                    }
+                }
+                // The name may be one the database gave an unaliased expression.
+                if !matched {
+                    found.extend(items.iter()
+                        .filter_map(|item| match item {
+                            Item::Column { name: None, origins } => Some(origins.iter().cloned()),
+                            _ => None,
+                        })
+                        .flatten());
+                }
+                found
+            }
+            Rel::Opaque => vec![Origin::Opaque],
+        }
+    }
+
+    /// Origins of the whole row, as in PostgreSQL's `SELECT t FROM t`.
+    fn row(&self) -> Vec<Origin> {
+        match self {
+            Rel::Derived(items) => items.iter()
+                .flat_map(|item| match item {
+                    Item::Column { origins, .. } => origins.clone(),
+                    Item::Expand(rels) => rels.iter().flat_map(Rel::row).collect(),
+                })
+                .collect(),
+            Rel::Table(_) | Rel::Opaque => vec![Origin::Opaque],
+        }
+    }
+}
+
+/// Relations visible to a query, with its CTEs and the enclosing query's scope.
+struct Scope<'a> {
+    ctes: Vec<(String, Vec<Item>)>,
+    relations: Vec<(String, Rel)>,
+    parent: Option<&'a Scope<'a>>,
+}
+
+impl<'a> Scope<'a> {
+    fn new(parent: Option<&'a Scope<'a>>) -> Scope<'a> {
+        Scope { ctes: Vec::new(), relations: Vec::new(), parent }
+    }
+
+    fn cte(&self, name: &str) -> Option<&[Item]> {
+        self.ctes.iter()
+            .find(|(n, _)| *n == normalize_name(name))
+            .map(|(_, items)| &items[..])
+            .or_else(|| self.parent?.cte(name))
+    }
+
+    fn relation(&self, alias: &str) -> Option<&Rel> {
+        self.relations.iter()
+            .find(|(n, _)| *n == normalize_name(alias))
+            .map(|(_, rel)| rel)
+            .or_else(|| self.parent?.relation(alias))
+    }
+
+    /// Origins of an identifier; unqualified names may come from any relation in scope.
+    fn resolve(&self, parts: &[Ident]) -> Vec<Origin> {
+        match parts {
+            [] => Vec::new(),
+            [name] => {
+                let mut origins = Vec::new();
+                let mut scope = Some(self);
+                while let Some(s) = scope {
+                    for (alias, rel) in &s.relations {
+                        origins.extend(rel.lookup(&name.value));
+                        if *alias == normalize_name(&name.value) {
+                            origins.extend(rel.row());
+                        }
+                    }
+                    scope = s.parent;
+                }
+                origins
+            }
+            [.., qualifier, column] => match self.relation(&qualifier.value) {
+                Some(rel) => rel.lookup(&column.value),
+                None => vec![Origin::Opaque],
+            },
+        }
+    }
+
+    fn wildcard(&self, qualifier: &ObjectName) -> Rel {
+        qualifier.0.last()
+            .and_then(|q| self.relation(&q.value))
+            .cloned()
+            .unwrap_or(Rel::Opaque)
+    }
+
+    fn add_factor(&mut self, factor: &TableFactor) {
+        let rename = |alias: &Option<TableAlias>, rel: Rel| match alias {
+            Some(alias) if !alias.columns.is_empty() => renamed(rel, &alias.columns),
+            _ => rel,
+        };
+        match factor {
+            TableFactor::Table { name, alias, args, .. } => {
+                let rel = match (args, name.0.as_slice()) {
+                    (Some(_), _) => Rel::Opaque,
+                    (None, [single]) if self.cte(&single.value).is_some() => {
+                        Rel::Derived(self.cte(&single.value).unwrap_or_default().to_vec())
+                    }
+                    _ => Rel::Table(name.to_string()),
+                };
+                let label = alias.as_ref().map(|a| a.name.value.clone())
+                    .or_else(|| name.0.last().map(|i| i.value.clone()))
+                    .unwrap_or_default();
+                let rel = rename(alias, rel);
+                self.relations.push((normalize_name(&label), rel));
+            }
+            TableFactor::Derived { subquery, alias, .. } => {
+                let rel = rename(alias, Rel::Derived(query_items(subquery, Some(self))));
+                let label = alias.as_ref().map(|a| normalize_name(&a.name.value)).unwrap_or_default();
+                self.relations.push((label, rel));
+            }
+            TableFactor::NestedJoin { table_with_joins, alias } => {
+                let before = self.relations.len();
+                self.add_from(std::slice::from_ref(table_with_joins));
+                if let Some(alias) = alias {
+                    let nested = self.relations[before..].iter().map(|(_, r)| r.clone()).collect();
+                    self.relations.push((normalize_name(&alias.name.value), Rel::Derived(vec![Item::Expand(nested)])));
+                }
+            }
+            // Table functions, UNNEST, PIVOT and the like.
+            _ => self.relations.push((String::new(), Rel::Opaque)),
+        }
+    }
+
+    fn add_from(&mut self, from: &[TableWithJoins]) {
+        for table in from {
+            self.add_factor(&table.relation);
+            for join in &table.joins {
+                self.add_factor(&join.relation);
+            }
+        }
+    }
+
+    /// Origins of every column an expression reads, including through subqueries.
+    fn expr_origins(&self, expr: &Expr) -> Vec<Origin> {
+        let mut collector = Collector { scope: self, depth: 0, origins: Vec::new() };
+        let _ = expr.visit(&mut collector);
+        collector.origins
+    }
+}
+
+/// Applies `AS t(a, b)` column aliases to a relation.
+fn renamed(rel: Rel, columns: &[TableAliasColumnDef]) -> Rel {
+    match rel {
+        Rel::Derived(items) if items.iter().all(|i| matches!(i, Item::Column { .. })) => Rel::Derived(
+            items.into_iter()
+                .enumerate()
+                .map(|(i, item)| match item {
+                    Item::Column { name, origins } => Item::Column {
+                        name: columns.get(i).map(|c| c.name.value.clone()).or(name),
+                        origins,
+                    },
+                    expand => expand,
+                })
+                .collect(),
+        ),
+        // Renamed table columns can no longer be matched by name.
+        _ => Rel::Opaque,
+    }
+}
+
+struct Collector<'s, 'a> {
+    scope: &'s Scope<'a>,
+    /// Nesting inside subqueries, whose identifiers resolve in their own scope.
+    depth: usize,
+    origins: Vec<Origin>,
+}
+
+impl Visitor for Collector<'_, '_> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<()> {
+        if self.depth == 0 {
+            for item in query_items(query, Some(self.scope)) {
+                match item {
+                    Item::Column { origins, .. } => self.origins.extend(origins),
+                    Item::Expand(rels) => self.origins.extend(rels.iter().flat_map(Rel::row)),
+                }
+            }
+        }
+        self.depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<()> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<()> {
+        if self.depth > 0 {
+            return ControlFlow::Continue(());
+        }
+        match expr {
+            Expr::Identifier(ident) => self.origins.extend(self.scope.resolve(std::slice::from_ref(ident))),
+            Expr::CompoundIdentifier(parts) => self.origins.extend(self.scope.resolve(parts)),
+            Expr::QualifiedWildcard(name, _) => self.origins.extend(self.scope.wildcard(name).row()),
+            // `to_json(t.*)`
+            Expr::Function(f) => {
+                if let FunctionArguments::List(list) = &f.args {
+                    for arg in &list.args {
+                        let (FunctionArg::Unnamed(arg) | FunctionArg::Named { arg, .. } | FunctionArg::ExprNamed { arg, .. }) = arg;
+                        if let FunctionArgExpr::QualifiedWildcard(name) = arg {
+                            self.origins.extend(self.scope.wildcard(name).row());
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+fn query_items(query: &Query, parent: Option<&Scope>) -> Vec<Item> {
+    let mut scope = Scope::new(parent);
+    if let Some(with) = &query.with {
+        for cte in &with.cte_tables {
+            let items = match renamed(Rel::Derived(query_items(&cte.query, Some(&scope))), &cte.alias.columns) {
+                Rel::Derived(items) => items,
+                rel => vec![Item::Expand(vec![rel])],
+            };
+            scope.ctes.push((normalize_name(&cte.alias.name.value), items));
+        }
+    }
+    set_items(&query.body, &scope)
+}
+
+fn set_items(body: &SetExpr, scope: &Scope) -> Vec<Item> {
+    match body {
+        SetExpr::Select(select) => {
+            let mut inner = Scope::new(Some(scope));
+            inner.add_from(&select.from);
+            select.projection.iter().map(|item| select_item(item, &inner)).collect()
+        }
+        SetExpr::Query(query) => query_items(query, Some(scope)),
+        SetExpr::SetOperation { left, right, .. } => {
+            let (left, right) = (set_items(left, scope), set_items(right, scope));
+            let positional = |items: &[Item]| items.iter().all(|i| matches!(i, Item::Column { .. }));
+            if left.len() != right.len() || !positional(&left) || !positional(&right) {
+                return vec![Item::Expand(vec![Rel::Opaque])];
+            }
+            left.into_iter()
+                .zip(right)
+                .map(|(l, r)| match (l, r) {
+                    (Item::Column { name, mut origins }, Item::Column { origins: more, .. }) => {
+                        origins.extend(more);
+                        Item::Column { name, origins }
+                    }
+                    (l, _) => l,
+                })
+                .collect()
+        }
+        SetExpr::Values(values) => {
+            let width = values.rows.first().map_or(0, Vec::len);
+            (0..width)
+                .map(|i| Item::Column {
+                    name: None,
+                    origins: values.rows.iter()
+                        .filter_map(|row| row.get(i))
+                        .flat_map(|e| scope.expr_origins(e))
+                        .collect(),
+                })
+                .collect()
+        }
+        SetExpr::Table(table) => {
+            let rel = match (&table.schema_name, &table.table_name) {
+                (_, Some(name)) if scope.cte(name).is_some() => Rel::Derived(scope.cte(name).unwrap_or_default().to_vec()),
+                (Some(schema), Some(name)) => Rel::Table(format!("{}.{}", schema, name)),
+                (None, Some(name)) => Rel::Table(name.clone()),
+                (_, None) => Rel::Opaque,
+            };
+            vec![Item::Expand(vec![rel])]
+        }
+        // Data-modifying statements in a CTE.
+        SetExpr::Insert(_) | SetExpr::Update(_) => vec![Item::Expand(vec![Rel::Opaque])],
+    }
+}
+
+fn select_item(item: &SelectItem, scope: &Scope) -> Item {
+    let all = || Item::Expand(scope.relations.iter().map(|(_, r)| r.clone()).collect());
+    match item {
+        // DuckDB's `COLUMNS(...)` expands to columns picked by name or pattern.
+        SelectItem::UnnamedExpr(Expr::Function(f)) if f.name.to_string().eq_ignore_ascii_case("columns") => all(),
+        SelectItem::UnnamedExpr(expr) => Item::Column {
+            name: match expr {
+                Expr::Identifier(ident) => Some(ident.value.clone()),
+                Expr::CompoundIdentifier(parts) => parts.last().map(|i| i.value.clone()),
+                _ => None,
+            },
+            origins: scope.expr_origins(expr),
+        },
+        SelectItem::ExprWithAlias { expr, alias } => Item::Column {
+            name: Some(alias.value.clone()),
+            origins: scope.expr_origins(expr),
+        },
+        // `* REPLACE (expr AS col)` and `* RENAME` put other values under column names.
+        SelectItem::Wildcard(options) | SelectItem::QualifiedWildcard(_, options)
+            if options.opt_replace.is_some() || options.opt_rename.is_some() => Item::Expand(vec![Rel::Opaque]),
+        SelectItem::Wildcard(_) => all(),
+        SelectItem::QualifiedWildcard(name, _) => Item::Expand(vec![scope.wildcard(name)]),
+    }
+}
+
+/// Where the columns of a result come from, as far as its SQL can be traced.
+#[derive(Debug, Clone)]
+pub struct Lineage {
+    /// Every table the statement reads; `None` if unknown.
+    tables: Option<Vec<String>>,
+    /// Projection of the statement; `None` if it cannot be traced.
+    items: Option<Vec<Item>>,
+}
+
+impl Lineage {
+    /// Traces the projection of `sql` back to table columns, through aliases,
+    /// expressions, subqueries and CTEs. Columns that cannot be traced (from SQL
+    /// that does not parse, several statements, procedure calls or table
+    /// functions) count as derived from every table the statement reads, or
+    /// from any table when those are unknown.
+    pub fn of_sql(sql: &str, db_type: DatabaseType) -> Lineage {
+        let Ok(statements) = Parser::parse_sql(sql_dialect(db_type).as_ref(), sql) else {
+            return Lineage { tables: None, items: None };
+        };
+        if statements.iter().any(|s| matches!(s, Statement::Call(_))) {
+            return Lineage { tables: None, items: None };
+        }
+        let mut tables = Vec::new();
+        let _ = visit_relations(&statements, |name| {
+            tables.push(name.to_string());
+            ControlFlow::<()>::Continue(())
+        });
+        let items = match statements.as_slice() {
+            [Statement::Query(query)] => Some(query_items(query, None)),
+            _ => None,
+        };
+        Lineage { tables: Some(tables), items }
+    }
+
+    /// A result read directly from `table`, with its own column names.
+    pub fn of_table(table: &str) -> Lineage {
+        Lineage {
+            tables: Some(vec![table.to_string()]),
+            items: Some(vec![Item::Expand(vec![Rel::Table(table.to_string())])]),
+        }
+    }
+
+    /// Origins of each of the result's `columns`.
+    fn origins(&self, columns: &[String]) -> Vec<Vec<Origin>> {
+        let opaque = || vec![vec![Origin::Opaque]; columns.len()];
+        let Some(items) = &self.items else { return opaque() };
+        let expands: Vec<usize> = items.iter()
+            .enumerate()
+            .filter(|(_, i)| matches!(i, Item::Expand(_)))
+            .map(|(i, _)| i)
+            .collect();
+        let origins_of = |item: &Item| match item {
+            Item::Column { origins, .. } => origins.clone(),
+            Item::Expand(_) => Vec::new(),
+        };
+        let (Some(&first), Some(&last)) = (expands.first(), expands.last()) else {
+            return match items.len() == columns.len() {
+                true => items.iter().map(origins_of).collect(),
+                false => opaque(),
+            };
+        };
+        let tail = items.len() - last - 1;
+        if columns.len() < first + tail {
+            return opaque();
+        }
+        // Columns between the first and last `*` are matched by name, except
+        // that any unaliased expression among them may be any of them.
+        let middle = &items[first..=last];
+        columns.iter()
+            .enumerate()
+            .map(|(i, column)| {
+                if i < first {
+                    origins_of(&items[i])
+                } else if i >= columns.len() - tail {
+                    origins_of(&items[items.len() - (columns.len() - i)])
+                } else {
+                    middle.iter()
+                        .flat_map(|item| match item {
+                            Item::Expand(rels) => rels.iter().flat_map(|r| r.lookup(column)).collect(),
+                            Item::Column { name: Some(n), origins } if normalize_name(n) == normalize_name(column) => origins.clone(),
+                            Item::Column { name: Some(_), .. } => Vec::new(),
+                            Item::Column { name: None, origins } => origins.clone(),
+                        })
+                        .collect()
+                }
+            })
+            .collect()
+    }
+}
+
+fn mask_text(mask: MaskType, value: &str) -> Option<String> {
+    match mask {
+        MaskType::Null => None,
+        MaskType::Redact => Some("***".into()),
+        MaskType::Hash => {
+            let digest = Sha256::digest(value.as_bytes());
+            Some(digest.iter().take(8).map(|b| format!("{:02x}", b)).collect())
+        }
+        MaskType::Partial => Some(match value.split_once('@') {
+            Some((local, domain)) => format!("{}***@{}", local.chars().next().unwrap_or('*'), domain),
+            None => {
+                let chars: Vec<char> = value.chars().collect();
+                if chars.len() <= 2 {
+                    "***".into()
+                } else {
+                    format!("{}***{}", chars[0], chars[chars.len() - 1])
+                }
+            }
+        }),
+    }
+}
+
+fn mask_cell(mask: MaskType, cell: &mut Option<CellValue>) {
+    *cell = match cell.take() {
+        None => None,
+        Some(CellValue::Text(text)) => mask_text(mask, &text).map(CellValue::Text),
+        // Binary previews are masked as a whole; hashing them would hash the preview.
+        Some(CellValue::Tagged(_)) => match mask {
+            MaskType::Null => None,
+            _ => Some(CellValue::Text("***".into())),
+        },
+    };
+}
+
+/// Mask for a result column named `column` whose values come from `origins`.
+/// The first matching rule wins.
+fn mask_for(rules: &[MaskingRule], column: &str, origins: &[Origin], tables: Option<&[String]>) -> Option<MaskType> {
+    rules.iter()
+        .find(|rule| match &rule.target {
+            MaskTarget::Pattern { pattern } => glob_match(pattern, column)
+                || origins.iter().any(|o| matches!(o, Origin::Column { column, .. } if glob_match(pattern, column))),
+            MaskTarget::Column { table, column: rule_column } => origins.iter().any(|o| match o {
+                Origin::Column { table: t, column: c } => {
+                    normalize_name(c) == normalize_name(rule_column) && same_table(t, table)
+                }
+                Origin::Opaque => match tables {
+                    Some(ts) => ts.iter().any(|t| same_table(t, table)),
+                    None => true,
+                },
+            }),
+        })
+        .map(|rule| rule.mask)
+}
+
+fn masks(rules: &[MaskingRule], columns: &[String], lineage: &Lineage) -> Vec<Option<MaskType>> {
+    lineage.origins(columns).iter()
+        .zip(columns)
+        .map(|(origins, column)| mask_for(rules, column, origins, lineage.tables.as_deref()))
+        .collect()
+}
+
+/// Masks `result` in place. Must run before the cell size limit so truncated
+/// cells are never retained unmasked.
+pub fn apply(rules: &[MaskingRule], result: &mut QueryResult, lineage: &Lineage) {
+    if rules.is_empty() {
+        return;
+    }
+    let masks: Vec<(usize, MaskType)> = masks(rules, &result.columns, lineage).into_iter()
+        .enumerate()
+        .filter_map(|(i, m)| m.map(|m| (i, m)))
+        .collect();
+    if masks.is_empty() {
+        return;
+    }
+    for row in &mut result.rows {
+        for (i, mask) in &masks {
+            if let Some(cell) = row.get_mut(*i) {
+                mask_cell(*mask, cell);
+            }
+        }
+    }
+}
+
+/// Whether any rule masks `column` of `table`.
+pub fn is_masked(rules: &[MaskingRule], table: &str, column: &str) -> bool {
+    !rules.is_empty() && masked(rules, &[column.to_string()], &Lineage::of_table(table))[0]
+}
+
+/// Which of the result's `columns` any rule masks.
+pub fn masked(rules: &[MaskingRule], columns: &[String], lineage: &Lineage) -> Vec<bool> {
+    masks(rules, columns, lineage).iter().map(Option::is_some).collect()
+}
+
+pub fn validate(rule: &MaskingRule) -> Result<(), String> {
+    let empty = match &rule.target {
+        MaskTarget::Pattern { pattern } => pattern.trim().is_empty(),
+        MaskTarget::Column { table, column } => table.trim().is_empty() || column.trim().is_empty(),
+    };
+    if empty {
+        return Err("Masking rule needs a column pattern or a table and column".into());
+    }
+    Ok(())
+}
+
+const COLUMNS: &str = "id, connection_id, kind, pattern, table_name, column_name, mask, created_at";
+
+fn from_row(row: &Row) -> rusqlite::Result<MaskingRule> {
+    let kind: String = row.get(2)?;
+    let target = if kind == "column" {
+        MaskTarget::Column { table: row.get(4)?, column: row.get(5)? }
+    } else {
+        MaskTarget::Pattern { pattern: row.get(3)? }
+    };
+    let mask: String = row.get(6)?;
+    Ok(MaskingRule {
+        id: row.get(0)?,
+        connection_id: row.get(1)?,
+        target,
+        mask: serde_json::from_value(serde_json::Value::String(mask)).unwrap_or(MaskType::Redact),
+        created_at: row.get(7)?,
+    })
+}
+
+pub fn list(conn: &Connection, connection_id: &str) -> rusqlite::Result<Vec<MaskingRule>> {
+    let sql = format!("SELECT {} FROM masking_rules WHERE connection_id = ?1 ORDER BY created_at", COLUMNS);
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params![connection_id], from_row)?;
+    rows.collect()
+}
+
+pub fn save(conn: &Connection, rule: &MaskingRule) -> rusqlite::Result<()> {
+    let (kind, pattern, table, column) = match &rule.target {
+        MaskTarget::Pattern { pattern } => ("pattern", Some(pattern.as_str()), None, None),
+        MaskTarget::Column { table, column } => ("column", None, Some(table.as_str()), Some(column.as_str())),
+    };
+    let mask = serde_json::to_value(rule.mask).ok().and_then(|v| v.as_str().map(str::to_string));
+    conn.execute(
+        "INSERT INTO masking_rules (id, connection_id, kind, pattern, table_name, column_name, mask, created_at) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8) \
+         ON CONFLICT(id) DO UPDATE SET \
+           kind = excluded.kind, pattern = excluded.pattern, table_name = excluded.table_name, \
+           column_name = excluded.column_name, mask = excluded.mask",
+        params![rule.id, rule.connection_id, kind, pattern, table, column, mask, rule.created_at],
+    )?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM masking_rules WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
@@ -2,147 +2,1094 @@ mod connection;
 mod sqlite;
 mod postgres;
 mod mysql;
+mod duckdb;
 mod types;
+mod dialect;
+mod saved_queries;
+mod store;
+mod catalog;
+mod health;
+mod results;
+mod blobs;
+mod vault;
+mod audit;
+mod guard;
+mod browse;
+mod jobs;
+mod search;
+mod masking;
+mod listen;
+mod sessions;
+mod privileges;
+mod ddl;
+mod generate;
+mod transfer;
+mod compare;
+mod cron;
+mod scheduler;
 
-use std::sync::Mutex;
-use tauri::State;
-use connection::{ConnectionPool, create_connection};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+use connection::{ConnectionPool, create_connection, run_blocking, with_connection};
+pub use catalog::CatalogCache;
+pub use guard::ConfirmationRegistry;
+pub use jobs::CancelRegistry;
+pub use listen::ListenRegistry;
+pub use health::{ConnectionStateEvent, HealthMonitor, spawn_health_checker};
+pub use results::ResultCache;
+pub use scheduler::spawn_scheduler;
+pub use store::AppStore;
+pub use vault::{Vault, VaultStatus};
 pub use types::*;
 
 pub fn get_connection_pool() -> ConnectionPool {
-    Mutex::new(std::collections::HashMap::new())
+    ConnectionPool::default()
 }
 
 // ── SQL Validation ──────────────────────────────────────────────────────────
 // Prevents dangerous SQL operations from the frontend TablePlus UI.
 
-fn validate_query_sql(sql: &str) -> Result<(), String> {
+fn validate_query_sql(sql: &str) -> Result<(), DbError> {
     let trimmed = sql.trim();
     if trimmed.is_empty() {
-        return Err("Empty SQL statement".into());
+        return Err(DbError::invalid_input("Empty SQL statement"));
     }
 
-    let upper = trimmed.to_uppercase();
-
     // Query must be read-only
-    if !upper.starts_with("SELECT")
-        && !upper.starts_with("SHOW")
-        && !upper.starts_with("DESCRIBE")
-        && !upper.starts_with("EXPLAIN")
-        && !upper.starts_with("WITH")
-    {
-        return Err("db_query only allows SELECT/SHOW/DESCRIBE/EXPLAIN/WITH statements".into());
+    if !is_read_statement(&trimmed.to_uppercase()) {
+        return Err(DbError::invalid_input("db_query only allows SELECT/SHOW/DESCRIBE/EXPLAIN/WITH statements"));
     }
 
     validate_common(sql)
 }
 
-fn validate_execute_sql(sql: &str) -> Result<(), String> {
+fn is_read_statement(upper: &str) -> bool {
+    upper.starts_with("SELECT")
+        || upper.starts_with("SHOW")
+        || upper.starts_with("DESCRIBE")
+        || upper.starts_with("EXPLAIN")
+        || upper.starts_with("WITH")
+}
+
+/// `db_query_multi` takes several statements, each read-only or a procedure
+/// `CALL`. `statements` is the batch as split by `dialect::split_statements`.
+fn validate_query_multi_sql(statements: &[&str]) -> Result<(), DbError> {
+    if statements.is_empty() {
+        return Err(DbError::invalid_input("Empty SQL statement"));
+    }
+    for statement in statements {
+        let upper = statement.to_uppercase();
+        if !is_read_statement(&upper) && !upper.starts_with("CALL") {
+            return Err(DbError::invalid_input("db_query_multi only allows SELECT/SHOW/DESCRIBE/EXPLAIN/WITH/CALL statements"));
+        }
+        validate_keywords(&upper)?;
+    }
+    Ok(())
+}
+
+fn validate_execute_sql(sql: &str) -> Result<(), DbError> {
     let trimmed = sql.trim();
     if trimmed.is_empty() {
-        return Err("Empty SQL statement".into());
+        return Err(DbError::invalid_input("Empty SQL statement"));
     }
 
     validate_common(sql)
 }
 
-fn validate_common(sql: &str) -> Result<(), String> {
-    let upper = sql.to_uppercase();
+fn validate_common(sql: &str) -> Result<(), DbError> {
+    validate_keywords(&sql.to_uppercase())?;
+
+    // Block multi-statement execution (simple heuristic: >1 semicolon in non-trailing position)
+    let without_trailing = sql.trim().trim_end_matches(';');
+    if without_trailing.contains(';') {
+        return Err(DbError::invalid_input("Multiple SQL statements in a single call are not allowed"));
+    }
+
+    Ok(())
+}
 
+fn validate_keywords(upper: &str) -> Result<(), DbError> {
     // Block dangerous keywords
     let blocked = ["ATTACH", "DETACH", "LOAD_EXTENSION"];
     for keyword in blocked {
         // Check for keyword as a standalone word (surrounded by whitespace or start/end)
         if upper.split_whitespace().any(|w| w == keyword) {
-            return Err(format!("SQL keyword '{}' is not allowed", keyword));
+            return Err(DbError::new(DbErrorKind::Permission, format!("SQL keyword '{}' is not allowed", keyword)));
         }
     }
+    Ok(())
+}
 
-    // Block multi-statement execution (simple heuristic: >1 semicolon in non-trailing position)
-    let without_trailing = sql.trim().trim_end_matches(';');
-    if without_trailing.contains(';') {
-        return Err("Multiple SQL statements in a single call are not allowed".into());
+/// Write paths refuse read-only connections before reaching the driver, which
+/// would reject the statement anyway.
+fn ensure_writable(conn: &dyn connection::DatabaseConnection) -> Result<(), DbError> {
+    if conn.info().read_only {
+        return Err(DbError::new(DbErrorKind::Permission, "Connection is read-only"));
     }
-
     Ok(())
 }
 
-#[tauri::command]
-pub fn db_connect(
+async fn connect(
     config: ConnectionConfig,
-    pool: State<ConnectionPool>,
-) -> Result<ConnectionInfo, String> {
-    let conn = create_connection(&config)?;
+    pool: &ConnectionPool,
+    health: &HealthMonitor,
+) -> Result<ConnectionInfo, DbError> {
+    let conn_config = config.clone();
+    let conn = run_blocking(move || create_connection(&conn_config)).await?;
     let mut info = conn.info();
     info.id = config.id.clone();
 
-    let mut pool = pool.lock().map_err(|_| "Lock error")?;
     pool.insert(info.id.clone(), conn);
+    health.track(config);
 
     Ok(info)
 }
 
 #[tauri::command]
-pub fn db_disconnect(
+pub async fn db_connect(
+    config: ConnectionConfig,
+    pool: State<'_, ConnectionPool>,
+    health: State<'_, HealthMonitor>,
+) -> Result<ConnectionInfo, DbError> {
+    connect(config, &pool, &health).await
+}
+
+#[tauri::command]
+pub async fn db_disconnect(
     conn_id: String,
-    pool: State<ConnectionPool>,
-) -> Result<(), String> {
-    let mut pool = pool.lock().map_err(|_| "Lock error")?;
+    pool: State<'_, ConnectionPool>,
+    catalogs: State<'_, CatalogCache>,
+    health: State<'_, HealthMonitor>,
+    listens: State<'_, ListenRegistry>,
+) -> Result<(), DbError> {
+    health.untrack(&conn_id);
+    listens.remove(&conn_id, None);
     if let Some(conn) = pool.remove(&conn_id) {
-        conn.close();
+        // Waits for any query still running on this connection.
+        run_blocking(move || {
+            conn.lock().map_err(|_| "Lock error")?.close();
+            Ok(())
+        }).await?;
     }
+    catalogs.invalidate(&conn_id);
     Ok(())
 }
 
+/// Last known health of every open connection; changes are pushed as
+/// `db:connection-state` events.
 #[tauri::command]
-pub fn db_query(
+pub fn db_get_connection_states(
+    health: State<HealthMonitor>,
+) -> Result<Vec<ConnectionStateEvent>, DbError> {
+    Ok(health.states())
+}
+
+/// Runs a read-only query under row and cell size limits (defaults when
+/// `limits` is omitted). Over-limit results come back flagged as truncated.
+#[tauri::command]
+pub async fn db_query(
     conn_id: String,
     sql: String,
-    pool: State<ConnectionPool>,
-) -> Result<QueryResult, String> {
+    limits: Option<QueryLimits>,
+    pool: State<'_, ConnectionPool>,
+    results: State<'_, ResultCache>,
+    store: State<'_, AppStore>,
+) -> Result<QueryResult, DbError> {
     validate_query_sql(&sql)?;
-    let pool = pool.lock().map_err(|_| "Lock error")?;
-    let conn = pool.get(&conn_id)
-        .ok_or("Connection not found")?;
-    conn.query(&sql)
+    let limits = limits.unwrap_or_default();
+    let rules = store.with_conn(|c| masking::list(c, &conn_id))?;
+    let mut result = with_connection(&pool, &conn_id, move |conn| {
+        let mut result = conn.query_limited(&sql, &[], &limits)?;
+        if !rules.is_empty() {
+            let lineage = masking::Lineage::of_sql(&sql, conn.info().db_type);
+            masking::apply(&rules, &mut result, &lineage);
+        }
+        Ok(result)
+    }).await?;
+    results.apply_cell_limit(&mut result, limits.max_cell_bytes);
+    Ok(result)
 }
 
+/// Runs a batch of read-only statements and stored procedure calls and returns
+/// every result set they produce, each under the row and cell limits.
 #[tauri::command]
-pub fn db_execute(
+pub async fn db_query_multi(
     conn_id: String,
     sql: String,
-    pool: State<ConnectionPool>,
-) -> Result<ExecuteResult, String> {
-    validate_execute_sql(&sql)?;
-    let pool = pool.lock().map_err(|_| "Lock error")?;
-    let conn = pool.get(&conn_id)
-        .ok_or("Connection not found")?;
-    conn.execute(&sql)
+    limits: Option<QueryLimits>,
+    pool: State<'_, ConnectionPool>,
+    results: State<'_, ResultCache>,
+    store: State<'_, AppStore>,
+) -> Result<Vec<ResultSet>, DbError> {
+    let limits = limits.unwrap_or_default();
+    let rules = store.with_conn(|c| masking::list(c, &conn_id))?;
+    let mut sets = with_connection(&pool, &conn_id, move |conn| {
+        let db_type = conn.info().db_type;
+        let statements = dialect::split_statements(&sql, db_type);
+        validate_query_multi_sql(&statements)?;
+        let mut sets = conn.query_multi(&statements, &limits)?;
+        if !rules.is_empty() {
+            for set in &mut sets {
+                let lineage = masking::Lineage::of_sql(statements[set.statement], db_type);
+                masking::apply(&rules, &mut set.result, &lineage);
+            }
+        }
+        Ok(sets)
+    }).await?;
+    for set in &mut sets {
+        results.apply_cell_limit(&mut set.result, limits.max_cell_bytes);
+    }
+    Ok(sets)
+}
+
+/// Full value of a cell that `db_query` truncated.
+#[tauri::command]
+pub fn db_get_cell(
+    result_id: String,
+    row: usize,
+    column: usize,
+    results: State<ResultCache>,
+) -> Result<String, DbError> {
+    results.get_cell(&result_id, row, column)
+        .ok_or_else(|| "Cell value is no longer available; re-run the query".into())
+}
+
+/// Records a write in the audit log. A failed audit write is logged rather
+/// than returned, since the statement itself has already run.
+fn audit_write(
+    store: &AppStore,
+    health: &HealthMonitor,
+    conn_id: &str,
+    operation: &str,
+    sql: &str,
+    result: &Result<ExecuteResult, DbError>,
+) {
+    let config = health.config(conn_id);
+    let record = audit::AuditRecord {
+        conn_id,
+        connection_name: config.as_ref().map_or(conn_id, |c| c.name.as_str()),
+        host: config.as_ref().and_then(|c| c.host.as_deref().or(c.file_path.as_deref())),
+        operation,
+        sql,
+        result: result.as_ref().map(|r| r.rows_affected).map_err(|e| e.message.as_str()),
+    };
+    if let Err(e) = store.with_conn(|c| audit::append(c, &record)) {
+        log::error!("Audit log write failed for {}: {}", conn_id, e);
+    }
 }
 
+/// Outcome of the connection-side part of `db_execute`.
+enum ExecuteRun {
+    Ran(Result<ExecuteResult, DbError>),
+    NeedsConfirmation(guard::DestructiveOp, Option<u64>),
+}
+
+/// Runs a write statement. Destructive statements (DROP, TRUNCATE, DROP COLUMN,
+/// UPDATE/DELETE without WHERE) come back as `confirmation_required` with an
+/// impact estimate and a one-time token, and only run when that token is sent
+/// back as `confirmation_token` with the same SQL.
 #[tauri::command]
-pub fn db_get_tables(
+#[allow(clippy::too_many_arguments)]
+pub async fn db_execute(
     conn_id: String,
-    pool: State<ConnectionPool>,
-) -> Result<Vec<TableInfo>, String> {
-    let pool = pool.lock().map_err(|_| "Lock error")?;
-    let conn = pool.get(&conn_id)
-        .ok_or("Connection not found")?;
-    conn.get_tables()
+    sql: String,
+    confirmation_token: Option<String>,
+    pool: State<'_, ConnectionPool>,
+    catalogs: State<'_, CatalogCache>,
+    store: State<'_, AppStore>,
+    health: State<'_, HealthMonitor>,
+    confirmations: State<'_, ConfirmationRegistry>,
+) -> Result<ExecuteResult, DbError> {
+    if let Err(e) = validate_execute_sql(&sql) {
+        audit_write(&store, &health, &conn_id, "execute", &sql, &Err(e.clone()));
+        return Err(e);
+    }
+    let confirmed = confirmation_token
+        .is_some_and(|token| confirmations.redeem(&token, &conn_id, &sql));
+
+    let exec_sql = sql.clone();
+    let run = with_connection(&pool, &conn_id, move |conn| {
+        if let Err(e) = ensure_writable(conn) {
+            return Ok(ExecuteRun::Ran(Err(e)));
+        }
+        if !confirmed {
+            if let Some(op) = guard::analyze(&exec_sql, conn.info().db_type) {
+                let estimate = guard::estimate_rows(conn, &op);
+                return Ok(ExecuteRun::NeedsConfirmation(op, estimate));
+            }
+        }
+        Ok(ExecuteRun::Ran(conn.execute(&exec_sql)))
+    }).await;
+
+    let result = match run {
+        Ok(ExecuteRun::NeedsConfirmation(op, estimated_rows)) => {
+            return Err(DbError::confirmation_required(ConfirmationRequest {
+                operation: op.operation,
+                tables: op.tables,
+                estimated_rows,
+                token: confirmations.issue(&conn_id, &sql),
+            }));
+        }
+        Ok(ExecuteRun::Ran(result)) => result,
+        Err(e) => Err(e),
+    };
+    audit_write(&store, &health, &conn_id, "execute", &sql, &result);
+    if catalog::is_ddl(&sql) {
+        catalogs.invalidate(&conn_id);
+    }
+    result
+}
+
+/// Reads a page of a table from a structured spec instead of raw SQL.
+#[tauri::command]
+pub async fn db_browse_table(
+    conn_id: String,
+    spec: BrowseSpec,
+    pool: State<'_, ConnectionPool>,
+    results: State<'_, ResultCache>,
+    store: State<'_, AppStore>,
+) -> Result<BrowseResult, DbError> {
+    let rules = store.with_conn(|c| masking::list(c, &conn_id))?;
+    // The cursor carries the last row's sort values, which masking never sees.
+    if matches!(spec.page, BrowsePage::Keyset { .. }) {
+        if let Some(key) = spec.sort.iter().find(|k| masking::is_masked(&rules, &spec.table, &k.column)) {
+            return Err(DbError::invalid_input(format!("Keyset paging cannot sort by masked column {}", key.column)));
+        }
+    }
+    let table = spec.table.clone();
+    let mut browsed = with_connection(&pool, &conn_id, move |conn| browse::browse(conn, &spec)).await?;
+    masking::apply(&rules, &mut browsed.result, &masking::Lineage::of_table(&table));
+    results.apply_cell_limit(&mut browsed.result, QueryLimits::default().max_cell_bytes);
+    Ok(browsed)
 }
 
 #[tauri::command]
-pub fn db_get_columns(
+pub async fn db_get_tables(
+    conn_id: String,
+    pool: State<'_, ConnectionPool>,
+) -> Result<Vec<TableInfo>, DbError> {
+    with_connection(&pool, &conn_id, |conn| conn.get_tables()).await
+}
+
+#[tauri::command]
+pub async fn db_get_columns(
     conn_id: String,
     table: String,
-    pool: State<ConnectionPool>,
-) -> Result<Vec<ColumnInfo>, String> {
+    pool: State<'_, ConnectionPool>,
+) -> Result<Vec<ColumnInfo>, DbError> {
     // Validate table name: only allow alphanumeric, underscore, dot (for schema.table)
     if !table.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.') {
-        return Err("Invalid table name: only alphanumeric, underscore, and dot allowed".into());
+        return Err(DbError::invalid_input("Invalid table name: only alphanumeric, underscore, and dot allowed"));
+    }
+    with_connection(&pool, &conn_id, move |conn| conn.get_columns(&table)).await
+}
+
+/// Bulk schema catalog for autocompletion, cached per connection until DDL runs
+/// through `db_execute` or the caller asks for a refresh.
+#[tauri::command]
+pub async fn db_get_catalog(
+    conn_id: String,
+    refresh: Option<bool>,
+    pool: State<'_, ConnectionPool>,
+    catalogs: State<'_, CatalogCache>,
+) -> Result<Catalog, DbError> {
+    if refresh.unwrap_or(false) {
+        catalogs.invalidate(&conn_id);
+    }
+    Ok((*cached_catalog(&pool, &catalogs, &conn_id).await?).clone())
+}
+
+async fn cached_catalog(
+    pool: &ConnectionPool,
+    catalogs: &CatalogCache,
+    conn_id: &str,
+) -> Result<Arc<Catalog>, DbError> {
+    if let Some(catalog) = catalogs.get(conn_id) {
+        return Ok(catalog);
+    }
+    let catalog = with_connection(pool, conn_id, |conn| conn.get_catalog()).await?;
+    Ok(catalogs.put(conn_id, catalog))
+}
+
+/// Databases and schemas the connection can switch to with `db_use_database`.
+#[tauri::command]
+pub async fn db_list_databases(
+    conn_id: String,
+    pool: State<'_, ConnectionPool>,
+) -> Result<DatabaseList, DbError> {
+    with_connection(&pool, &conn_id, |conn| conn.list_databases()).await
+}
+
+/// Switches the database and/or schema that `db_get_tables` and unqualified
+/// names refer to. PostgreSQL reconnects to change database and sets the
+/// `search_path` for a schema; MySQL runs `USE`. The choice is kept for
+/// reconnects by the health checker.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn db_use_database(
+    conn_id: String,
+    database: Option<String>,
+    schema: Option<String>,
+    app: AppHandle,
+    pool: State<'_, ConnectionPool>,
+    catalogs: State<'_, CatalogCache>,
+    health: State<'_, HealthMonitor>,
+) -> Result<ConnectionInfo, DbError> {
+    let mut config = health.config(&conn_id)
+        .ok_or_else(|| DbError::new(DbErrorKind::Connection, "Connection not found"))?;
+    let reconnect = config.db_type == DatabaseType::Postgres
+        && database.as_ref().is_some_and(|db| config.database.as_ref() != Some(db));
+    match config.db_type {
+        DatabaseType::Mysql => {
+            if let Some(db) = database.as_ref().or(schema.as_ref()) {
+                config.database = Some(db.clone());
+            }
+        }
+        _ => {
+            if let Some(db) = &database {
+                config.database = Some(db.clone());
+            }
+            if schema.is_some() || reconnect {
+                config.schema = schema.clone();
+            }
+        }
+    }
+
+    let mut info = if reconnect {
+        let conn_config = config.clone();
+        let conn = run_blocking(move || create_connection(&conn_config)).await?;
+        let info = conn.info();
+        health::replace_connection(&app, &conn_id, conn);
+        info
+    } else {
+        // PostgreSQL is already on `database` here.
+        let database = database.filter(|_| config.db_type != DatabaseType::Postgres);
+        with_connection(&pool, &conn_id, move |conn| {
+            conn.use_database(database.as_deref(), schema.as_deref())?;
+            Ok(conn.info())
+        }).await?
+    };
+    info.id = conn_id.clone();
+    health.track(config);
+    catalogs.invalidate(&conn_id);
+    Ok(info)
+}
+
+/// Searches text-like (and, for numeric terms, integer) columns of every table
+/// for `term`. With `options.job_id` set, progress is emitted as
+/// `db:search-progress` and the search can be stopped with `db_cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn db_search_all(
+    conn_id: String,
+    term: String,
+    options: Option<SearchOptions>,
+    app: AppHandle,
+    pool: State<'_, ConnectionPool>,
+    catalogs: State<'_, CatalogCache>,
+    jobs: State<'_, CancelRegistry>,
+    store: State<'_, AppStore>,
+) -> Result<SearchResult, DbError> {
+    if term.trim().is_empty() {
+        return Err(DbError::invalid_input("Search term must not be empty"));
+    }
+    let options = options.unwrap_or_default();
+    let job = options.job_id.as_deref().map(|id| jobs.register(id)).transpose()?;
+    let cancel = job.as_ref().map(|j| j.token());
+
+    let rules = store.with_conn(|c| masking::list(c, &conn_id))?;
+    let catalog = cached_catalog(&pool, &catalogs, &conn_id).await?;
+    let search_rules = rules.clone();
+    let mut found = with_connection(&pool, &conn_id, move |conn| {
+        Ok(search::search_all(conn, &catalog, &term, &options, &search_rules, cancel.as_ref(), &app))
+    }).await?;
+    for hit in &mut found.hits {
+        masking::apply(&rules, &mut hit.result, &masking::Lineage::of_table(&hit.table));
+    }
+    Ok(found)
+}
+
+/// Asks a running job (search, transfer, ...) to stop; returns whether it was found.
+#[tauri::command]
+pub fn db_cancel_job(job_id: String, jobs: State<CancelRegistry>) -> Result<bool, DbError> {
+    Ok(jobs.cancel(&job_id))
+}
+
+// ── Notifications ───────────────────────────────────────────────────────────
+// PostgreSQL LISTEN/NOTIFY; payloads arrive as `db:notification` events and
+// subscriptions survive reconnects by the health checker.
+
+#[tauri::command]
+pub async fn db_listen(
+    conn_id: String,
+    channel: String,
+    app: AppHandle,
+    pool: State<'_, ConnectionPool>,
+    listens: State<'_, ListenRegistry>,
+) -> Result<(), DbError> {
+    if channel.trim().is_empty() {
+        return Err(DbError::invalid_input("Channel name must not be empty"));
+    }
+    let id = conn_id.clone();
+    let name = channel.clone();
+    with_connection(&pool, &conn_id, move |conn| listen::listen(&app, &id, conn, &name)).await?;
+    listens.add(&conn_id, &channel);
+    Ok(())
+}
+
+/// Stops listening on `channel`, or on every channel when it is omitted.
+#[tauri::command]
+pub async fn db_unlisten(
+    conn_id: String,
+    channel: Option<String>,
+    pool: State<'_, ConnectionPool>,
+    listens: State<'_, ListenRegistry>,
+) -> Result<(), DbError> {
+    let name = channel.clone();
+    with_connection(&pool, &conn_id, move |conn| listen::unlisten(conn, name.as_deref())).await?;
+    listens.remove(&conn_id, channel.as_deref());
+    Ok(())
+}
+
+// ── Schema Changes ──────────────────────────────────────────────────────────
+// Structured table alterations turned into the connection's DDL dialect.
+
+/// The statements `db_alter_table` would run, without running them.
+#[tauri::command]
+pub async fn db_plan_alter_table(
+    conn_id: String,
+    alteration: TableAlteration,
+    pool: State<'_, ConnectionPool>,
+) -> Result<DdlPlan, DbError> {
+    with_connection(&pool, &conn_id, move |conn| ddl::plan(conn, &alteration)).await
+}
+
+/// Applies an alteration, in one transaction where the dialect allows, and
+/// returns the plan that ran. Dropping columns needs a confirmation token as
+/// in `db_execute`; the token is bound to the generated script.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn db_alter_table(
+    conn_id: String,
+    alteration: TableAlteration,
+    confirmation_token: Option<String>,
+    pool: State<'_, ConnectionPool>,
+    catalogs: State<'_, CatalogCache>,
+    store: State<'_, AppStore>,
+    health: State<'_, HealthMonitor>,
+    confirmations: State<'_, ConfirmationRegistry>,
+) -> Result<DdlPlan, DbError> {
+    let destructive = ddl::destructive(&alteration);
+    let plan = with_connection(&pool, &conn_id, move |conn| ddl::plan(conn, &alteration)).await?;
+    let script = plan.statements.join(";\n");
+
+    let confirmed = confirmation_token
+        .is_some_and(|token| confirmations.redeem(&token, &conn_id, &script));
+    if let (Some(op), false) = (destructive, confirmed) {
+        let count_op = op.clone();
+        let estimated_rows = with_connection(&pool, &conn_id, move |conn| {
+            Ok(guard::estimate_rows(conn, &count_op))
+        }).await?;
+        return Err(DbError::confirmation_required(ConfirmationRequest {
+            operation: op.operation,
+            tables: op.tables,
+            estimated_rows,
+            token: confirmations.issue(&conn_id, &script),
+        }));
+    }
+
+    let (plan, result) = with_connection(&pool, &conn_id, move |conn| {
+        let mut plan = plan;
+        let result = ensure_writable(conn).and_then(|_| ddl::apply(conn, &mut plan));
+        Ok((plan, result))
+    }).await?;
+    audit_write(&store, &health, &conn_id, "alter_table", &script, &result);
+    catalogs.invalidate(&conn_id);
+    result?;
+    Ok(plan)
+}
+
+// ── Test Data ───────────────────────────────────────────────────────────────
+
+/// Inserts `count` rows of fake data, guessed per column from its name and type
+/// unless `rules` says otherwise. Foreign keys reference sampled parent rows.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn db_generate_rows(
+    conn_id: String,
+    table: String,
+    count: u64,
+    rules: Option<HashMap<String, GeneratorRule>>,
+    pool: State<'_, ConnectionPool>,
+    catalogs: State<'_, CatalogCache>,
+    store: State<'_, AppStore>,
+    health: State<'_, HealthMonitor>,
+) -> Result<GenerateResult, DbError> {
+    let description = format!("INSERT INTO {} <{} generated rows>", table, count);
+    let catalog = cached_catalog(&pool, &catalogs, &conn_id).await?;
+    let result = with_connection(&pool, &conn_id, move |conn| {
+        ensure_writable(conn)?;
+        generate::generate(conn, &catalog, &table, count, &rules.unwrap_or_default())
+    }).await;
+    let audited = result.as_ref()
+        .map(|r| ExecuteResult { rows_affected: r.inserted, last_insert_id: None, execution_time_ms: r.execution_time_ms })
+        .map_err(DbError::clone);
+    audit_write(&store, &health, &conn_id, "generate_rows", &description, &audited);
+    result
+}
+
+// ── Transfer ────────────────────────────────────────────────────────────────
+
+/// Copies tables from one connection to another, creating missing target
+/// tables with column types mapped to the target's dialect. Rows move in
+/// batches; with `options.job_id` set, progress is emitted as
+/// `db:transfer-progress` and the copy can be stopped with `db_cancel_job`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn db_transfer(
+    source_conn_id: String,
+    target_conn_id: String,
+    options: TransferOptions,
+    app: AppHandle,
+    pool: State<'_, ConnectionPool>,
+    catalogs: State<'_, CatalogCache>,
+    jobs: State<'_, CancelRegistry>,
+    store: State<'_, AppStore>,
+    health: State<'_, HealthMonitor>,
+) -> Result<TransferResult, DbError> {
+    if options.tables.is_empty() {
+        return Err(DbError::invalid_input("No tables to transfer"));
     }
-    let pool = pool.lock().map_err(|_| "Lock error")?;
-    let conn = pool.get(&conn_id)
-        .ok_or("Connection not found")?;
-    conn.get_columns(&table)
+    with_connection(&pool, &target_conn_id, |conn| ensure_writable(conn)).await?;
+    let sources: Vec<&str> = options.tables.iter().map(|t| t.source.as_str()).collect();
+    let description = format!("INSERT <transfer of {} from connection {}>", sources.join(", "), source_conn_id);
+    let job = options.job_id.as_deref().map(|id| jobs.register(id)).transpose()?;
+    let cancel = job.as_ref().map(|j| j.token());
+
+    let rules = match options.mask {
+        true => store.with_conn(|c| masking::list(c, &source_conn_id))?,
+        false => Vec::new(),
+    };
+    let source = cached_catalog(&pool, &catalogs, &source_conn_id).await?;
+    let target = cached_catalog(&pool, &catalogs, &target_conn_id).await?;
+    let result = transfer::Transfer {
+        pool: &pool,
+        source_id: &source_conn_id,
+        target_id: &target_conn_id,
+        rules: &rules,
+        cancel: cancel.as_ref(),
+        app: &app,
+    }.run(&source, &target, &options).await;
+    catalogs.invalidate(&target_conn_id);
+
+    let audited = result.as_ref()
+        .map(|r| ExecuteResult {
+            rows_affected: r.tables.iter().map(|t| t.rows).sum(),
+            last_insert_id: None,
+            execution_time_ms: r.execution_time_ms,
+        })
+        .map_err(DbError::clone);
+    audit_write(&store, &health, &target_conn_id, "transfer", &description, &audited);
+    result
+}
+
+// ── Data Comparison ─────────────────────────────────────────────────────────
+
+/// Compares the rows of two tables or read-only queries, possibly on different
+/// connections, by `key_columns`. Returns counts and samples of rows found on
+/// one side only or differing, and optionally the statements that would make
+/// the right table match the left. Nothing is executed.
+#[tauri::command]
+pub async fn db_compare_data(
+    left: CompareSource,
+    right: CompareSource,
+    key_columns: Vec<String>,
+    options: Option<CompareDataOptions>,
+    pool: State<'_, ConnectionPool>,
+    store: State<'_, AppStore>,
+) -> Result<DataComparison, DbError> {
+    for sql in [&left.sql, &right.sql].into_iter().flatten() {
+        validate_query_sql(sql)?;
+    }
+    let left_rules = store.with_conn(|c| masking::list(c, &left.conn_id))?;
+    let right_rules = store.with_conn(|c| masking::list(c, &right.conn_id))?;
+    compare::compare(&pool, &left, left_rules, &right, right_rules, &key_columns, &options.unwrap_or_default()).await
+}
+
+// ── Sessions ────────────────────────────────────────────────────────────────
+// Server-side session and lock monitor for PostgreSQL and MySQL.
+
+/// Sessions on the server with their running statement, wait state and the
+/// sessions blocking them.
+#[tauri::command]
+pub async fn db_get_sessions(
+    conn_id: String,
+    pool: State<'_, ConnectionPool>,
+) -> Result<Vec<SessionInfo>, DbError> {
+    with_connection(&pool, &conn_id, |conn| sessions::list(conn)).await
+}
+
+/// Cancels a session's statement or terminates the session (the default).
+/// Recorded in the audit log like any other write.
+#[tauri::command]
+pub async fn db_kill_session(
+    conn_id: String,
+    session_id: String,
+    mode: Option<KillMode>,
+    pool: State<'_, ConnectionPool>,
+    store: State<'_, AppStore>,
+    health: State<'_, HealthMonitor>,
+) -> Result<ExecuteResult, DbError> {
+    let kill = with_connection(&pool, &conn_id, move |conn| {
+        ensure_writable(conn)?;
+        sessions::kill(conn, &session_id, mode.unwrap_or_default())
+    }).await?;
+    audit_write(&store, &health, &conn_id, "kill_session", &kill.sql, &kill.result);
+    kill.result
+}
+
+// ── Privileges ──────────────────────────────────────────────────────────────
+
+/// Roles/accounts, their memberships and their grants down to column level,
+/// as one flat list the explorer pivots into an access matrix.
+#[tauri::command]
+pub async fn db_get_privileges(
+    conn_id: String,
+    pool: State<'_, ConnectionPool>,
+) -> Result<PrivilegeReport, DbError> {
+    with_connection(&pool, &conn_id, |conn| privileges::report(conn)).await
+}
+
+// ── Binary Data ─────────────────────────────────────────────────────────────
+// Binary cells come back from queries as a tagged preview; these move the full
+// value between a cell and a file, addressed by the row's key columns.
+
+#[tauri::command]
+pub async fn db_export_blob(
+    conn_id: String,
+    table: String,
+    column: String,
+    key: Vec<KeyValue>,
+    path: String,
+    pool: State<'_, ConnectionPool>,
+    store: State<'_, AppStore>,
+) -> Result<usize, DbError> {
+    let rules = store.with_conn(|c| masking::list(c, &conn_id))?;
+    if masking::is_masked(&rules, &table, &column) {
+        return Err(DbError::new(DbErrorKind::Permission, format!("Column '{}' is masked and cannot be exported", column)));
+    }
+    with_connection(&pool, &conn_id, move |conn| {
+        blobs::export(conn, &table, &column, &key, &path)
+    }).await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn db_import_blob(
+    conn_id: String,
+    table: String,
+    column: String,
+    key: Vec<KeyValue>,
+    path: String,
+    pool: State<'_, ConnectionPool>,
+    store: State<'_, AppStore>,
+    health: State<'_, HealthMonitor>,
+) -> Result<ExecuteResult, DbError> {
+    let description = format!("UPDATE {} SET {} = <contents of {}>", table, column, path);
+    let result = with_connection(&pool, &conn_id, move |conn| {
+        ensure_writable(conn)?;
+        blobs::import(conn, &table, &column, &key, &path)
+    }).await;
+    audit_write(&store, &health, &conn_id, "blob_import", &description, &result);
+    result
+}
+
+// ── Connection Vault ────────────────────────────────────────────────────────
+// Saved connections live encrypted in the app data dir. Passwords go in through
+// `db_save_connection` and only come out inside the backend, to connect.
+
+#[tauri::command]
+pub fn db_vault_status(vault: State<Vault>) -> Result<VaultStatus, DbError> {
+    Ok(vault.status())
+}
+
+/// Unlocks (or creates) the vault and moves any connections still stored in
+/// plain text by older versions into it.
+#[tauri::command]
+pub fn db_vault_unlock(
+    passphrase: String,
+    vault: State<Vault>,
+    store: State<AppStore>,
+) -> Result<VaultStatus, DbError> {
+    vault.unlock(&passphrase)?;
+    let legacy = store.with_conn(vault::legacy_connections)?;
+    if !legacy.is_empty() {
+        vault.import(legacy)?;
+        store.with_conn(vault::clear_legacy_connections)?;
+    }
+    Ok(vault.status())
+}
+
+#[tauri::command]
+pub fn db_vault_lock(vault: State<Vault>) -> Result<VaultStatus, DbError> {
+    vault.lock();
+    Ok(vault.status())
+}
+
+#[tauri::command]
+pub fn db_list_connections(vault: State<Vault>) -> Result<Vec<SavedConnection>, DbError> {
+    Ok(vault.list()?)
+}
+
+#[tauri::command]
+pub fn db_save_connection(
+    config: ConnectionConfig,
+    vault: State<Vault>,
+) -> Result<SavedConnection, DbError> {
+    if config.id.is_empty() || config.name.trim().is_empty() {
+        return Err(DbError::invalid_input("Connection id and name are required"));
+    }
+    Ok(vault.save(config)?)
+}
+
+#[tauri::command]
+pub fn db_delete_connection(
+    id: String,
+    vault: State<Vault>,
+) -> Result<(), DbError> {
+    Ok(vault.delete(&id)?)
+}
+
+#[tauri::command]
+pub async fn db_connect_saved(
+    id: String,
+    vault: State<'_, Vault>,
+    pool: State<'_, ConnectionPool>,
+    health: State<'_, HealthMonitor>,
+) -> Result<ConnectionInfo, DbError> {
+    let config = vault.get(&id)?;
+    connect(config, &pool, &health).await
+}
+
+// ── Saved Queries ───────────────────────────────────────────────────────────
+// Named SQL snippets with `{{variable}}` placeholders, stored in nexus.db.
+
+#[tauri::command]
+pub fn db_list_saved_queries(
+    connection_id: Option<String>,
+    store: State<AppStore>,
+) -> Result<Vec<SavedQuery>, DbError> {
+    Ok(store.with_conn(|c| saved_queries::list(c, connection_id.as_deref()))?)
+}
+
+#[tauri::command]
+pub fn db_save_query(
+    mut query: SavedQuery,
+    store: State<AppStore>,
+) -> Result<SavedQuery, DbError> {
+    saved_queries::validate(&query)?;
+
+    let now = store::now_millis();
+    if query.id.is_empty() {
+        query.id = uuid::Uuid::new_v4().to_string();
+        query.created_at = now;
+    }
+    query.updated_at = now;
+
+    store.with_conn(|c| saved_queries::save(c, &query))?;
+    Ok(query)
+}
+
+#[tauri::command]
+pub fn db_delete_saved_query(
+    id: String,
+    store: State<AppStore>,
+) -> Result<(), DbError> {
+    Ok(store.with_conn(|c| saved_queries::delete(c, &id))?)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn db_run_saved_query(
+    conn_id: String,
+    query_id: String,
+    values: HashMap<String, serde_json::Value>,
+    pool: State<'_, ConnectionPool>,
+    store: State<'_, AppStore>,
+    catalogs: State<'_, CatalogCache>,
+    results: State<'_, ResultCache>,
+    health: State<'_, HealthMonitor>,
+) -> Result<SavedQueryOutput, DbError> {
+    let query = store.with_conn(|c| saved_queries::get(c, &query_id))?
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, "Saved query not found"))?;
+    if query.connection_id.as_ref().is_some_and(|id| *id != conn_id) {
+        return Err(DbError::invalid_input("Saved query belongs to a different connection"));
+    }
+
+    let rules = store.with_conn(|c| masking::list(c, &conn_id))?;
+
+    let run = with_connection(&pool, &conn_id, move |conn| {
+        let resolved = saved_queries::resolve(&query, &values, conn.info().db_type)
+            .map_err(DbError::invalid_input)?;
+        if validate_query_sql(&resolved.sql).is_ok() {
+            let mut result = conn.query_limited(&resolved.sql, &resolved.params, &QueryLimits::default())?;
+            let lineage = masking::Lineage::of_sql(&resolved.sql, conn.info().db_type);
+            masking::apply(&rules, &mut result, &lineage);
+            Ok(SavedQueryRun::Read(result))
+        } else {
+            validate_execute_sql(&resolved.sql)?;
+            if let Some(op) = guard::analyze(&resolved.sql, conn.info().db_type) {
+                return Err(DbError::invalid_input(format!("{} needs confirmation; run it through the query editor", op.operation)));
+            }
+            let result = ensure_writable(conn)
+                .and_then(|_| conn.execute_with_params(&resolved.sql, &resolved.params));
+            Ok(SavedQueryRun::Write { sql: resolved.sql, result })
+        }
+    }).await?;
+
+    match run {
+        SavedQueryRun::Read(mut result) => {
+            results.apply_cell_limit(&mut result, QueryLimits::default().max_cell_bytes);
+            Ok(SavedQueryOutput::Query(result))
+        }
+        SavedQueryRun::Write { sql, result } => {
+            audit_write(&store, &health, &conn_id, "saved_query", &sql, &result);
+            if catalog::is_ddl(&sql) {
+                catalogs.invalidate(&conn_id);
+            }
+            result.map(SavedQueryOutput::Execute)
+        }
+    }
+}
+
+/// A resolved saved query; writes keep their SQL for the audit log.
+enum SavedQueryRun {
+    Read(QueryResult),
+    Write { sql: String, result: Result<ExecuteResult, DbError> },
+}
+
+// ── Scheduled Queries ───────────────────────────────────────────────────────
+// Saved queries run in the background on a cron schedule against saved
+// connections; every run leaves a snapshot in nexus.db and may raise alerts.
+
+#[tauri::command]
+pub fn db_list_schedules(store: State<AppStore>) -> Result<Vec<ScheduledQuery>, DbError> {
+    Ok(store.with_conn(scheduler::list)?)
+}
+
+#[tauri::command]
+pub fn db_save_schedule(
+    mut schedule: ScheduledQuery,
+    store: State<AppStore>,
+) -> Result<ScheduledQuery, DbError> {
+    scheduler::validate(&schedule)?;
+    if store.with_conn(|c| saved_queries::get(c, &schedule.query_id))?.is_none() {
+        return Err(DbError::new(DbErrorKind::NotFound, "Saved query not found"));
+    }
+
+    let now = store::now_millis();
+    if schedule.id.is_empty() {
+        schedule.id = uuid::Uuid::new_v4().to_string();
+        schedule.created_at = now;
+    }
+    schedule.updated_at = now;
+
+    store.with_conn(|c| scheduler::save(c, &schedule))?;
+    Ok(schedule)
+}
+
+/// Deletes the schedule and its snapshots.
+#[tauri::command]
+pub fn db_delete_schedule(
+    id: String,
+    store: State<AppStore>,
+) -> Result<(), DbError> {
+    Ok(store.with_conn(|c| scheduler::delete(c, &id))?)
+}
+
+/// Runs a schedule now, outside its cron schedule; rules and events apply as usual.
+#[tauri::command]
+pub async fn db_run_schedule(
+    id: String,
+    app: AppHandle,
+    store: State<'_, AppStore>,
+) -> Result<QuerySnapshot, DbError> {
+    let schedule = store.with_conn(|c| scheduler::get(c, &id))?
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, "Scheduled query not found"))?;
+    run_blocking(move || Ok(scheduler::run(&app, &schedule)?)).await
+}
+
+/// Snapshots of a schedule, newest first; the last 50 unless `limit` says otherwise.
+#[tauri::command]
+pub fn db_list_snapshots(
+    schedule_id: String,
+    limit: Option<u32>,
+    store: State<AppStore>,
+) -> Result<Vec<QuerySnapshot>, DbError> {
+    Ok(store.with_conn(|c| scheduler::list_snapshots(c, &schedule_id, limit.unwrap_or(50)))?)
+}
+
+// ── Masking Rules ───────────────────────────────────────────────────────────
+// Per-connection rules that mask column values in Rust, before query, browse
+// and search results reach the webview.
+
+#[tauri::command]
+pub fn db_list_masking_rules(
+    connection_id: String,
+    store: State<AppStore>,
+) -> Result<Vec<MaskingRule>, DbError> {
+    Ok(store.with_conn(|c| masking::list(c, &connection_id))?)
+}
+
+#[tauri::command]
+pub fn db_save_masking_rule(
+    mut rule: MaskingRule,
+    store: State<AppStore>,
+) -> Result<MaskingRule, DbError> {
+    masking::validate(&rule)?;
+    if rule.id.is_empty() {
+        rule.id = uuid::Uuid::new_v4().to_string();
+        rule.created_at = store::now_millis();
+    }
+    store.with_conn(|c| masking::save(c, &rule))?;
+    Ok(rule)
+}
+
+#[tauri::command]
+pub fn db_delete_masking_rule(
+    id: String,
+    store: State<AppStore>,
+) -> Result<(), DbError> {
+    Ok(store.with_conn(|c| masking::delete(c, &id))?)
+}
+
+// ── Audit Log ───────────────────────────────────────────────────────────────
+// Hash-chained record of every write statement, kept append-only in nexus.db.
+
+#[tauri::command]
+pub fn db_audit_list(
+    filter: Option<AuditFilter>,
+    store: State<AppStore>,
+) -> Result<Vec<AuditEntry>, DbError> {
+    Ok(store.with_conn(|c| audit::list(c, &filter.unwrap_or_default()))?)
+}
+
+#[tauri::command]
+pub fn db_audit_verify(store: State<AppStore>) -> Result<AuditVerification, DbError> {
+    Ok(store.with_conn(audit::verify)?)
+}
+
+/// Writes matching entries to `path`, oldest first; returns how many.
+#[tauri::command]
+pub fn db_audit_export(
+    path: String,
+    format: AuditExportFormat,
+    filter: Option<AuditFilter>,
+    store: State<AppStore>,
+) -> Result<usize, DbError> {
+    let entries = store.with_conn(|c| audit::list(c, &filter.unwrap_or_default()))?;
+    let rendered = audit::render(&entries, format)?;
+    std::fs::write(&path, rendered).map_err(|e| format!("Export error: {}", e))?;
+    Ok(entries.len())
 }
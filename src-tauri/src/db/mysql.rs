@@ -1,11 +1,22 @@
-use mysql::{Pool, PooledConn, OptsBuilder, SslOpts, Value, prelude::Queryable};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use mysql::{Column, Opts, Params, Pool, PoolConstraints, PoolOpts, PooledConn, OptsBuilder, SslOpts, TxOpts, Value, prelude::{Protocol, Queryable}};
+use mysql::consts::ColumnType;
+use super::catalog::CatalogBuilder;
 use super::connection::DatabaseConnection;
 use super::types::*;
 
 pub struct MysqlConnection {
     id: String,
     name: String,
-    pool: Pool,
+    /// Replaced by `use_database` with a pool opened on the chosen database.
+    pool: Mutex<Pool>,
+    /// Options the first pool was opened with.
+    opts: Opts,
+    read_only: bool,
+    /// Server thread ids of the pool's connections, recorded as they are
+    /// handed out.
+    thread_ids: Mutex<HashSet<u32>>,
 }
 
 impl MysqlConnection {
@@ -47,6 +58,31 @@ impl MysqlConnection {
         }
     }
 
+    /// Binary strings and blobs; the `binary` charset alone also matches numeric
+    /// columns sent over the text protocol.
+    fn is_binary_column(column: &Column) -> bool {
+        const BINARY_CHARSET: u16 = 63;
+        column.character_set() == BINARY_CHARSET && matches!(
+            column.column_type(),
+            ColumnType::MYSQL_TYPE_TINY_BLOB
+                | ColumnType::MYSQL_TYPE_MEDIUM_BLOB
+                | ColumnType::MYSQL_TYPE_LONG_BLOB
+                | ColumnType::MYSQL_TYPE_BLOB
+                | ColumnType::MYSQL_TYPE_VAR_STRING
+                | ColumnType::MYSQL_TYPE_STRING
+                | ColumnType::MYSQL_TYPE_VARCHAR
+                | ColumnType::MYSQL_TYPE_GEOMETRY
+                | ColumnType::MYSQL_TYPE_BIT
+        )
+    }
+
+    fn value_to_cell(value: &Value, column: &Column) -> Option<CellValue> {
+        match value {
+            Value::Bytes(bytes) if Self::is_binary_column(column) => Some(CellValue::binary(bytes)),
+            other => Self::value_to_string(other).map(CellValue::Text),
+        }
+    }
+
     fn build_pool(
         host: &str,
         port: u16,
@@ -54,26 +90,38 @@ impl MysqlConnection {
         username: &str,
         password: &str,
         use_tls: bool,
-    ) -> Result<Pool, String> {
+        read_only: bool,
+    ) -> Result<(Pool, Opts), DbError> {
         let mut opts = OptsBuilder::new()
             .ip_or_hostname(Some(host))
             .tcp_port(port)
             .db_name(Some(database))
             .user(Some(username))
-            .pass(Some(password));
+            .pass(Some(password))
+            // Connections open only on demand, so each one passes through
+            // `get_conn` and has its thread id recorded.
+            .pool_opts(PoolOpts::default().with_constraints(
+                PoolConstraints::new(0, PoolConstraints::DEFAULT.max()).unwrap_or(PoolConstraints::DEFAULT),
+            ));
+
+        if read_only {
+            // Runs on every pooled connection as it is opened.
+            opts = opts.init(vec!["SET SESSION TRANSACTION READ ONLY"]);
+        }
 
         if use_tls {
             opts = opts.ssl_opts(Some(SslOpts::default()));
         }
 
-        let pool = Pool::new(opts)
-            .map_err(|e| format!("MySQL connection error: {}", e))?;
+        let opts = Opts::from(opts);
+        let pool = Pool::new(opts.clone())
+            .map_err(|e| with_context("MySQL connection error", e))?;
 
         // Force a real handshake early so UI fails fast with actionable error.
         pool.get_conn()
-            .map_err(|e| format!("MySQL authentication/handshake failed: {}", e))?;
+            .map_err(|e| with_context("MySQL authentication/handshake failed", e))?;
 
-        Ok(pool)
+        Ok((pool, opts))
     }
 
     pub fn new(
@@ -82,22 +130,24 @@ impl MysqlConnection {
         database: &str,
         username: &str,
         password: &str,
-    ) -> Result<Box<dyn DatabaseConnection + Send>, String> {
-        let pool = match Self::build_pool(host, port, database, username, password, false) {
+        read_only: bool,
+    ) -> Result<Box<dyn DatabaseConnection + Send>, DbError> {
+        let (pool, opts) = match Self::build_pool(host, port, database, username, password, false, read_only) {
             Ok(pool) => pool,
             Err(primary_err) => {
                 // Retry with TLS for managed MySQL that enforces secure transport.
-                if let Ok(pool) = Self::build_pool(host, port, database, username, password, true) {
+                if let Ok(pool) = Self::build_pool(host, port, database, username, password, true, read_only) {
                     pool
                 } else if host.eq_ignore_ascii_case("localhost") {
                     // Common local setup: MySQL listens on 127.0.0.1 but "localhost" may resolve unexpectedly.
-                    Self::build_pool("127.0.0.1", port, database, username, password, false)
-                        .or_else(|_| Self::build_pool("127.0.0.1", port, database, username, password, true))
-                        .map_err(|fallback_err| {
-                            format!(
+                    Self::build_pool("127.0.0.1", port, database, username, password, false, read_only)
+                        .or_else(|_| Self::build_pool("127.0.0.1", port, database, username, password, true, read_only))
+                        .map_err(|fallback_err| DbError {
+                            message: format!(
                                 "{}. Retries with TLS/127.0.0.1 also failed: {}",
                                 primary_err, fallback_err
-                            )
+                            ),
+                            ..primary_err
                         })?
                 } else {
                     return Err(primary_err);
@@ -105,19 +155,156 @@ impl MysqlConnection {
             }
         };
 
-        Ok(Box::new(MysqlConnection {
+        let connection = MysqlConnection {
             id: uuid::Uuid::new_v4().to_string(),
             name: format!("{}/{}", host, database),
-            pool,
-        }))
+            pool: Mutex::new(pool),
+            opts,
+            read_only,
+            thread_ids: Mutex::new(HashSet::new()),
+        };
+        // Records the connection the handshake opened.
+        connection.get_conn()?;
+        Ok(Box::new(connection))
+    }
+
+    fn to_params(params: &[QueryParam]) -> Params {
+        if params.is_empty() {
+            return Params::Empty;
+        }
+        Params::Positional(params.iter().map(|p| match p {
+            QueryParam::Null => Value::NULL,
+            QueryParam::Bool(b) => Value::Int(*b as i64),
+            QueryParam::Int(n) => Value::Int(*n),
+            QueryParam::Float(n) => Value::Double(*n),
+            QueryParam::Text(s) => Value::Bytes(s.as_bytes().to_vec()),
+            QueryParam::Bytes(b) => Value::Bytes(b.clone()),
+        }).collect())
     }
 
-    fn get_conn(&self) -> Result<PooledConn, String> {
-        self.pool.get_conn()
-            .map_err(|e| format!("Connection pool error: {}", e))
+    /// Reads the first result set, stopping after `max_rows`. The rest of the
+    /// set is discarded when `result` is dropped.
+    fn collect_rows<P: Protocol>(
+        result: &mut mysql::QueryResult<'_, '_, '_, P>,
+        limits: &QueryLimits,
+        start: std::time::Instant,
+    ) -> Result<QueryResult, DbError> {
+        match result.iter() {
+            Some(set) => Self::collect_set(set, limits, start),
+            None => Ok(QueryResult::new(vec![], vec![], false, start)),
+        }
+    }
+
+    /// Reads one result set, stopping after `max_rows`. Dropping `set` skips
+    /// its remaining rows, so the next set can be read after it.
+    fn collect_set<P: Protocol>(
+        set: mysql::ResultSet<'_, '_, '_, '_, P>,
+        limits: &QueryLimits,
+        start: std::time::Instant,
+    ) -> Result<QueryResult, DbError> {
+        let set_columns: Vec<Column> = set.columns().as_ref().to_vec();
+        let columns: Vec<String> = set_columns
+            .iter()
+            .map(|c| c.name_str().to_string())
+            .collect();
+
+        let mut rows: Vec<Row> = Vec::new();
+        let mut truncated = false;
+        for row in set {
+            let row = row?;
+            if limits.max_rows.is_some_and(|max| rows.len() >= max) {
+                truncated = true;
+                break;
+            }
+            rows.push(
+                set_columns.iter()
+                    .enumerate()
+                    .map(|(i, column)| row.as_ref(i).and_then(|v| Self::value_to_cell(v, column)))
+                    .collect()
+            );
+        }
+
+        Ok(QueryResult::new(columns, rows, truncated, start))
+    }
+
+    /// Runs each statement on `conn` and reads every result set it returns.
+    fn collect_sets(conn: &mut PooledConn, statements: &[&str], limits: &QueryLimits) -> Result<Vec<ResultSet>, DbError> {
+        let mut sets = Vec::new();
+        for (statement, sql) in statements.iter().enumerate() {
+            let mut start = std::time::Instant::now();
+            let mut result = conn.query_iter(*sql)
+                .map_err(|e| DbError::from(e).locate(sql))?;
+            loop {
+                // Only known before the set is read; zero for sets with rows.
+                let rows_affected = result.affected_rows();
+                let Some(set) = result.iter() else { break };
+                sets.push(ResultSet {
+                    statement,
+                    result: Self::collect_set(set, limits, start)?,
+                    rows_affected,
+                });
+                start = std::time::Instant::now();
+            }
+        }
+        Ok(sets)
+    }
+
+    /// Caps top-level SELECTs on `conn` at one row past `max_rows`, so the
+    /// server stops there instead of sending rows for the client to discard.
+    /// A query's own LIMIT takes precedence, and statements run inside stored
+    /// procedures are not capped.
+    fn cap_rows(conn: &mut PooledConn, max_rows: Option<usize>) -> Result<(), DbError> {
+        let cap = max_rows.map_or_else(|| "DEFAULT".to_string(), |max| (max + 1).to_string());
+        conn.query_drop(format!("SET SESSION sql_select_limit = {}", cap))?;
+        Ok(())
+    }
+
+    fn get_conn(&self) -> Result<PooledConn, DbError> {
+        let pool = self.pool.lock().map_err(|_| "Lock error")?.clone();
+        let conn = pool.get_conn()
+            .map_err(|e| with_context("Connection pool error", e))?;
+        self.thread_ids.lock().map_err(|_| "Lock error")?.insert(conn.connection_id());
+        Ok(conn)
     }
 }
 
+impl From<mysql::Error> for DbError {
+    fn from(e: mysql::Error) -> Self {
+        use mysql::{DriverError, Error};
+        match e {
+            Error::MySqlError(e) => {
+                // HY000 is MySQL's catch-all state; the vendor code says more.
+                let mut error = if e.state == "HY000" {
+                    DbError { code: Some(e.code.to_string()), ..DbError::new(DbErrorKind::Other, e.message) }
+                } else {
+                    DbError::from_sqlstate(&e.state, e.message)
+                };
+                match e.code {
+                    1205 => (error.kind, error.retryable) = (DbErrorKind::Timeout, true),
+                    3024 => error.kind = DbErrorKind::Timeout,
+                    1290 | 1792 => error.kind = DbErrorKind::Permission,
+                    1044 | 1045 | 1142 | 1143 | 1227 => error.kind = DbErrorKind::Permission,
+                    1049 | 1305 => error.kind = DbErrorKind::NotFound,
+                    _ => {}
+                }
+                error
+            }
+            Error::IoError(_) | Error::CodecError(_) | Error::TlsError(_)
+            | Error::DriverError(DriverError::ConnectTimeout | DriverError::CouldNotConnect(_)) => {
+                DbError::connection(e.to_string())
+            }
+            Error::DriverError(DriverError::Timeout) => DbError { retryable: true, ..DbError::new(DbErrorKind::Timeout, e.to_string()) },
+            e => DbError::from(e.to_string()),
+        }
+    }
+}
+
+/// Keeps the classification of `e` while prefixing its message.
+fn with_context(context: &str, e: mysql::Error) -> DbError {
+    let e = DbError::from(e);
+    DbError { message: format!("{}: {}", context, e.message), ..e }
+}
+
 impl DatabaseConnection for MysqlConnection {
     fn info(&self) -> ConnectionInfo {
         ConnectionInfo {
@@ -126,80 +313,115 @@ impl DatabaseConnection for MysqlConnection {
             db_type: DatabaseType::Mysql,
             version: "MySQL".to_string(),
             connected: true,
+            read_only: self.read_only,
         }
     }
 
-    fn query(&self, sql: &str) -> Result<QueryResult, String> {
+    fn query_limited(&self, sql: &str, params: &[QueryParam], limits: &QueryLimits) -> Result<QueryResult, DbError> {
         let start = std::time::Instant::now();
         let mut conn = self.get_conn()?;
+        if limits.max_rows.is_some() {
+            Self::cap_rows(&mut conn, limits.max_rows)?;
+        }
 
-        let result: Vec<mysql::Row> = conn.query(sql)
-            .map_err(|e| format!("Query error: {}", e))?;
+        // The text protocol handles statements that can't be prepared (e.g. some SHOW forms).
+        let result = if params.is_empty() {
+            conn.query_iter(sql)
+                .map_err(|e| DbError::from(e).locate(sql))
+                .and_then(|mut result| Self::collect_rows(&mut result, limits, start))
+        } else {
+            conn.exec_iter(sql, Self::to_params(params))
+                .map_err(|e| DbError::from(e).locate(sql))
+                .and_then(|mut result| Self::collect_rows(&mut result, limits, start))
+        };
+        if limits.max_rows.is_some() {
+            Self::cap_rows(&mut conn, None)?;
+        }
+        result
+    }
 
-        if result.is_empty() {
-            return Ok(QueryResult {
-                columns: vec![],
-                rows: vec![],
-                row_count: 0,
-                execution_time_ms: start.elapsed().as_millis() as u64,
-            });
+    // Each statement goes on its own, on one pooled connection, so a batch runs
+    // exactly the statements the caller validated.
+    fn query_multi(&self, statements: &[&str], limits: &QueryLimits) -> Result<Vec<ResultSet>, DbError> {
+        let mut conn = self.get_conn()?;
+        if limits.max_rows.is_some() {
+            Self::cap_rows(&mut conn, limits.max_rows)?;
+        }
+        let sets = Self::collect_sets(&mut conn, statements, limits);
+        if limits.max_rows.is_some() {
+            Self::cap_rows(&mut conn, None)?;
         }
+        sets
+    }
 
-        let columns: Vec<String> = result[0].columns()
-            .iter()
-            .map(|c| c.name_str().to_string())
-            .collect();
+    fn execute_with_params(&self, sql: &str, params: &[QueryParam]) -> Result<ExecuteResult, DbError> {
+        let start = std::time::Instant::now();
+        let mut conn = self.get_conn()?;
 
-        let rows: Vec<Vec<Option<String>>> = result
-            .iter()
-            .map(|row| {
-                columns.iter()
-                    .enumerate()
-                    .map(|(i, _)| {
-                        match row.as_ref(i) {
-                            Some(value) => Self::value_to_string(value),
-                            None => None,
-                        }
-                    })
-                    .collect()
-            })
-            .collect();
+        if params.is_empty() {
+            conn.query_drop(sql)
+        } else {
+            conn.exec_drop(sql, Self::to_params(params))
+        }.map_err(|e| DbError::from(e).locate(sql))?;
 
-        Ok(QueryResult {
-            columns,
-            rows: rows.clone(),
-            row_count: rows.len(),
+        Ok(ExecuteResult {
+            rows_affected: conn.affected_rows(),
+            last_insert_id: Some(conn.last_insert_id() as i64),
             execution_time_ms: start.elapsed().as_millis() as u64,
         })
     }
 
-    fn execute(&self, sql: &str) -> Result<ExecuteResult, String> {
+    // Pooled sessions are not sticky, so the transaction has to hold one conn.
+    fn execute_in_transaction(&self, statements: &[(String, Vec<QueryParam>)]) -> Result<ExecuteResult, DbError> {
         let start = std::time::Instant::now();
         let mut conn = self.get_conn()?;
+        let mut tx = conn.start_transaction(TxOpts::default())?;
 
-        conn.query_drop(sql)
-            .map_err(|e| format!("Execute error: {}", e))?;
+        let mut rows_affected = 0;
+        for (sql, params) in statements {
+            // Dropping `tx` on error rolls back.
+            if params.is_empty() {
+                tx.query_drop(sql)
+            } else {
+                tx.exec_drop(sql, Self::to_params(params))
+            }.map_err(|e| DbError::from(e).locate(sql))?;
+            rows_affected += tx.affected_rows();
+        }
+        tx.commit()?;
 
         Ok(ExecuteResult {
-            rows_affected: conn.affected_rows(),
-            last_insert_id: Some(conn.last_insert_id() as i64),
+            rows_affected,
+            last_insert_id: None,
             execution_time_ms: start.elapsed().as_millis() as u64,
         })
     }
 
-    fn get_tables(&self) -> Result<Vec<TableInfo>, String> {
-        // Load all tables in all schemas/databases visible to this credential.
+    fn query_bytes(&self, sql: &str, params: &[QueryParam]) -> Result<Option<Vec<u8>>, DbError> {
+        let mut conn = self.get_conn()?;
+        let row: mysql::Row = conn.exec_first(sql, Self::to_params(params))
+            .map_err(|e| DbError::from(e).locate(sql))?
+            .ok_or_else(|| DbError::new(DbErrorKind::NotFound, "No row matches"))?;
+        Ok(match row.as_ref(0) {
+            Some(Value::Bytes(bytes)) => Some(bytes.clone()),
+            Some(other) => Self::value_to_string(other).map(String::into_bytes),
+            None => None,
+        })
+    }
+
+    fn get_tables(&self) -> Result<Vec<TableInfo>, DbError> {
+        // Only the current database; shared servers can have thousands of tables
+        // across the databases a credential sees. See `list_databases`.
         let result = self.query(
             "SELECT table_schema, table_name \
              FROM information_schema.tables \
-             WHERE table_type = 'BASE TABLE' \
+             WHERE table_type = 'BASE TABLE' AND table_schema = DATABASE() \
              ORDER BY table_schema, table_name"
         )?;
 
         Ok(result.rows.iter()
             .filter_map(|row| {
-                let schema = row.get(0)?.clone().unwrap_or_default();
-                let table_name = row.get(1)?.clone().unwrap_or_default();
+                let schema = row.first()?.as_ref().and_then(CellValue::as_text).unwrap_or_default().to_string();
+                let table_name = row.get(1)?.as_ref().and_then(CellValue::as_text).unwrap_or_default().to_string();
                 Some(TableInfo {
                     name: format!("{}.{}", schema, table_name),
                     schema: Some(schema),
@@ -209,7 +431,33 @@ impl DatabaseConnection for MysqlConnection {
             .collect())
     }
 
-    fn get_columns(&self, table: &str) -> Result<Vec<ColumnInfo>, String> {
+    fn list_databases(&self) -> Result<DatabaseList, DbError> {
+        let databases = self.query("SHOW DATABASES")?;
+        let current = self.query("SELECT DATABASE()")?;
+        Ok(DatabaseList {
+            databases: databases.rows.iter().filter_map(|row| cell_text(row, 0)).collect(),
+            current_database: current.rows.first().and_then(|row| cell_text(row, 0)),
+            schemas: Vec::new(),
+            current_schema: None,
+        })
+    }
+
+    /// MySQL calls its databases schemas, so either argument names the database.
+    fn use_database(&self, database: Option<&str>, schema: Option<&str>) -> Result<(), DbError> {
+        let Some(database) = database.or(schema) else { return Ok(()) };
+        let pool = Pool::new(OptsBuilder::from_opts(self.opts.clone()).db_name(Some(database)))
+            .map_err(|e| with_context("Connection pool error", e))?;
+        // Fails here, for an unknown or forbidden database, rather than on the next query.
+        let conn = pool.get_conn()?;
+        // The old pool's connections close once it is dropped.
+        let mut ids = self.thread_ids.lock().map_err(|_| "Lock error")?;
+        ids.clear();
+        ids.insert(conn.connection_id());
+        *self.pool.lock().map_err(|_| "Lock error")? = pool;
+        Ok(())
+    }
+
+    fn get_columns(&self, table: &str) -> Result<Vec<ColumnInfo>, DbError> {
         let (schema_name, table_name) = match table.split_once('.') {
             Some((schema, name)) => (schema.to_string(), name.to_string()),
             None => ("".to_string(), table.to_string()),
@@ -224,7 +472,7 @@ impl DatabaseConnection for MysqlConnection {
                  WHERE table_name = ? \
                  ORDER BY ordinal_position",
                 (table_name.as_str(),)
-            ).map_err(|e| format!("Query error: {}", e))?
+            )?
         } else {
             conn.exec(
                 "SELECT column_name, column_type, is_nullable, column_default, column_key \
@@ -232,7 +480,7 @@ impl DatabaseConnection for MysqlConnection {
                  WHERE table_schema = ? AND table_name = ? \
                  ORDER BY ordinal_position",
                 (schema_name.as_str(), table_name.as_str())
-            ).map_err(|e| format!("Query error: {}", e))?
+            )?
         };
 
         let columns: Vec<String> = if result.is_empty() {
@@ -265,8 +513,64 @@ impl DatabaseConnection for MysqlConnection {
             .collect())
     }
 
+    fn get_catalog(&self) -> Result<Catalog, DbError> {
+        let mut conn = self.get_conn()?;
+        let mut builder = CatalogBuilder::default();
+        let text = |row: &mysql::Row, i: usize| row.as_ref(i).and_then(Self::value_to_string);
+
+        let columns: Vec<mysql::Row> = conn.query(
+            "SELECT c.table_schema, c.table_name, c.column_name, c.column_type, \
+                    c.is_nullable, c.column_default, c.column_key \
+             FROM information_schema.columns c \
+             JOIN information_schema.tables t \
+               ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+             WHERE t.table_type = 'BASE TABLE' AND c.table_schema = DATABASE() \
+             ORDER BY c.table_schema, c.table_name, c.ordinal_position"
+        )?;
+
+        for row in &columns {
+            let schema = text(row, 0).unwrap_or_default();
+            let table = text(row, 1).unwrap_or_default();
+            builder.add_column(Some(&schema), &table, ColumnInfo {
+                name: text(row, 2).unwrap_or_default(),
+                data_type: text(row, 3).unwrap_or_default(),
+                nullable: text(row, 4).unwrap_or_default().eq_ignore_ascii_case("yes"),
+                default: text(row, 5),
+                primary_key: text(row, 6).unwrap_or_default().eq_ignore_ascii_case("pri"),
+            });
+        }
+
+        let fks: Vec<mysql::Row> = conn.query(
+            "SELECT table_schema, table_name, constraint_name, column_name, \
+                    referenced_table_schema, referenced_table_name, referenced_column_name \
+             FROM information_schema.key_column_usage \
+             WHERE referenced_table_name IS NOT NULL AND table_schema = DATABASE() \
+             ORDER BY table_schema, table_name, constraint_name, ordinal_position"
+        )?;
+
+        for row in &fks {
+            let schema = text(row, 0).unwrap_or_default();
+            let table = text(row, 1).unwrap_or_default();
+            builder.add_foreign_key(Some(&schema), &table, ForeignKeyInfo {
+                name: text(row, 2),
+                columns: text(row, 3).into_iter().collect(),
+                referenced_schema: text(row, 4),
+                referenced_table: text(row, 5).unwrap_or_default(),
+                referenced_columns: text(row, 6).into_iter().collect(),
+            });
+        }
+
+        Ok(builder.build())
+    }
+
+    fn own_session_ids(&self) -> Vec<String> {
+        self.thread_ids.lock()
+            .map(|ids| ids.iter().map(u32::to_string).collect())
+            .unwrap_or_default()
+    }
+
     fn is_alive(&self) -> bool {
-        self.pool.get_conn().is_ok()
+        self.get_conn().is_ok()
     }
 
     fn close(&self) {
@@ -1,14 +1,25 @@
-use tokio_postgres::Config as PgConfig;
-use std::sync::Arc;
-use tokio::runtime::Runtime;
-use super::connection::DatabaseConnection;
+use tokio_postgres::{AsyncMessage, Config as PgConfig, SimpleQueryMessage, SimpleQueryRow};
+use tokio_postgres::error::{ErrorPosition, SqlState};
+use tokio_postgres::types::{to_sql_checked, Format, FromSql, IsNull, Kind, ToSql, Type};
+use bytes::BytesMut;
+use futures_util::{StreamExt, TryStreamExt};
+use std::sync::Mutex;
+use tauri::async_runtime::RuntimeHandle;
+use super::catalog::CatalogBuilder;
+use super::connection::{DatabaseConnection, NotificationReceiver};
+use super::dialect::quote_ident;
 use super::types::*;
 
 pub struct PostgresConnection {
     id: String,
     name: String,
-    client: Arc<tokio_postgres::Client>,
-    rt: Runtime,
+    /// Async lock so a portal's transaction can borrow the client mutably.
+    client: tokio::sync::Mutex<tokio_postgres::Client>,
+    rt: RuntimeHandle,
+    read_only: bool,
+    notifications: Mutex<Option<NotificationReceiver>>,
+    /// Schema set through `use_database`; scopes `get_tables` when set.
+    schema: Mutex<Option<String>>,
 }
 
 impl PostgresConnection {
@@ -19,38 +30,478 @@ impl PostgresConnection {
         username: &str,
         password: &str,
         _use_ssl: bool,
-    ) -> Result<Box<dyn DatabaseConnection + Send>, String> {
-        let rt = Runtime::new().map_err(|e| format!("Runtime error: {}", e))?;
+        read_only: bool,
+    ) -> Result<Box<dyn DatabaseConnection + Send>, DbError> {
+        // Driver calls run on blocking threads and drive the client on the app's
+        // shared runtime, where the connection task also lives.
+        let rt = tauri::async_runtime::handle();
 
         let conn_str = format!(
             "host={} port={} user={} password={} dbname={}",
             host, port, username, password, database
         );
 
-        let pg_config: PgConfig = conn_str
+        let mut pg_config: PgConfig = conn_str
             .parse()
-            .map_err(|e| format!("Config error: {}", e))?;
+            .map_err(|e| DbError::invalid_input(format!("Config error: {}", e)))?;
+        if read_only {
+            // Startup option, so it is also the value a later `RESET ALL` returns to.
+            pg_config.options("-c default_transaction_read_only=on");
+        }
 
         let result = rt.block_on(async { pg_config.connect(tokio_postgres::NoTls).await });
 
-        let (client, connection) = result.map_err(|e| format!("Connection error: {}", e))?;
+        let (client, mut connection) = result?;
 
-        // Spawn connection handler
+        // Drive the connection ourselves so `NOTIFY` messages are kept instead of
+        // dropped. Once it exits the client reports `is_closed()` and the health
+        // checker takes over.
+        let (notify_tx, notify_rx) = tokio::sync::mpsc::unbounded_channel();
         rt.spawn(async move {
-            if let Err(e) = connection.await {
-                eprintln!("PostgreSQL connection error: {}", e);
+            let mut messages = futures_util::stream::poll_fn(move |cx| connection.poll_message(cx));
+            while let Some(message) = messages.next().await {
+                match message {
+                    Ok(AsyncMessage::Notification(n)) => {
+                        let _ = notify_tx.send(DbNotification {
+                            channel: n.channel().to_string(),
+                            payload: n.payload().to_string(),
+                            process_id: n.process_id(),
+                        });
+                    }
+                    Ok(AsyncMessage::Notice(notice)) => log::info!("PostgreSQL notice: {}", notice),
+                    Ok(_) => {}
+                    Err(e) => {
+                        log::warn!("PostgreSQL connection error: {}", e);
+                        break;
+                    }
+                }
             }
         });
 
         Ok(Box::new(PostgresConnection {
             id: uuid::Uuid::new_v4().to_string(),
             name: format!("{}/{}", host, database),
-            client: Arc::new(client),
+            client: tokio::sync::Mutex::new(client),
             rt,
+            read_only,
+            notifications: Mutex::new(Some(notify_rx)),
+            schema: Mutex::new(None),
         }))
     }
 }
 
+// Parameters go over the wire in text format so the server parses them into
+// whatever type it inferred for each placeholder (dates, numerics, uuids...).
+impl ToSql for QueryParam {
+    fn to_sql(&self, _ty: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        match self.to_text() {
+            Some(text) => {
+                out.extend_from_slice(text.as_bytes());
+                Ok(IsNull::No)
+            }
+            None => Ok(IsNull::Yes),
+        }
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+
+    fn encode_format(&self, _ty: &Type) -> Format {
+        Format::Text
+    }
+
+    to_sql_checked!();
+}
+
+impl From<tokio_postgres::Error> for DbError {
+    fn from(e: tokio_postgres::Error) -> Self {
+        let Some(db) = e.as_db_error() else {
+            let io = std::error::Error::source(&e).is_some_and(|s| s.is::<std::io::Error>());
+            if e.is_closed() || io {
+                return DbError::connection(e.to_string());
+            }
+            return DbError::from(e.to_string());
+        };
+        DbError {
+            detail: db.detail().map(str::to_string),
+            hint: db.hint().map(str::to_string),
+            position: match db.position() {
+                Some(ErrorPosition::Original(position)) => Some(*position),
+                _ => None,
+            },
+            ..DbError::from_sqlstate(db.code().code(), db.message())
+        }
+    }
+}
+
+fn pg_params(params: &[QueryParam]) -> Vec<&(dyn ToSql + Sync)> {
+    params.iter().map(|p| p as &(dyn ToSql + Sync)).collect()
+}
+
+/// A value's wire bytes, whatever its type.
+struct Raw<'a>(&'a [u8]);
+
+impl<'a> FromSql<'a> for Raw<'a> {
+    fn from_sql(_ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(Raw(raw))
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+}
+
+/// Sends bytes received in binary format back as a parameter of the same type.
+#[derive(Debug)]
+struct Binary<'a>(Option<&'a [u8]>);
+
+impl ToSql for Binary<'_> {
+    fn to_sql(&self, _ty: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        match self.0 {
+            Some(bytes) => {
+                out.extend_from_slice(bytes);
+                Ok(IsNull::No)
+            }
+            None => Ok(IsNull::Yes),
+        }
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+
+    to_sql_checked!();
+}
+
+fn be_i16(raw: &[u8], at: usize) -> Option<i16> {
+    Some(i16::from_be_bytes(raw.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn be_i32(raw: &[u8]) -> Option<i32> {
+    Some(i32::from_be_bytes(raw.try_into().ok()?))
+}
+
+fn be_i64(raw: &[u8]) -> Option<i64> {
+    Some(i64::from_be_bytes(raw.try_into().ok()?))
+}
+
+/// Seconds fraction as PostgreSQL prints it: trailing zeros dropped.
+fn fraction(micros: i64) -> String {
+    match micros {
+        0 => String::new(),
+        _ => format!(".{:06}", micros).trim_end_matches('0').to_string(),
+    }
+}
+
+/// Base-10000 digits to decimal text, `dscale` digits after the point.
+fn numeric_text(raw: &[u8]) -> Option<String> {
+    let ndigits = be_i16(raw, 0)? as usize;
+    let weight = be_i16(raw, 2)? as i64;
+    let sign = be_i16(raw, 4)? as u16;
+    let dscale = be_i16(raw, 6)? as usize;
+    let digits: Vec<i16> = (0..ndigits).map(|i| be_i16(raw, 8 + i * 2)).collect::<Option<_>>()?;
+    let digit = |i: i64| usize::try_from(i).ok().and_then(|i| digits.get(i)).copied().unwrap_or(0);
+
+    let mut text = match sign {
+        0x0000 => String::new(),
+        0x4000 => "-".to_string(),
+        0xC000 => return Some("NaN".into()),
+        0xD000 => return Some("Infinity".into()),
+        0xF000 => return Some("-Infinity".into()),
+        _ => return None,
+    };
+    if weight < 0 {
+        text.push('0');
+    } else {
+        text.push_str(&digit(0).to_string());
+        for i in 1..=weight {
+            text.push_str(&format!("{:04}", digit(i)));
+        }
+    }
+    if dscale > 0 {
+        let mut frac = String::new();
+        let mut i = weight + 1;
+        while frac.len() < dscale {
+            frac.push_str(&format!("{:04}", digit(i)));
+            i += 1;
+        }
+        frac.truncate(dscale);
+        text.push('.');
+        text.push_str(&frac);
+    }
+    Some(text)
+}
+
+/// Days since 2000-01-01 as `YYYY-MM-DD`; BC dates are left to the server.
+fn date_text(days: i32) -> Option<String> {
+    match days {
+        i32::MAX => Some("infinity".into()),
+        i32::MIN => Some("-infinity".into()),
+        _ => {
+            let date = chrono::NaiveDate::from_ymd_opt(2000, 1, 1)?
+                .checked_add_signed(chrono::Duration::days(days.into()))?;
+            (chrono::Datelike::year(&date) >= 1).then(|| date.format("%Y-%m-%d").to_string())
+        }
+    }
+}
+
+fn time_text(micros: i64) -> String {
+    let secs = micros / 1_000_000;
+    format!("{:02}:{:02}:{:02}{}", secs / 3600, secs / 60 % 60, secs % 60, fraction(micros % 1_000_000))
+}
+
+/// Quotes an array element or composite field the way PostgreSQL's text
+/// output does when it would otherwise be ambiguous.
+fn quote_element(text: &str, specials: &str, array: bool) -> String {
+    let ambiguous = text.is_empty()
+        || text.chars().any(|c| c.is_whitespace() || specials.contains(c))
+        || (array && text.eq_ignore_ascii_case("NULL"));
+    if !ambiguous {
+        return text.to_string();
+    }
+    let mut quoted = String::from('"');
+    for c in text.chars() {
+        match c {
+            '"' if !array => quoted.push_str("\"\""),
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Shortest round-trip digits, in exponent form (`1e+20`) outside the range
+/// PostgreSQL prints positionally.
+fn float_text<F: Copy + Into<f64> + std::fmt::Display + std::fmt::LowerExp>(v: F) -> String {
+    match v.into() {
+        f64::INFINITY => "Infinity".into(),
+        f64::NEG_INFINITY => "-Infinity".into(),
+        wide if wide != 0.0 && !(1e-4..1e15).contains(&wide.abs()) => {
+            let text = format!("{:e}", v);
+            let (mantissa, exp) = text.split_once('e').unwrap_or((&text, "0"));
+            let (sign, digits) = exp.strip_prefix('-').map_or(("+", exp), |d| ("-", d));
+            format!("{}e{}{:0>2}", mantissa, sign, digits)
+        }
+        _ => v.to_string(),
+    }
+}
+
+/// One-based arrays as `{...}`, nested per dimension.
+fn array_text(elem: &Type, raw: &[u8]) -> Option<String> {
+    let ndim = usize::try_from(be_i32(raw.get(0..4)?)?).ok()?;
+    if ndim == 0 {
+        return Some("{}".into());
+    }
+    let mut dims = Vec::with_capacity(ndim);
+    for d in 0..ndim {
+        let at = 12 + d * 8;
+        let len = usize::try_from(be_i32(raw.get(at..at + 4)?)?).ok()?;
+        if be_i32(raw.get(at + 4..at + 8)?)? != 1 {
+            return None;
+        }
+        dims.push(len);
+    }
+    let mut rest = raw.get(12 + ndim * 8..)?;
+    let mut elements = Vec::new();
+    while !rest.is_empty() {
+        let len = be_i32(rest.get(0..4)?)?;
+        rest = &rest[4..];
+        elements.push(match usize::try_from(len) {
+            Ok(len) => {
+                let text = binary_text(elem, rest.get(..len)?)?;
+                rest = &rest[len..];
+                quote_element(&text, "{},\"\\", true)
+            }
+            Err(_) => "NULL".into(),
+        });
+    }
+    // Innermost dimension first: group elements into `{...}` until one remains.
+    for &len in dims.iter().rev() {
+        if len == 0 || elements.len() % len != 0 {
+            return None;
+        }
+        elements = elements.chunks(len).map(|group| format!("{{{}}}", group.join(","))).collect();
+    }
+    elements.pop()
+}
+
+/// Composite values as `(...)`; anonymous records carry each field's type.
+fn record_text(fields: Option<&[tokio_postgres::types::Field]>, raw: &[u8]) -> Option<String> {
+    let count = usize::try_from(be_i32(raw.get(0..4)?)?).ok()?;
+    let mut rest = &raw[4..];
+    let mut texts = Vec::with_capacity(count);
+    for i in 0..count {
+        let oid = be_i32(rest.get(0..4)?)? as u32;
+        let len = be_i32(rest.get(4..8)?)?;
+        rest = &rest[8..];
+        let ty = match fields {
+            Some(fields) => fields.get(i)?.type_().clone(),
+            None => Type::from_oid(oid)?,
+        };
+        texts.push(match usize::try_from(len) {
+            Ok(len) => {
+                let text = binary_text(&ty, rest.get(..len)?)?;
+                rest = &rest[len..];
+                quote_element(&text, "(),\"\\", false)
+            }
+            Err(_) => String::new(),
+        });
+    }
+    Some(format!("({})", texts.join(",")))
+}
+
+/// PostgreSQL's text output for the binary format of the types this side
+/// knows. `None` leaves the value to `server_text`.
+fn binary_text(ty: &Type, raw: &[u8]) -> Option<String> {
+    Some(match *ty {
+        Type::BYTEA => format!("\\x{}", raw.iter().map(|b| format!("{:02x}", b)).collect::<String>()),
+        Type::BOOL => if *raw.first()? != 0 { "t" } else { "f" }.to_string(),
+        Type::INT2 => be_i16(raw, 0)?.to_string(),
+        Type::INT4 => be_i32(raw)?.to_string(),
+        Type::INT8 => be_i64(raw)?.to_string(),
+        Type::OID => (be_i32(raw)? as u32).to_string(),
+        Type::FLOAT4 => float_text(f32::from_be_bytes(raw.try_into().ok()?)),
+        Type::FLOAT8 => float_text(f64::from_be_bytes(raw.try_into().ok()?)),
+        Type::NUMERIC => numeric_text(raw)?,
+        Type::DATE => date_text(be_i32(raw)?)?,
+        Type::TIME => time_text(be_i64(raw)?),
+        Type::TIMESTAMP => match be_i64(raw)? {
+            i64::MAX => "infinity".into(),
+            i64::MIN => "-infinity".into(),
+            micros => {
+                let days = micros.div_euclid(86_400_000_000);
+                format!("{} {}", date_text(days.try_into().ok()?)?, time_text(micros.rem_euclid(86_400_000_000)))
+            }
+        },
+        Type::UUID => {
+            let hex: String = raw.iter().map(|b| format!("{:02x}", b)).collect();
+            if hex.len() != 32 {
+                return None;
+            }
+            format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+        }
+        // Version byte, then the text.
+        Type::JSONB => std::str::from_utf8(raw.strip_prefix(&[1])?).ok()?.to_string(),
+        Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::NAME | Type::JSON | Type::XML | Type::UNKNOWN => {
+            std::str::from_utf8(raw).ok()?.to_string()
+        }
+        Type::RECORD => record_text(None, raw)?,
+        _ => match ty.kind() {
+            Kind::Enum(_) => std::str::from_utf8(raw).ok()?.to_string(),
+            Kind::Array(elem) => array_text(elem, raw)?,
+            Kind::Composite(fields) => record_text(Some(fields), raw)?,
+            Kind::Domain(base) => binary_text(base, raw)?,
+            _ => return None,
+        },
+    })
+}
+
+/// Like `binary_text`, but with bytea kept as bytes and booleans spelled out
+/// as the text-format path does.
+fn binary_cell(ty: &Type, raw: &[u8]) -> Option<CellValue> {
+    match *ty {
+        Type::BYTEA => Some(CellValue::binary(raw)),
+        Type::BOOL => Some((*raw.first()? != 0).to_string().into()),
+        _ => binary_text(ty, raw).map(CellValue::from),
+    }
+}
+
+/// Decodes a cell from PostgreSQL's text format, which every type has.
+fn text_cell(ty: &Type, text: Option<&str>) -> Option<CellValue> {
+    let text = text?;
+    Some(match *ty {
+        // `\x...` unless the session uses the old escape format.
+        Type::BYTEA => match text.strip_prefix("\\x").and_then(decode_hex) {
+            Some(bytes) => CellValue::binary(&bytes),
+            None => CellValue::binary(text.as_bytes()),
+        },
+        Type::BOOL => (text == "t").to_string().into(),
+        _ => text.to_string().into(),
+    })
+}
+
+fn text_row(row: &SimpleQueryRow, types: &[Type]) -> Row {
+    types.iter().enumerate().map(|(i, ty)| text_cell(ty, row.get(i))).collect()
+}
+
+/// Values per `server_text` round trip.
+const TEXT_BATCH: usize = 1000;
+
+/// Has the server print values this side can't decode (timestamptz in the
+/// session time zone, intervals, arrays, composites...) by sending them back
+/// as parameters of their own type and casting to text.
+async fn server_text(client: &tokio_postgres::Client, ty: &Type, values: &[Option<&[u8]>]) -> Result<Vec<Option<String>>, DbError> {
+    let mut texts = Vec::with_capacity(values.len());
+    for chunk in values.chunks(TEXT_BATCH) {
+        let select = (1..=chunk.len()).map(|i| format!("${}::text", i)).collect::<Vec<_>>().join(", ");
+        let params: Vec<Binary> = chunk.iter().map(|v| Binary(*v)).collect();
+        let typed: Vec<(&(dyn ToSql + Sync), Type)> = params.iter()
+            .map(|p| (p as &(dyn ToSql + Sync), ty.clone()))
+            .collect();
+        let rows = client.query_typed(&format!("SELECT {}", select), &typed).await?;
+        let row = rows.first().ok_or("Text conversion returned no row")?;
+        texts.extend((0..chunk.len()).map(|i| row.try_get::<_, Option<String>>(i).ok().flatten()));
+    }
+    Ok(texts)
+}
+
+/// Decodes binary-format rows, with one `server_text` pass per column that
+/// has values `binary_cell` can't read.
+async fn binary_rows(client: &tokio_postgres::Client, rows: &[tokio_postgres::Row], types: &[Type]) -> Result<Vec<Row>, DbError> {
+    let mut decoded: Vec<Row> = vec![Vec::with_capacity(types.len()); rows.len()];
+    for (col, ty) in types.iter().enumerate() {
+        let raws: Vec<Option<&[u8]>> = rows.iter()
+            .map(|row| row.try_get::<_, Option<Raw>>(col).ok().flatten().map(|r| r.0))
+            .collect();
+        let mut cells: Vec<Option<CellValue>> = raws.iter()
+            .map(|raw| raw.and_then(|raw| binary_cell(ty, raw)))
+            .collect();
+        let pending: Vec<usize> = (0..raws.len()).filter(|&i| raws[i].is_some() && cells[i].is_none()).collect();
+        if !pending.is_empty() {
+            let values: Vec<Option<&[u8]>> = pending.iter().map(|&i| raws[i]).collect();
+            for (i, text) in pending.into_iter().zip(server_text(client, ty, &values).await?) {
+                cells[i] = text.map(CellValue::from);
+            }
+        }
+        for (row, cell) in decoded.iter_mut().zip(cells) {
+            row.push(cell);
+        }
+    }
+    Ok(decoded)
+}
+
+/// Whether the session is inside a transaction block the user opened:
+/// `SAVEPOINT` fails outside one, and is released straight away inside.
+async fn in_transaction(client: &tokio_postgres::Client) -> Result<bool, DbError> {
+    match client.simple_query("SAVEPOINT crate_probe; RELEASE SAVEPOINT crate_probe").await {
+        Ok(_) => Ok(true),
+        Err(e) if e.code() == Some(&SqlState::NO_ACTIVE_SQL_TRANSACTION) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Runs `stmt` through a portal that returns at most `cap` rows, so the
+/// server stops there rather than producing the rest for the client to drop.
+/// Portals only live inside a transaction; it commits like autocommit would.
+async fn portal_rows(
+    client: &mut tokio_postgres::Client,
+    stmt: &tokio_postgres::Statement,
+    params: &[QueryParam],
+    cap: usize,
+) -> Result<Vec<tokio_postgres::Row>, DbError> {
+    let tx = client.transaction().await?;
+    let portal = tx.bind(stmt, &pg_params(params)).await?;
+    let rows = tx.query_portal(&portal, i32::try_from(cap).unwrap_or(i32::MAX)).await?;
+    drop(portal);
+    tx.commit().await?;
+    Ok(rows)
+}
+
 impl DatabaseConnection for PostgresConnection {
     fn info(&self) -> ConnectionInfo {
         ConnectionInfo {
@@ -59,57 +510,75 @@ impl DatabaseConnection for PostgresConnection {
             db_type: DatabaseType::Postgres,
             version: "PostgreSQL".to_string(),
             connected: true,
+            read_only: self.read_only,
         }
     }
 
-    fn query(&self, sql: &str) -> Result<QueryResult, String> {
+    fn query_limited(&self, sql: &str, params: &[QueryParam], limits: &QueryLimits) -> Result<QueryResult, DbError> {
         self.rt.block_on(async {
             let start = std::time::Instant::now();
-            let rows = self.client
-                .query(sql, &[])
-                .await
-                .map_err(|e| format!("Query error: {}", e))?;
-
-            if rows.is_empty() {
-                return Ok(QueryResult {
-                    columns: vec![],
-                    rows: vec![],
-                    row_count: 0,
-                    execution_time_ms: start.elapsed().as_millis() as u64,
-                });
-            }
+            let mut client = self.client.lock().await;
+            // A portal needs a transaction of its own, so a limited query first
+            // checks for the user's; the probe is pipelined with the prepare.
+            let probe = async {
+                match limits.max_rows {
+                    Some(_) => in_transaction(&client).await.map(Some),
+                    None => Ok(None),
+                }
+            };
+            let (stmt, in_transaction) = futures_util::join!(client.prepare(sql), probe);
+            let stmt = stmt.map_err(DbError::from)?;
+            let in_transaction = in_transaction?;
 
-            let columns: Vec<String> = rows[0].columns()
+            let columns: Vec<String> = stmt.columns()
                 .iter().map(|c| c.name().to_string()).collect();
 
-            let result_rows: Vec<Vec<Option<String>>> = rows
-                .iter()
-                .map(|row| {
-                    columns.iter()
-                        .enumerate()
-                        .map(|(i, _)| {
-                            row.try_get::<_, Option<String>>(i).ok().flatten()
-                        })
-                        .collect()
-                })
-                .collect();
+            let types: Vec<Type> = stmt.columns().iter().map(|c| c.type_().clone()).collect();
 
-            Ok(QueryResult {
-                columns,
-                rows: result_rows.clone(),
-                row_count: result_rows.len(),
-                execution_time_ms: start.elapsed().as_millis() as u64,
-            })
+            let cap = limits.max_rows.map_or(usize::MAX, |max| max.saturating_add(1));
+            let mut result_rows: Vec<Row> = if in_transaction == Some(false) && !columns.is_empty() {
+                let rows = portal_rows(&mut client, &stmt, params, cap).await?;
+                binary_rows(&client, &rows, &types).await?
+            } else if params.is_empty() {
+                // Inside the user's transaction the rows are streamed and
+                // abandoned at the limit; the server still sends the rest.
+                let mut rows = Vec::new();
+                let stream = client.simple_query_raw(sql).await?;
+                let mut stream = std::pin::pin!(stream);
+                while rows.len() < cap {
+                    match stream.try_next().await? {
+                        Some(SimpleQueryMessage::Row(row)) => rows.push(text_row(&row, &types)),
+                        Some(_) => {}
+                        None => break,
+                    }
+                }
+                rows
+            } else {
+                let mut rows = Vec::new();
+                let stream = client.query_raw(&stmt, pg_params(params)).await?;
+                let mut stream = std::pin::pin!(stream);
+                while rows.len() < cap {
+                    let Some(row) = stream.try_next().await? else { break };
+                    rows.push(row);
+                }
+                binary_rows(&client, &rows, &types).await?
+            };
+
+            let truncated = limits.max_rows.is_some_and(|max| result_rows.len() > max);
+            if let Some(max) = limits.max_rows {
+                result_rows.truncate(max);
+            }
+            Ok(QueryResult::new(columns, result_rows, truncated, start))
         })
     }
 
-    fn execute(&self, sql: &str) -> Result<ExecuteResult, String> {
+    fn execute_with_params(&self, sql: &str, params: &[QueryParam]) -> Result<ExecuteResult, DbError> {
         self.rt.block_on(async {
             let start = std::time::Instant::now();
-            let result = self.client
-                .execute(sql, &[])
+            let result = self.client.lock().await
+                .execute(sql, &pg_params(params))
                 .await
-                .map_err(|e| format!("Execute error: {}", e))?;
+                .map_err(DbError::from)?;
 
             Ok(ExecuteResult {
                 rows_affected: result,
@@ -119,18 +588,42 @@ impl DatabaseConnection for PostgresConnection {
         })
     }
 
-    fn get_tables(&self) -> Result<Vec<TableInfo>, String> {
-        self.query(
-            "SELECT table_schema, table_name \
-             FROM information_schema.tables \
-             WHERE table_type = 'BASE TABLE' \
-               AND table_schema NOT IN ('pg_catalog', 'information_schema') \
-             ORDER BY table_schema, table_name"
+    fn query_bytes(&self, sql: &str, params: &[QueryParam]) -> Result<Option<Vec<u8>>, DbError> {
+        self.rt.block_on(async {
+            let row = self.client.lock().await
+                .query_opt(sql, &pg_params(params))
+                .await
+                .map_err(DbError::from)?
+                .ok_or_else(|| DbError::new(DbErrorKind::NotFound, "No row matches"))?;
+            if let Ok(bytes) = row.try_get::<_, Option<Vec<u8>>>(0) {
+                return Ok(bytes);
+            }
+            Ok(row.try_get::<_, Option<String>>(0)
+                .map_err(DbError::from)?
+                .map(String::into_bytes))
+        })
+    }
+
+    fn get_tables(&self) -> Result<Vec<TableInfo>, DbError> {
+        let schema = self.schema.lock().map_err(|_| "Lock error")?.clone();
+        let (filter, params) = match schema {
+            Some(schema) => ("AND table_schema = $1", vec![QueryParam::Text(schema)]),
+            None => ("AND table_schema NOT IN ('pg_catalog', 'information_schema')", Vec::new()),
+        };
+        self.query_with_params(
+            &format!(
+                "SELECT table_schema, table_name \
+                 FROM information_schema.tables \
+                 WHERE table_type = 'BASE TABLE' {} \
+                 ORDER BY table_schema, table_name",
+                filter
+            ),
+            &params,
         ).map(|r| {
             r.rows.iter()
                 .filter_map(|row| {
-                    let schema = row.get(0)?.clone().unwrap_or_default();
-                    let table_name = row.get(1)?.clone().unwrap_or_default();
+                    let schema = row.first()?.as_ref().and_then(CellValue::as_text).unwrap_or_default().to_string();
+                    let table_name = row.get(1)?.as_ref().and_then(CellValue::as_text).unwrap_or_default().to_string();
                     Some(TableInfo {
                         name: format!("{}.{}", schema, table_name),
                         schema: Some(schema),
@@ -141,7 +634,7 @@ impl DatabaseConnection for PostgresConnection {
         })
     }
 
-    fn get_columns(&self, table: &str) -> Result<Vec<ColumnInfo>, String> {
+    fn get_columns(&self, table: &str) -> Result<Vec<ColumnInfo>, DbError> {
         let (schema_name, table_name) = match table.split_once('.') {
             Some((schema, name)) => (schema.to_string(), name.to_string()),
             None => ("public".to_string(), table.to_string()),
@@ -149,7 +642,7 @@ impl DatabaseConnection for PostgresConnection {
 
         self.rt.block_on(async {
             let start = std::time::Instant::now();
-            let rows = self.client
+            let rows = self.client.lock().await
                 .query(
                     "SELECT column_name::text, data_type::text, is_nullable::text, column_default::text \
                      FROM information_schema.columns \
@@ -158,7 +651,7 @@ impl DatabaseConnection for PostgresConnection {
                     &[&schema_name, &table_name]
                 )
                 .await
-                .map_err(|e| format!("Error: {}", e))?;
+                .map_err(DbError::from)?;
 
             let columns: Vec<ColumnInfo> = rows.iter()
                 .filter_map(|row| {
@@ -177,9 +670,136 @@ impl DatabaseConnection for PostgresConnection {
         })
     }
 
+    fn get_catalog(&self) -> Result<Catalog, DbError> {
+        self.rt.block_on(async {
+            let mut builder = CatalogBuilder::default();
+
+            let columns = self.client.lock().await
+                .query(
+                    "SELECT c.table_schema::text, c.table_name::text, c.column_name::text, c.data_type::text, \
+                            c.is_nullable::text, c.column_default::text, (pk.column_name IS NOT NULL) \
+                     FROM information_schema.columns c \
+                     JOIN information_schema.tables t \
+                       ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+                     LEFT JOIN ( \
+                         SELECT k.table_schema, k.table_name, k.column_name \
+                         FROM information_schema.table_constraints tc \
+                         JOIN information_schema.key_column_usage k \
+                           ON k.constraint_schema = tc.constraint_schema AND k.constraint_name = tc.constraint_name \
+                         WHERE tc.constraint_type = 'PRIMARY KEY' \
+                     ) pk ON pk.table_schema = c.table_schema AND pk.table_name = c.table_name \
+                         AND pk.column_name = c.column_name \
+                     WHERE t.table_type = 'BASE TABLE' \
+                       AND c.table_schema NOT IN ('pg_catalog', 'information_schema') \
+                     ORDER BY c.table_schema, c.table_name, c.ordinal_position",
+                    &[]
+                )
+                .await
+                .map_err(DbError::from)?;
+
+            for row in &columns {
+                let schema: String = row.try_get(0).unwrap_or_default();
+                let table: String = row.try_get(1).unwrap_or_default();
+                builder.add_column(Some(&schema), &table, ColumnInfo {
+                    name: row.try_get(2).unwrap_or_default(),
+                    data_type: row.try_get(3).unwrap_or_default(),
+                    nullable: row.try_get::<_, String>(4).unwrap_or_default() == "YES",
+                    default: row.try_get(5).ok().flatten(),
+                    primary_key: row.try_get(6).unwrap_or(false),
+                });
+            }
+
+            let fks = self.client.lock().await
+                .query(
+                    "SELECT ns.nspname::text, cl.relname::text, con.conname::text, \
+                            ARRAY(SELECT a.attname::text FROM unnest(con.conkey) WITH ORDINALITY k(n, ord) \
+                                  JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.n \
+                                  ORDER BY k.ord), \
+                            fns.nspname::text, fcl.relname::text, \
+                            ARRAY(SELECT a.attname::text FROM unnest(con.confkey) WITH ORDINALITY k(n, ord) \
+                                  JOIN pg_attribute a ON a.attrelid = con.confrelid AND a.attnum = k.n \
+                                  ORDER BY k.ord) \
+                     FROM pg_constraint con \
+                     JOIN pg_class cl ON cl.oid = con.conrelid \
+                     JOIN pg_namespace ns ON ns.oid = cl.relnamespace \
+                     JOIN pg_class fcl ON fcl.oid = con.confrelid \
+                     JOIN pg_namespace fns ON fns.oid = fcl.relnamespace \
+                     WHERE con.contype = 'f' \
+                       AND ns.nspname NOT IN ('pg_catalog', 'information_schema') \
+                     ORDER BY ns.nspname, cl.relname, con.conname",
+                    &[]
+                )
+                .await
+                .map_err(DbError::from)?;
+
+            for row in &fks {
+                let schema: String = row.try_get(0).unwrap_or_default();
+                let table: String = row.try_get(1).unwrap_or_default();
+                builder.add_foreign_key(Some(&schema), &table, ForeignKeyInfo {
+                    name: row.try_get(2).ok(),
+                    columns: row.try_get(3).unwrap_or_default(),
+                    referenced_schema: row.try_get(4).ok(),
+                    referenced_table: row.try_get(5).unwrap_or_default(),
+                    referenced_columns: row.try_get(6).unwrap_or_default(),
+                });
+            }
+
+            Ok(builder.build())
+        })
+    }
+
+    fn list_databases(&self) -> Result<DatabaseList, DbError> {
+        let texts = |result: QueryResult| -> Vec<String> {
+            result.rows.iter().filter_map(|row| cell_text(row, 0)).collect()
+        };
+        let databases = texts(self.query(
+            "SELECT datname::text FROM pg_database WHERE datallowconn AND NOT datistemplate ORDER BY datname"
+        )?);
+        let schemas = texts(self.query(
+            "SELECT nspname::text FROM pg_namespace \
+             WHERE nspname NOT IN ('pg_catalog', 'information_schema') AND nspname NOT LIKE 'pg\\_%' \
+             ORDER BY nspname"
+        )?);
+        let current = self.query("SELECT current_database()::text, current_schema()::text")?;
+        let current = current.rows.first();
+        Ok(DatabaseList {
+            databases,
+            current_database: current.and_then(|row| cell_text(row, 0)),
+            schemas,
+            current_schema: current.and_then(|row| cell_text(row, 1)),
+        })
+    }
+
+    fn use_database(&self, database: Option<&str>, schema: Option<&str>) -> Result<(), DbError> {
+        if database.is_some() {
+            return Err(DbError::invalid_input("PostgreSQL needs a new connection to change database"));
+        }
+        let Some(schema) = schema else { return Ok(()) };
+        // `public` stays on the path for the extensions installed there.
+        let path = match schema {
+            "public" => quote_ident(DatabaseType::Postgres, schema),
+            _ => format!("{}, public", quote_ident(DatabaseType::Postgres, schema)),
+        };
+        self.execute(&format!("SET search_path TO {}", path))?;
+        *self.schema.lock().map_err(|_| "Lock error")? = Some(schema.to_string());
+        Ok(())
+    }
+
+    fn take_notifications(&self) -> Option<NotificationReceiver> {
+        self.notifications.lock().ok()?.take()
+    }
+
     fn is_alive(&self) -> bool {
         self.rt.block_on(async {
-            self.client.simple_query("SELECT 1").await.is_ok()
+            let client = self.client.lock().await;
+            if client.is_closed() {
+                return false;
+            }
+            // A half-open socket would otherwise hang the ping until TCP gives up.
+            tokio::time::timeout(std::time::Duration::from_secs(5), client.simple_query("SELECT 1"))
+                .await
+                .map(|r| r.is_ok())
+                .unwrap_or(false)
         })
     }
 
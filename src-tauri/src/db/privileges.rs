@@ -0,0 +1,240 @@
+use super::connection::DatabaseConnection;
+use super::types::*;
+
+const PG_ROLES: &str = "\
+    SELECT r.rolname::text, r.rolcanlogin::text, r.rolsuper::text, \
+           COALESCE(string_agg(g.rolname::text, E'\\n' ORDER BY g.rolname), '') \
+    FROM pg_roles r \
+    LEFT JOIN pg_auth_members m ON m.member = r.oid \
+    LEFT JOIN pg_roles g ON g.oid = m.roleid \
+    WHERE r.rolname !~ '^pg_' \
+    GROUP BY r.rolname, r.rolcanlogin, r.rolsuper \
+    ORDER BY r.rolname";
+
+const PG_TABLE_GRANTS: &str = "\
+    SELECT grantee::text, table_schema::text, table_name::text, privilege_type::text, is_grantable::text \
+    FROM information_schema.role_table_grants \
+    WHERE table_schema NOT IN ('pg_catalog', 'information_schema') \
+    ORDER BY grantee, table_schema, table_name, privilege_type";
+
+// Table-level grants are repeated per column in the column views; only keep
+// privileges granted on the column itself.
+const PG_COLUMN_GRANTS: &str = "\
+    SELECT c.grantee::text, c.table_schema::text, c.table_name::text, c.column_name::text, \
+           c.privilege_type::text, c.is_grantable::text \
+    FROM information_schema.role_column_grants c \
+    WHERE c.table_schema NOT IN ('pg_catalog', 'information_schema') \
+      AND NOT EXISTS ( \
+          SELECT 1 FROM information_schema.role_table_grants t \
+          WHERE t.grantee = c.grantee AND t.table_schema = c.table_schema \
+            AND t.table_name = c.table_name AND t.privilege_type = c.privilege_type) \
+    ORDER BY c.grantee, c.table_schema, c.table_name, c.column_name, c.privilege_type";
+
+pub fn report(conn: &dyn DatabaseConnection) -> Result<PrivilegeReport, DbError> {
+    match conn.info().db_type {
+        DatabaseType::Postgres => postgres_report(conn),
+        DatabaseType::Mysql => mysql_report(conn),
+        other => Err(DbError::invalid_input(format!("Privilege introspection is not supported for {:?}", other))),
+    }
+}
+
+fn is_yes(value: Option<String>) -> bool {
+    matches!(value.as_deref(), Some("true" | "YES" | "Y"))
+}
+
+fn postgres_report(conn: &dyn DatabaseConnection) -> Result<PrivilegeReport, DbError> {
+    let roles = conn.query(PG_ROLES)?.rows.iter().map(|row| RoleInfo {
+        name: cell_text(row, 0).unwrap_or_default(),
+        can_login: is_yes(cell_text(row, 1)),
+        superuser: is_yes(cell_text(row, 2)),
+        member_of: cell_text(row, 3)
+            .map(|m| m.lines().map(str::to_string).collect())
+            .unwrap_or_default(),
+    }).collect();
+
+    let mut grants: Vec<PrivilegeGrant> = conn.query(PG_TABLE_GRANTS)?.rows.iter().map(|row| PrivilegeGrant {
+        grantee: cell_text(row, 0).unwrap_or_default(),
+        level: GrantLevel::Table,
+        schema: cell_text(row, 1),
+        object: cell_text(row, 2),
+        column: None,
+        privilege: cell_text(row, 3).unwrap_or_default(),
+        grantable: is_yes(cell_text(row, 4)),
+    }).collect();
+    grants.extend(conn.query(PG_COLUMN_GRANTS)?.rows.iter().map(|row| PrivilegeGrant {
+        grantee: cell_text(row, 0).unwrap_or_default(),
+        level: GrantLevel::Column,
+        schema: cell_text(row, 1),
+        object: cell_text(row, 2),
+        column: cell_text(row, 3),
+        privilege: cell_text(row, 4).unwrap_or_default(),
+        grantable: is_yes(cell_text(row, 5)),
+    }));
+
+    Ok(PrivilegeReport { roles, grants, skipped: Vec::new() })
+}
+
+fn mysql_account(user: &str, host: &str) -> String {
+    format!("'{}'@'{}'", user.replace('\'', "''"), host.replace('\'', "''"))
+}
+
+fn mysql_report(conn: &dyn DatabaseConnection) -> Result<PrivilegeReport, DbError> {
+    // Reading mysql.user needs SELECT on the mysql schema.
+    let accounts = conn.query("SELECT User, Host, Super_priv FROM mysql.user ORDER BY User, Host")
+        .map_err(|e| DbError { message: format!("Cannot read mysql.user: {}", e), ..e })?;
+    let mut report = PrivilegeReport { roles: Vec::new(), grants: Vec::new(), skipped: Vec::new() };
+
+    for row in &accounts.rows {
+        let (user, host) = (cell_text(row, 0).unwrap_or_default(), cell_text(row, 1).unwrap_or_default());
+        let account = mysql_account(&user, &host);
+        let mut role = RoleInfo {
+            name: account.clone(),
+            can_login: true,
+            superuser: is_yes(cell_text(row, 2)),
+            member_of: Vec::new(),
+        };
+        match conn.query(&format!("SHOW GRANTS FOR {}", account)) {
+            Ok(result) => {
+                for line in result.rows.iter().filter_map(|r| cell_text(r, 0)) {
+                    match parse_grant(&line) {
+                        Some(ParsedGrant::Roles(roles)) => role.member_of.extend(roles),
+                        Some(ParsedGrant::Privileges(privileges)) => {
+                            if privileges.iter().any(|p| p.level == GrantLevel::Global && p.privilege == "ALL PRIVILEGES") {
+                                role.superuser = true;
+                            }
+                            report.grants.extend(privileges.into_iter().map(|mut p| {
+                                p.grantee = account.clone();
+                                p
+                            }));
+                        }
+                        None => {}
+                    }
+                }
+            }
+            Err(e) => report.skipped.push(format!("{}: {}", account, e)),
+        }
+        report.roles.push(role);
+    }
+
+    // MySQL 8 roles are locked accounts; mark them so they do not read as users.
+    if let Ok(locked) = conn.query("SELECT User, Host FROM mysql.user WHERE account_locked = 'Y'") {
+        let locked: Vec<String> = locked.rows.iter()
+            .map(|r| mysql_account(&cell_text(r, 0).unwrap_or_default(), &cell_text(r, 1).unwrap_or_default()))
+            .collect();
+        for role in &mut report.roles {
+            role.can_login = !locked.contains(&role.name);
+        }
+    }
+
+    Ok(report)
+}
+
+enum ParsedGrant {
+    /// `GRANT role TO account`: membership.
+    Roles(Vec<String>),
+    /// `GRANT privileges ON object TO account`; grantee is filled in by the caller.
+    Privileges(Vec<PrivilegeGrant>),
+}
+
+/// Byte offset of `needle` outside backticks, quotes and parentheses.
+fn find_top_level(s: &str, needle: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '`' | '\'' | '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, _) if depth == 0 && s[i..].starts_with(needle) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = s;
+    while let Some(i) = find_top_level(rest, ",") {
+        parts.push(rest[..i].trim());
+        rest = &rest[i + 1..];
+    }
+    parts.push(rest.trim());
+    parts
+}
+
+fn unquote(ident: &str) -> String {
+    let ident = ident.trim();
+    match ident.strip_prefix('`').and_then(|s| s.strip_suffix('`')) {
+        Some(inner) => inner.replace("``", "`"),
+        None => ident.to_string(),
+    }
+}
+
+/// `` `user`@`host` `` as the `'user'@'host'` form used for account names.
+fn normalize_account(account: &str) -> String {
+    match find_top_level(account, "@") {
+        Some(i) => mysql_account(&unquote(&account[..i]), &unquote(&account[i + 1..])),
+        None => unquote(account),
+    }
+}
+
+/// Parses one line of `SHOW GRANTS` output.
+fn parse_grant(line: &str) -> Option<ParsedGrant> {
+    let body = line.trim().strip_prefix("GRANT ")?;
+    let to = find_top_level(body, " TO ")?;
+    let grantable = body.ends_with("WITH GRANT OPTION");
+
+    let Some(on) = find_top_level(&body[..to], " ON ") else {
+        let roles = split_top_level(&body[..to]).into_iter().map(normalize_account).collect();
+        return Some(ParsedGrant::Roles(roles));
+    };
+
+    let mut object = body[on + 4..to].trim();
+    let mut routine = false;
+    for prefix in ["PROCEDURE ", "FUNCTION ", "TABLE "] {
+        if let Some(rest) = object.strip_prefix(prefix) {
+            routine = prefix != "TABLE ";
+            object = rest;
+        }
+    }
+    let (schema, name) = match find_top_level(object, ".") {
+        Some(i) => (unquote(&object[..i]), unquote(&object[i + 1..])),
+        None => (String::new(), unquote(object)),
+    };
+    let (level, schema, name) = match (schema.as_str(), name.as_str()) {
+        ("*", "*") => (GrantLevel::Global, None, None),
+        (_, "*") => (GrantLevel::Database, Some(schema), None),
+        _ if routine => (GrantLevel::Routine, Some(schema), Some(name)),
+        _ => (GrantLevel::Table, Some(schema), Some(name)),
+    };
+
+    let mut grants = Vec::new();
+    for privilege in split_top_level(&body[..on]) {
+        // USAGE is the placeholder for "no privileges".
+        if privilege.eq_ignore_ascii_case("USAGE") {
+            continue;
+        }
+        let grant = |privilege: &str, level, column| PrivilegeGrant {
+            grantee: String::new(),
+            level,
+            schema: schema.clone(),
+            object: name.clone(),
+            column,
+            privilege: privilege.to_string(),
+            grantable,
+        };
+        match privilege.find('(') {
+            Some(open) => {
+                let columns = privilege[open + 1..].trim_end_matches(')');
+                for column in split_top_level(columns) {
+                    grants.push(grant(privilege[..open].trim(), GrantLevel::Column, Some(unquote(column))));
+                }
+            }
+            None => grants.push(grant(privilege, level, None)),
+        }
+    }
+    Some(ParsedGrant::Privileges(grants))
+}
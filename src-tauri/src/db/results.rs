@@ -0,0 +1,73 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use super::types::{CellValue, QueryResult};
+
+/// How many recent results keep their truncated cells around.
+const KEPT_RESULTS: usize = 8;
+/// Upper bound on full cell values retained for a single result.
+const MAX_RETAINED_BYTES: usize = 64 * 1024 * 1024;
+
+struct Retained {
+    result_id: String,
+    cells: HashMap<(usize, usize), String>,
+}
+
+/// Full values of cells cut by the cell size limit, for `db_get_cell`.
+#[derive(Default)]
+pub struct ResultCache {
+    results: Mutex<VecDeque<Retained>>,
+}
+
+impl ResultCache {
+    /// Shortens cells longer than `max_cell_bytes` in place and keeps their
+    /// full values under a new `result_id`.
+    pub fn apply_cell_limit(&self, result: &mut QueryResult, max_cell_bytes: Option<usize>) {
+        let Some(max) = max_cell_bytes else { return };
+
+        let mut cells = HashMap::new();
+        let mut retained_bytes = 0;
+        for (r, row) in result.rows.iter_mut().enumerate() {
+            for (c, cell) in row.iter_mut().enumerate() {
+                let Some(CellValue::Text(value)) = cell else { continue };
+                if value.len() <= max {
+                    continue;
+                }
+                let cut = floor_char_boundary(value, max);
+                let full = std::mem::replace(value, value[..cut].to_string());
+                result.truncated_cells += 1;
+                if retained_bytes + full.len() <= MAX_RETAINED_BYTES {
+                    retained_bytes += full.len();
+                    cells.insert((r, c), full);
+                }
+            }
+        }
+
+        if cells.is_empty() {
+            return;
+        }
+        let result_id = uuid::Uuid::new_v4().to_string();
+        result.result_id = Some(result_id.clone());
+        if let Ok(mut results) = self.results.lock() {
+            if results.len() >= KEPT_RESULTS {
+                results.pop_front();
+            }
+            results.push_back(Retained { result_id, cells });
+        }
+    }
+
+    pub fn get_cell(&self, result_id: &str, row: usize, column: usize) -> Option<String> {
+        let results = self.results.lock().ok()?;
+        results.iter()
+            .find(|r| r.result_id == result_id)?
+            .cells.get(&(row, column))
+            .cloned()
+    }
+}
+
+fn floor_char_boundary(s: &str, max: usize) -> usize {
+    let mut cut = max.min(s.len());
+    while !s.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    cut
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde_json::Value;
+use super::dialect::placeholder;
+use super::types::*;
+
+/// SQL with every `{{variable}}` swapped for a driver placeholder, plus the values to bind.
+pub struct ResolvedQuery {
+    pub sql: String,
+    pub params: Vec<QueryParam>,
+}
+
+enum Segment<'a> {
+    Sql(&'a str),
+    Variable(&'a str),
+}
+
+/// Splits a template into SQL text and `{{name}}` references.
+/// Braces inside string literals, quoted identifiers and comments are left alone.
+fn segments(sql: &str) -> Result<Vec<Segment<'_>>, String> {
+    let bytes = sql.as_bytes();
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            b'{' if bytes.get(i + 1) == Some(&b'{') => {
+                let close = sql[i + 2..].find("}}")
+                    .ok_or("Unclosed {{ in saved query")?;
+                let name = sql[i + 2..i + 2 + close].trim();
+                if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(format!("Invalid variable name '{}'", name));
+                }
+                out.push(Segment::Sql(&sql[start..i]));
+                out.push(Segment::Variable(name));
+                i += close + 4;
+                start = i;
+            }
+            _ => i += 1,
+        }
+    }
+
+    out.push(Segment::Sql(&sql[start.min(sql.len())..]));
+    Ok(out)
+}
+
+/// Names referenced by a template, in first-use order.
+pub fn template_variables(sql: &str) -> Result<Vec<String>, String> {
+    let mut names: Vec<String> = Vec::new();
+    for segment in segments(sql)? {
+        if let Segment::Variable(name) = segment {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    Ok(names)
+}
+
+pub fn validate(query: &SavedQuery) -> Result<(), String> {
+    if query.name.trim().is_empty() {
+        return Err("Saved query name is required".into());
+    }
+    for name in template_variables(&query.sql)? {
+        if !query.variables.iter().any(|v| v.name == name) {
+            return Err(format!("Variable '{}' is used but not declared", name));
+        }
+    }
+    for var in &query.variables {
+        if let Some(default) = &var.default {
+            coerce(var, default)?;
+        }
+    }
+    Ok(())
+}
+
+fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() >= 10
+        && b[4] == b'-'
+        && b[7] == b'-'
+        && b[..10].iter().enumerate().all(|(i, c)| i == 4 || i == 7 || c.is_ascii_digit())
+}
+
+fn coerce(var: &QueryVariable, value: &Value) -> Result<QueryParam, String> {
+    let invalid = || format!("Variable '{}' expects a {:?} value", var.name, var.var_type);
+
+    if value.is_null() {
+        return Ok(QueryParam::Null);
+    }
+
+    match var.var_type {
+        VariableType::Text => Ok(QueryParam::Text(match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })),
+        VariableType::Date => match value {
+            Value::String(s) if is_date(s) => Ok(QueryParam::Text(s.clone())),
+            _ => Err(invalid()),
+        },
+        VariableType::Integer => match value {
+            Value::Number(n) => n.as_i64().map(QueryParam::Int).ok_or_else(invalid),
+            Value::String(s) => s.trim().parse().map(QueryParam::Int).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        },
+        VariableType::Number => match value {
+            Value::Number(n) => n.as_f64().map(QueryParam::Float).ok_or_else(invalid),
+            Value::String(s) => s.trim().parse().map(QueryParam::Float).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        },
+        VariableType::Boolean => match value {
+            Value::Bool(b) => Ok(QueryParam::Bool(*b)),
+            Value::String(s) => match s.trim().to_lowercase().as_str() {
+                "true" | "1" | "yes" => Ok(QueryParam::Bool(true)),
+                "false" | "0" | "no" => Ok(QueryParam::Bool(false)),
+                _ => Err(invalid()),
+            },
+            _ => Err(invalid()),
+        },
+    }
+}
+
+/// Turns a saved query into placeholder SQL for `db_type`, taking each value from
+/// `values` or the variable's declared default.
+pub fn resolve(
+    query: &SavedQuery,
+    values: &HashMap<String, Value>,
+    db_type: DatabaseType,
+) -> Result<ResolvedQuery, String> {
+    let mut sql = String::with_capacity(query.sql.len());
+    let mut params = Vec::new();
+
+    for segment in segments(&query.sql)? {
+        match segment {
+            Segment::Sql(text) => sql.push_str(text),
+            Segment::Variable(name) => {
+                let var = query.variables.iter()
+                    .find(|v| v.name == name)
+                    .ok_or_else(|| format!("Variable '{}' is used but not declared", name))?;
+                let value = values.get(name)
+                    .or(var.default.as_ref())
+                    .ok_or_else(|| format!("No value for variable '{}'", name))?;
+                params.push(coerce(var, value)?);
+                sql.push_str(&placeholder(db_type, params.len()));
+            }
+        }
+    }
+
+    Ok(ResolvedQuery { sql, params })
+}
+
+// ── Storage (nexus.db `saved_queries`) ──────────────────────────────────────
+
+const COLUMNS: &str =
+    "id, name, connection_id, folder, tags, sql, variables, created_at, updated_at";
+
+fn from_row(row: &Row) -> rusqlite::Result<SavedQuery> {
+    let tags: String = row.get(4)?;
+    let variables: String = row.get(6)?;
+    Ok(SavedQuery {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        connection_id: row.get(2)?,
+        folder: row.get(3)?,
+        tags: serde_json::from_str(&tags).unwrap_or_default(),
+        sql: row.get(5)?,
+        variables: serde_json::from_str(&variables).unwrap_or_default(),
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+/// Global queries plus those bound to `connection_id`; everything when it is `None`.
+pub fn list(conn: &Connection, connection_id: Option<&str>) -> rusqlite::Result<Vec<SavedQuery>> {
+    let sql = format!(
+        "SELECT {} FROM saved_queries \
+         WHERE ?1 IS NULL OR connection_id IS NULL OR connection_id = ?1 \
+         ORDER BY folder, name",
+        COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params![connection_id], from_row)?;
+    rows.collect()
+}
+
+pub fn get(conn: &Connection, id: &str) -> rusqlite::Result<Option<SavedQuery>> {
+    let sql = format!("SELECT {} FROM saved_queries WHERE id = ?1", COLUMNS);
+    conn.query_row(&sql, params![id], from_row).optional()
+}
+
+pub fn save(conn: &Connection, query: &SavedQuery) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO saved_queries (id, name, connection_id, folder, tags, sql, variables, created_at, updated_at) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9) \
+         ON CONFLICT(id) DO UPDATE SET \
+           name = excluded.name, connection_id = excluded.connection_id, folder = excluded.folder, \
+           tags = excluded.tags, sql = excluded.sql, variables = excluded.variables, \
+           updated_at = excluded.updated_at",
+        params![
+            query.id,
+            query.name,
+            query.connection_id,
+            query.folder,
+            serde_json::to_string(&query.tags).unwrap_or_else(|_| "[]".into()),
+            query.sql,
+            serde_json::to_string(&query.variables).unwrap_or_else(|_| "[]".into()),
+            query.created_at,
+            query.updated_at,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM saved_queries WHERE id = ?1", params![id])?;
+    Ok(())
+}
@@ -0,0 +1,354 @@
+use std::time::Duration;
+use chrono::{Local, TimeZone};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Manager};
+use super::connection::create_connection;
+use super::cron::CronSchedule;
+use super::masking;
+use super::saved_queries;
+use super::store::{now_millis, AppStore};
+use super::types::*;
+use super::validate_query_sql;
+use super::vault::Vault;
+
+pub const SCHEDULE_RUN_EVENT: &str = "db:schedule-run";
+pub const SCHEDULE_ALERT_EVENT: &str = "db:schedule-alert";
+
+const TICK: Duration = Duration::from_secs(30);
+/// Rows kept in each snapshot.
+const SNAPSHOT_ROWS: usize = 100;
+/// Snapshots kept per schedule; older ones are pruned after each run.
+const KEEP_SNAPSHOTS: i64 = 200;
+/// The UI's limit on card titles.
+const MAX_CARD_TITLE: usize = 500;
+
+pub fn validate(schedule: &ScheduledQuery) -> Result<(), String> {
+    if schedule.name.trim().is_empty() {
+        return Err("Scheduled query name is required".into());
+    }
+    if schedule.connection_id.is_empty() {
+        return Err("Scheduled query needs a saved connection".into());
+    }
+    CronSchedule::parse(&schedule.schedule)?;
+    Ok(())
+}
+
+// ── Rules ───────────────────────────────────────────────────────────────────
+
+fn holds(op: Comparison, a: f64, b: f64) -> bool {
+    match op {
+        Comparison::Gt => a > b,
+        Comparison::Ge => a >= b,
+        Comparison::Lt => a < b,
+        Comparison::Le => a <= b,
+        Comparison::Eq => a == b,
+        Comparison::Ne => a != b,
+    }
+}
+
+fn symbol(op: Comparison) -> &'static str {
+    match op {
+        Comparison::Gt => ">",
+        Comparison::Ge => ">=",
+        Comparison::Lt => "<",
+        Comparison::Le => "<=",
+        Comparison::Eq => "=",
+        Comparison::Ne => "!=",
+    }
+}
+
+/// Description of `rule` when it fires for `result`; `previous` is the hash
+/// of the last successful run.
+fn evaluate(rule: &AlertRule, result: &QueryResult, hash: &str, previous: Option<&str>) -> Option<String> {
+    match rule {
+        AlertRule::RowCount { op, value } => {
+            let count = result.rows.len() as f64;
+            holds(*op, count, *value).then(|| format!("row count {} {} (is {})", symbol(*op), value, count))
+        }
+        AlertRule::Value { column, op, value } => {
+            let i = match column {
+                Some(name) => result.columns.iter().position(|c| c == name)?,
+                None => 0,
+            };
+            let name = result.columns.get(i)?;
+            let cell = result.rows.first()?.get(i)?.as_ref()?.as_text()?.trim().parse::<f64>().ok()?;
+            holds(*op, cell, *value).then(|| format!("{} {} {} (is {})", name, symbol(*op), value, cell))
+        }
+        AlertRule::Changed => previous.is_some_and(|p| p != hash).then(|| "result changed".to_string()),
+    }
+}
+
+fn result_hash(result: &QueryResult) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_string(&(&result.columns, &result.rows)).unwrap_or_default().as_bytes());
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// ── Runs ────────────────────────────────────────────────────────────────────
+
+/// Runs the saved query on its own connection to the saved connection, so
+/// runs neither need nor disturb the user's open connections. Returns the
+/// unmasked result and its lineage, for masking.
+fn execute(app: &AppHandle, schedule: &ScheduledQuery) -> Result<(QueryResult, masking::Lineage), DbError> {
+    let query = app.state::<AppStore>().with_conn(|c| saved_queries::get(c, &schedule.query_id))?
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, "Saved query not found"))?;
+    let config = app.state::<Vault>().get(&schedule.connection_id)?;
+    let conn = create_connection(&config)?;
+    let db_type = conn.info().db_type;
+    let result = saved_queries::resolve(&query, &schedule.values, db_type)
+        .map_err(DbError::invalid_input)
+        .and_then(|resolved| {
+            validate_query_sql(&resolved.sql)?;
+            let result = conn.query_limited(&resolved.sql, &resolved.params, &QueryLimits::default())?;
+            Ok((result, masking::Lineage::of_sql(&resolved.sql, db_type)))
+        });
+    conn.close();
+    result
+}
+
+/// Runs `schedule` once: stores a snapshot, evaluates its rules, adds a card
+/// to its board when one fires, and emits `db:schedule-run` (and
+/// `db:schedule-alert` when rules fired).
+pub fn run(app: &AppHandle, schedule: &ScheduledQuery) -> Result<QuerySnapshot, String> {
+    let store = app.state::<AppStore>();
+    let run_at = now_millis();
+    store.with_conn(|c| mark_run(c, &schedule.id, run_at))?;
+    let previous = store.with_conn(|c| last_hash(c, &schedule.id))?;
+
+    let mut snapshot = QuerySnapshot {
+        id: 0,
+        schedule_id: schedule.id.clone(),
+        run_at,
+        row_count: None,
+        result: None,
+        error: None,
+        fired: Vec::new(),
+        card_id: None,
+    };
+    let mut hash = None;
+    match execute(app, schedule) {
+        Ok((mut result, lineage)) => {
+            let digest = result_hash(&result);
+            snapshot.fired = schedule.rules.iter()
+                .filter_map(|rule| evaluate(rule, &result, &digest, previous.as_deref()))
+                .collect();
+            snapshot.row_count = Some(result.rows.len() as u64);
+
+            let rules = store.with_conn(|c| masking::list(c, &schedule.connection_id))?;
+            masking::apply(&rules, &mut result, &lineage);
+            if result.rows.len() > SNAPSHOT_ROWS {
+                result.rows.truncate(SNAPSHOT_ROWS);
+                result.truncated = true;
+            }
+            snapshot.result = Some(result);
+            hash = Some(digest);
+        }
+        Err(e) => snapshot.error = Some(e.message),
+    }
+
+    if let (Some(board_id), false) = (&schedule.board_id, snapshot.fired.is_empty()) {
+        let title: String = format!("{}: {}", schedule.name, snapshot.fired.join("; "))
+            .chars()
+            .take(MAX_CARD_TITLE)
+            .collect();
+        let at = Local.timestamp_millis_opt(run_at).single()
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let description = format!("Scheduled query \"{}\" at {}:\n- {}", schedule.name, at, snapshot.fired.join("\n- "));
+        snapshot.card_id = store.with_conn(|c| create_card(c, board_id, &title, &description))?;
+    }
+    snapshot.id = store.with_conn(|c| insert_snapshot(c, &snapshot, hash.as_deref()))?;
+
+    let event = ScheduleRunEvent {
+        schedule_id: schedule.id.clone(),
+        name: schedule.name.clone(),
+        snapshot_id: snapshot.id,
+        row_count: snapshot.row_count,
+        error: snapshot.error.clone(),
+        fired: snapshot.fired.clone(),
+        card_id: snapshot.card_id.clone(),
+    };
+    if !event.fired.is_empty() {
+        let _ = app.emit(SCHEDULE_ALERT_EVENT, event.clone());
+    }
+    let _ = app.emit(SCHEDULE_RUN_EVENT, event);
+    Ok(snapshot)
+}
+
+/// Starts the background thread that runs due schedules, one at a time. A
+/// schedule that came due while the app was closed runs once on the first tick.
+pub fn spawn_scheduler(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(TICK);
+        run_due(&app);
+    });
+}
+
+fn run_due(app: &AppHandle) {
+    let Ok(schedules) = app.state::<AppStore>().with_conn(list) else { return };
+    let now = Local::now();
+    for schedule in schedules.iter().filter(|s| s.enabled) {
+        // Editing a schedule restarts it from the time of the edit.
+        let since = schedule.last_run_at.unwrap_or(0).max(schedule.updated_at);
+        let due = Local.timestamp_millis_opt(since).single()
+            .zip(CronSchedule::parse(&schedule.schedule).ok())
+            .and_then(|(since, cron)| cron.next_after(since))
+            .is_some_and(|next| next <= now);
+        if due {
+            if let Err(e) = run(app, schedule) {
+                log::warn!("Scheduled query {} could not run: {}", schedule.name, e);
+            }
+        }
+    }
+}
+
+// ── Storage (nexus.db `scheduled_queries`, `query_snapshots`) ────────────────
+
+const COLUMNS: &str = "id, name, query_id, connection_id, schedule, variable_values, rules, board_id, \
+                       enabled, last_run_at, created_at, updated_at";
+
+fn from_row(row: &Row) -> rusqlite::Result<ScheduledQuery> {
+    let values: String = row.get(5)?;
+    let rules: String = row.get(6)?;
+    Ok(ScheduledQuery {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        query_id: row.get(2)?,
+        connection_id: row.get(3)?,
+        schedule: row.get(4)?,
+        values: serde_json::from_str(&values).unwrap_or_default(),
+        rules: serde_json::from_str(&rules).unwrap_or_default(),
+        board_id: row.get(7)?,
+        enabled: row.get(8)?,
+        last_run_at: row.get(9)?,
+        created_at: row.get(10)?,
+        updated_at: row.get(11)?,
+    })
+}
+
+pub fn list(conn: &Connection) -> rusqlite::Result<Vec<ScheduledQuery>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM scheduled_queries ORDER BY name", COLUMNS))?;
+    let rows = stmt.query_map([], from_row)?;
+    rows.collect()
+}
+
+pub fn get(conn: &Connection, id: &str) -> rusqlite::Result<Option<ScheduledQuery>> {
+    let sql = format!("SELECT {} FROM scheduled_queries WHERE id = ?1", COLUMNS);
+    conn.query_row(&sql, params![id], from_row).optional()
+}
+
+/// Inserts or updates by id; `last_run_at` is only ever set by runs.
+pub fn save(conn: &Connection, schedule: &ScheduledQuery) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO scheduled_queries (id, name, query_id, connection_id, schedule, variable_values, rules, board_id, \
+                                        enabled, created_at, updated_at) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11) \
+         ON CONFLICT(id) DO UPDATE SET \
+           name = excluded.name, query_id = excluded.query_id, connection_id = excluded.connection_id, \
+           schedule = excluded.schedule, variable_values = excluded.variable_values, rules = excluded.rules, \
+           board_id = excluded.board_id, enabled = excluded.enabled, updated_at = excluded.updated_at",
+        params![
+            schedule.id,
+            schedule.name,
+            schedule.query_id,
+            schedule.connection_id,
+            schedule.schedule,
+            serde_json::to_string(&schedule.values).unwrap_or_else(|_| "{}".into()),
+            serde_json::to_string(&schedule.rules).unwrap_or_else(|_| "[]".into()),
+            schedule.board_id,
+            schedule.enabled,
+            schedule.created_at,
+            schedule.updated_at,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM query_snapshots WHERE schedule_id = ?1", params![id])?;
+    conn.execute("DELETE FROM scheduled_queries WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+fn mark_run(conn: &Connection, id: &str, at: i64) -> rusqlite::Result<()> {
+    conn.execute("UPDATE scheduled_queries SET last_run_at = ?2 WHERE id = ?1", params![id, at])?;
+    Ok(())
+}
+
+fn last_hash(conn: &Connection, schedule_id: &str) -> rusqlite::Result<Option<String>> {
+    conn.query_row(
+        "SELECT result_hash FROM query_snapshots \
+         WHERE schedule_id = ?1 AND error IS NULL ORDER BY run_at DESC, id DESC LIMIT 1",
+        params![schedule_id],
+        |row| row.get(0),
+    ).optional().map(Option::flatten)
+}
+
+fn snapshot_from_row(row: &Row) -> rusqlite::Result<QuerySnapshot> {
+    let result: Option<String> = row.get(4)?;
+    let fired: String = row.get(6)?;
+    Ok(QuerySnapshot {
+        id: row.get(0)?,
+        schedule_id: row.get(1)?,
+        run_at: row.get(2)?,
+        row_count: row.get::<_, Option<i64>>(3)?.map(|n| n as u64),
+        result: result.and_then(|r| serde_json::from_str(&r).ok()),
+        error: row.get(5)?,
+        fired: serde_json::from_str(&fired).unwrap_or_default(),
+        card_id: row.get(7)?,
+    })
+}
+
+/// Newest first.
+pub fn list_snapshots(conn: &Connection, schedule_id: &str, limit: u32) -> rusqlite::Result<Vec<QuerySnapshot>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, schedule_id, run_at, row_count, result, error, fired, card_id FROM query_snapshots \
+         WHERE schedule_id = ?1 ORDER BY run_at DESC, id DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![schedule_id, limit], snapshot_from_row)?;
+    rows.collect()
+}
+
+fn insert_snapshot(conn: &Connection, snapshot: &QuerySnapshot, hash: Option<&str>) -> rusqlite::Result<i64> {
+    conn.execute(
+        "INSERT INTO query_snapshots (schedule_id, run_at, row_count, result, result_hash, error, fired, card_id) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            snapshot.schedule_id,
+            snapshot.run_at,
+            snapshot.row_count.map(|n| n as i64),
+            snapshot.result.as_ref().and_then(|r| serde_json::to_string(r).ok()),
+            hash,
+            snapshot.error,
+            serde_json::to_string(&snapshot.fired).unwrap_or_else(|_| "[]".into()),
+            snapshot.card_id,
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    conn.execute(
+        "DELETE FROM query_snapshots WHERE schedule_id = ?1 AND id NOT IN \
+         (SELECT id FROM query_snapshots WHERE schedule_id = ?1 ORDER BY run_at DESC, id DESC LIMIT ?2)",
+        params![snapshot.schedule_id, KEEP_SNAPSHOTS],
+    )?;
+    Ok(id)
+}
+
+/// Adds a card to the first column of `board_id`, as the board UI would;
+/// `None` when the board has no columns.
+fn create_card(conn: &Connection, board_id: &str, title: &str, description: &str) -> rusqlite::Result<Option<String>> {
+    let column: Option<String> = conn.query_row(
+        "SELECT id FROM columns WHERE board_id = ?1 ORDER BY position LIMIT 1",
+        params![board_id],
+        |row| row.get(0),
+    ).optional()?;
+    let Some(column) = column else { return Ok(None) };
+    let id = uuid::Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO cards (id, column_id, title, description, priority, labels, source_channel, position, created_at, is_done) \
+         VALUES (?1, ?2, ?3, ?4, 'high', '[\"alert\"]', 'scheduler', \
+                 (SELECT COALESCE(MAX(position), -1) + 1 FROM cards WHERE column_id = ?2), ?5, 0)",
+        params![id, column, title, description, now_millis()],
+    )?;
+    Ok(Some(id))
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter};
+use super::connection::DatabaseConnection;
+use super::dialect::{placeholder, quote_ident, quote_table};
+use super::jobs::CancelToken;
+use super::masking;
+use super::types::*;
+
+pub const SEARCH_PROGRESS_EVENT: &str = "db:search-progress";
+
+const DEFAULT_HITS_PER_TABLE: u32 = 20;
+
+fn is_text_type(data_type: &str) -> bool {
+    let t = data_type.to_lowercase();
+    ["char", "text", "string", "clob", "uuid", "json", "enum", "citext", "name"]
+        .iter()
+        .any(|k| t.contains(k))
+}
+
+fn is_integer_type(data_type: &str) -> bool {
+    let t = data_type.to_lowercase();
+    t.contains("int") && !t.contains("interval") && !t.contains("point")
+}
+
+/// Predicate for one text column, pushed down in the connection's own dialect.
+fn text_predicate(db_type: DatabaseType, column: &str, index: usize, options: &SearchOptions) -> String {
+    // Casting lets non-text types such as uuid or json use text operators.
+    let col = match db_type {
+        DatabaseType::Postgres | DatabaseType::Duckdb => format!("{}::text", quote_ident(db_type, column)),
+        DatabaseType::Sqlite | DatabaseType::Mysql => quote_ident(db_type, column),
+    };
+    let p = placeholder(db_type, index);
+    match (options.mode, db_type, options.case_sensitive) {
+        (SearchMode::Exact, _, true) => format!("{} = {}", col, p),
+        (SearchMode::Exact, _, false) => format!("LOWER({}) = LOWER({})", col, p),
+        (SearchMode::FullText, DatabaseType::Postgres, _) => {
+            format!("to_tsvector('simple', {}) @@ plainto_tsquery('simple', {})", col, p)
+        }
+        (_, DatabaseType::Postgres | DatabaseType::Duckdb, false) => format!("{} ILIKE {}", col, p),
+        (_, DatabaseType::Postgres | DatabaseType::Duckdb, true) => format!("{} LIKE {}", col, p),
+        // GLOB is SQLite's case-sensitive LIKE.
+        (_, DatabaseType::Sqlite, true) => format!("{} GLOB {}", col, p),
+        (_, DatabaseType::Mysql, true) => format!("{} LIKE BINARY {}", col, p),
+        (_, DatabaseType::Sqlite | DatabaseType::Mysql, false) => format!("LOWER({}) LIKE LOWER({})", col, p),
+    }
+}
+
+/// Column lists of MySQL FULLTEXT indexes by table name, in index order.
+/// `MATCH` only works on exactly the columns of one such index.
+fn fulltext_indexes(conn: &dyn DatabaseConnection) -> Result<HashMap<String, Vec<Vec<String>>>, DbError> {
+    let result = conn.query(
+        "SELECT table_schema, table_name, index_name, column_name \
+         FROM information_schema.statistics \
+         WHERE index_type = 'FULLTEXT' \
+         ORDER BY table_schema, table_name, index_name, seq_in_index"
+    )?;
+    let mut indexes: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+    let mut last: Option<(String, String)> = None;
+    for row in &result.rows {
+        let (Some(schema), Some(table), Some(index), Some(column)) =
+            (cell_text(row, 0), cell_text(row, 1), cell_text(row, 2), cell_text(row, 3)) else { continue };
+        let name = format!("{}.{}", schema, table);
+        let same = last.as_ref().is_some_and(|(n, i)| *n == name && *i == index);
+        let lists = indexes.entry(name.clone()).or_default();
+        match lists.last_mut() {
+            Some(list) if same => list.push(column),
+            _ => lists.push(vec![column]),
+        }
+        last = Some((name, index));
+    }
+    Ok(indexes)
+}
+
+/// The bound value for text predicates: the term itself for exact and
+/// full-text matches, otherwise a pattern with LIKE (or GLOB) metacharacters
+/// left as typed.
+fn text_pattern(db_type: DatabaseType, term: &str, options: &SearchOptions) -> String {
+    let fulltext = options.mode == SearchMode::FullText && db_type == DatabaseType::Postgres;
+    match options.mode {
+        SearchMode::Exact => term.to_string(),
+        _ if fulltext => term.to_string(),
+        _ if db_type == DatabaseType::Sqlite && options.case_sensitive => format!("*{}*", term),
+        _ => format!("%{}%", term),
+    }
+}
+
+fn search_table(
+    conn: &dyn DatabaseConnection,
+    table: &CatalogTable,
+    term: &str,
+    options: &SearchOptions,
+    rules: &[MaskingRule],
+    fulltext: &[Vec<String>],
+) -> Result<Option<SearchHit>, DbError> {
+    let db_type = conn.info().db_type;
+    let int_term = term.trim().parse::<i64>().ok();
+    // A match on a masked column would reveal which row holds the value.
+    let searchable = |name: &str| !masking::is_masked(rules, &table.name, name);
+
+    let mut predicates = Vec::new();
+    let mut params = Vec::new();
+    // MySQL full-text search uses the FULLTEXT indexes whose columns are all
+    // searchable; other text columns fall back to a substring match.
+    let mut covered: Vec<&str> = Vec::new();
+    for index in fulltext.iter().filter(|index| index.iter().all(|c| searchable(c))) {
+        params.push(QueryParam::Text(term.to_string()));
+        let cols: Vec<String> = index.iter().map(|c| quote_ident(db_type, c)).collect();
+        predicates.push(format!(
+            "MATCH({}) AGAINST ({} IN NATURAL LANGUAGE MODE)",
+            cols.join(", "),
+            placeholder(db_type, params.len()),
+        ));
+        covered.extend(index.iter().map(String::as_str));
+    }
+    let contains = SearchOptions { mode: SearchMode::Contains, ..options.clone() };
+    let text_options = match (options.mode, db_type) {
+        (SearchMode::FullText, DatabaseType::Mysql) => &contains,
+        _ => options,
+    };
+
+    let mut columns = Vec::new();
+    for column in &table.columns {
+        if !searchable(&column.name) {
+            continue;
+        }
+        if covered.iter().any(|c| c.eq_ignore_ascii_case(&column.name)) {
+            columns.push(column.name.clone());
+            continue;
+        }
+        if is_text_type(&column.data_type) {
+            params.push(QueryParam::Text(text_pattern(db_type, term, text_options)));
+            predicates.push(text_predicate(db_type, &column.name, params.len(), text_options));
+        } else if let Some(n) = int_term.filter(|_| is_integer_type(&column.data_type)) {
+            // Ids are often integers; match those exactly.
+            params.push(QueryParam::Int(n));
+            predicates.push(format!("{} = {}", quote_ident(db_type, &column.name), placeholder(db_type, params.len())));
+        } else {
+            continue;
+        }
+        columns.push(column.name.clone());
+    }
+    if predicates.is_empty() {
+        return Ok(None);
+    }
+
+    let limit = options.max_hits_per_table.unwrap_or(DEFAULT_HITS_PER_TABLE).max(1);
+    let sql = format!(
+        "SELECT * FROM {} WHERE {} LIMIT {}",
+        quote_table(db_type, &table.name),
+        predicates.join(" OR "),
+        limit,
+    );
+    let result = conn.query_limited(&sql, &params, &QueryLimits::default())?;
+    Ok((!result.rows.is_empty()).then(|| SearchHit { table: table.name.clone(), columns, result }))
+}
+
+/// Searches every table in `catalog` (filtered by `options.schemas`), one
+/// query per table. Columns `rules` mask are not searched. Stops between
+/// tables once `cancel` is set.
+pub fn search_all(
+    conn: &dyn DatabaseConnection,
+    catalog: &Catalog,
+    term: &str,
+    options: &SearchOptions,
+    rules: &[MaskingRule],
+    cancel: Option<&CancelToken>,
+    app: &AppHandle,
+) -> SearchResult {
+    let tables: Vec<&CatalogTable> = catalog.tables.iter()
+        .filter(|t| options.schemas.is_empty()
+            || t.schema.as_ref().is_some_and(|s| options.schemas.contains(s)))
+        .collect();
+
+    let mut result = SearchResult { hits: Vec::new(), tables_searched: 0, skipped: Vec::new(), cancelled: false };
+    // Without the index list every column gets a substring match.
+    let fulltext = match (options.mode, conn.info().db_type) {
+        (SearchMode::FullText, DatabaseType::Mysql) => fulltext_indexes(conn).unwrap_or_default(),
+        _ => HashMap::new(),
+    };
+    for (i, table) in tables.iter().enumerate() {
+        if cancel.is_some_and(CancelToken::is_cancelled) {
+            result.cancelled = true;
+            break;
+        }
+        let indexes = fulltext.get(&table.name).map(Vec::as_slice).unwrap_or_default();
+        match search_table(conn, table, term, options, rules, indexes) {
+            Ok(Some(hit)) => result.hits.push(hit),
+            Ok(None) => {}
+            Err(error) => result.skipped.push(SkippedTable { table: table.name.clone(), error }),
+        }
+        result.tables_searched += 1;
+
+        if let Some(job_id) = &options.job_id {
+            let _ = app.emit(SEARCH_PROGRESS_EVENT, SearchProgressEvent {
+                job_id: job_id.clone(),
+                tables_done: i + 1,
+                tables_total: tables.len(),
+                table: table.name.clone(),
+                hits: result.hits.len(),
+            });
+        }
+    }
+    result
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use super::connection::DatabaseConnection;
+use super::types::*;
+
+// Everything is cast to text so both backends decode the same way.
+// `pg_blocking_pids` follows `pg_locks` to the sessions holding conflicting locks.
+const PG_SESSIONS: &str = "\
+    SELECT a.pid::text, a.usename::text, a.datname::text, \
+           COALESCE(host(a.client_addr), CASE WHEN a.client_port = -1 THEN 'local' END), \
+           NULLIF(a.application_name, ''), a.state, a.query, \
+           (EXTRACT(EPOCH FROM now() - COALESCE(a.query_start, a.backend_start)) * 1000)::bigint::text, \
+           CASE WHEN a.wait_event IS NOT NULL THEN a.wait_event_type || ': ' || a.wait_event END, \
+           array_to_string(pg_blocking_pids(a.pid), ','), \
+           (a.pid = pg_backend_pid())::text \
+    FROM pg_stat_activity a \
+    WHERE a.backend_type = 'client backend' \
+    ORDER BY a.query_start NULLS LAST";
+
+// MySQL 8 reports lock waits in performance_schema; 5.7 only in information_schema.
+const MYSQL_LOCK_WAITS: [&str; 2] = [
+    "SELECT r.trx_mysql_thread_id, b.trx_mysql_thread_id \
+     FROM performance_schema.data_lock_waits w \
+     JOIN information_schema.innodb_trx r ON r.trx_id = w.REQUESTING_ENGINE_TRANSACTION_ID \
+     JOIN information_schema.innodb_trx b ON b.trx_id = w.BLOCKING_ENGINE_TRANSACTION_ID",
+    "SELECT r.trx_mysql_thread_id, b.trx_mysql_thread_id \
+     FROM information_schema.innodb_lock_waits w \
+     JOIN information_schema.innodb_trx r ON r.trx_id = w.requesting_trx_id \
+     JOIN information_schema.innodb_trx b ON b.trx_id = w.blocking_trx_id",
+];
+
+fn unsupported(db_type: DatabaseType) -> DbError {
+    DbError::invalid_input(format!("Session monitoring is not supported for {:?}", db_type))
+}
+
+pub fn list(conn: &dyn DatabaseConnection) -> Result<Vec<SessionInfo>, DbError> {
+    match conn.info().db_type {
+        DatabaseType::Postgres => postgres_sessions(conn),
+        DatabaseType::Mysql => mysql_sessions(conn),
+        other => Err(unsupported(other)),
+    }
+}
+
+fn postgres_sessions(conn: &dyn DatabaseConnection) -> Result<Vec<SessionInfo>, DbError> {
+    let result = conn.query(PG_SESSIONS)?;
+    Ok(result.rows.iter().map(|row| SessionInfo {
+        id: cell_text(row, 0).unwrap_or_default(),
+        user: cell_text(row, 1),
+        database: cell_text(row, 2),
+        client: cell_text(row, 3),
+        application: cell_text(row, 4),
+        state: cell_text(row, 5),
+        query: cell_text(row, 6).filter(|q| !q.is_empty()),
+        duration_ms: cell_text(row, 7).and_then(|d| d.parse().ok()),
+        wait: cell_text(row, 8),
+        blocked_by: cell_text(row, 9)
+            .map(|pids| pids.split(',').filter(|p| !p.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default(),
+        is_current: cell_text(row, 10).as_deref() == Some("true"),
+    }).collect())
+}
+
+fn mysql_sessions(conn: &dyn DatabaseConnection) -> Result<Vec<SessionInfo>, DbError> {
+    // Any pooled connection may run a given statement, so all of them are "current".
+    let own = conn.own_session_ids();
+
+    // Lock wait views need the PROCESS privilege; without it sessions still list.
+    let mut blockers: HashMap<String, Vec<String>> = HashMap::new();
+    if let Some(waits) = MYSQL_LOCK_WAITS.iter().find_map(|sql| conn.query(sql).ok()) {
+        for row in &waits.rows {
+            if let (Some(waiting), Some(blocking)) = (cell_text(row, 0), cell_text(row, 1)) {
+                let entry = blockers.entry(waiting).or_default();
+                if !entry.contains(&blocking) {
+                    entry.push(blocking);
+                }
+            }
+        }
+    }
+
+    // Id, User, Host, db, Command, Time, State, Info
+    let result = conn.query("SHOW FULL PROCESSLIST")?;
+    Ok(result.rows.iter().map(|row| {
+        let id = cell_text(row, 0).unwrap_or_default();
+        SessionInfo {
+            blocked_by: blockers.remove(&id).unwrap_or_default(),
+            is_current: own.contains(&id),
+            user: cell_text(row, 1),
+            client: cell_text(row, 2),
+            database: cell_text(row, 3),
+            application: None,
+            state: cell_text(row, 4),
+            duration_ms: cell_text(row, 5).and_then(|t| t.parse::<i64>().ok()).map(|s| s * 1000),
+            wait: cell_text(row, 6).filter(|s| !s.is_empty()),
+            query: cell_text(row, 7),
+            id,
+        }
+    }).collect())
+}
+
+/// The statement that ended a session and how it went, for the audit log.
+pub struct Kill {
+    pub sql: String,
+    pub result: Result<ExecuteResult, DbError>,
+}
+
+pub fn kill(conn: &dyn DatabaseConnection, session_id: &str, mode: KillMode) -> Result<Kill, DbError> {
+    let db_type = conn.info().db_type;
+    // Ids are interpolated into KILL, so only plain integers get through.
+    let id: u32 = session_id.trim().parse()
+        .map_err(|_| DbError::invalid_input(format!("Invalid session id: {}", session_id)))?;
+    if list(conn)?.iter().any(|s| s.is_current && s.id == id.to_string()) {
+        return Err(DbError::invalid_input("Refusing to kill the session this app is using"));
+    }
+
+    let start = std::time::Instant::now();
+    let (sql, result) = match (db_type, mode) {
+        (DatabaseType::Postgres, _) => {
+            let function = match mode {
+                KillMode::Cancel => "pg_cancel_backend",
+                KillMode::Terminate => "pg_terminate_backend",
+            };
+            let sql = format!("SELECT {}({})", function, id);
+            let result = conn.query(&sql).and_then(|r| {
+                let signalled = r.rows.first().and_then(|row| cell_text(row, 0));
+                match signalled.as_deref() {
+                    Some("true") => Ok(1),
+                    _ => Err(DbError::new(DbErrorKind::NotFound, format!("Session {} was not found or could not be signalled", id))),
+                }
+            });
+            (sql, result)
+        }
+        (DatabaseType::Mysql, KillMode::Cancel) => {
+            let sql = format!("KILL QUERY {}", id);
+            let result = conn.execute(&sql).map(|_| 1);
+            (sql, result)
+        }
+        (DatabaseType::Mysql, KillMode::Terminate) => {
+            let sql = format!("KILL {}", id);
+            let result = conn.execute(&sql).map(|_| 1);
+            (sql, result)
+        }
+        (other, _) => return Err(unsupported(other)),
+    };
+
+    Ok(Kill {
+        sql,
+        result: result.map(|rows_affected| ExecuteResult {
+            rows_affected,
+            last_insert_id: None,
+            execution_time_ms: start.elapsed().as_millis() as u64,
+        }),
+    })
+}
@@ -1,4 +1,6 @@
-use rusqlite::{Connection, OpenFlags};
+use rusqlite::{Connection, OpenFlags, params_from_iter};
+use rusqlite::types::{ToSql, ToSqlOutput, Value as SqlValue, ValueRef};
+use super::catalog::CatalogBuilder;
 use super::connection::DatabaseConnection;
 use super::types::*;
 
@@ -6,23 +8,87 @@ pub struct SqliteConnection {
     id: String,
     name: String,
     conn: Connection,
+    read_only: bool,
 }
 
 impl SqliteConnection {
-    pub fn new(path: &str) -> Result<Box<dyn DatabaseConnection + Send>, String> {
-        let conn = Connection::open_with_flags(
-            path,
+    pub fn new(path: &str, read_only: bool) -> Result<Box<dyn DatabaseConnection + Send>, DbError> {
+        let flags = if read_only {
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+        } else {
             OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE
-        ).map_err(|e| format!("SQLite connection error: {}", e))?;
+        };
+        let conn = Connection::open_with_flags(path, flags)
+            .map_err(|e| DbError { kind: DbErrorKind::Connection, ..e.into() })?;
 
         Ok(Box::new(SqliteConnection {
             id: uuid::Uuid::new_v4().to_string(),
             name: path.to_string(),
             conn,
+            read_only,
         }))
     }
 }
 
+impl ToSql for QueryParam {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Owned(match self {
+            QueryParam::Null => SqlValue::Null,
+            QueryParam::Bool(b) => SqlValue::Integer(*b as i64),
+            QueryParam::Int(n) => SqlValue::Integer(*n),
+            QueryParam::Float(n) => SqlValue::Real(*n),
+            QueryParam::Text(s) => SqlValue::Text(s.clone()),
+            QueryParam::Bytes(b) => SqlValue::Blob(b.clone()),
+        }))
+    }
+}
+
+impl From<rusqlite::Error> for DbError {
+    fn from(e: rusqlite::Error) -> Self {
+        use rusqlite::ErrorCode;
+        let (failure, message, position) = match &e {
+            // Prepare errors carry the byte offset of the failing token.
+            rusqlite::Error::SqlInputError { error, msg, sql, offset } => {
+                let position = usize::try_from(*offset).ok()
+                    .and_then(|offset| sql.get(..offset))
+                    .map(|before| before.chars().count() as u32 + 1);
+                (*error, msg.clone(), position)
+            }
+            rusqlite::Error::SqliteFailure(failure, _) => (*failure, e.to_string(), None),
+            _ => return DbError::from(e.to_string()),
+        };
+        let (kind, retryable) = match failure.code {
+            ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => (DbErrorKind::Timeout, true),
+            ErrorCode::ConstraintViolation => (DbErrorKind::Constraint, false),
+            ErrorCode::PermissionDenied | ErrorCode::ReadOnly | ErrorCode::AuthorizationForStatementDenied => (DbErrorKind::Permission, false),
+            ErrorCode::OperationInterrupted => (DbErrorKind::Cancelled, false),
+            ErrorCode::TypeMismatch | ErrorCode::TooBig => (DbErrorKind::InvalidInput, false),
+            ErrorCode::CannotOpen => (DbErrorKind::Connection, false),
+            ErrorCode::SchemaChanged => (DbErrorKind::Other, true),
+            // Plain SQLITE_ERROR covers both bad syntax and unknown names.
+            _ if message.starts_with("no such ") => (DbErrorKind::NotFound, false),
+            _ if message.contains("syntax error") || message.starts_with("near ") => (DbErrorKind::Syntax, false),
+            _ => (DbErrorKind::Other, false),
+        };
+        DbError {
+            code: Some(failure.extended_code.to_string()),
+            position,
+            retryable,
+            ..DbError::new(kind, message)
+        }
+    }
+}
+
+fn to_cell(value: ValueRef<'_>) -> Option<CellValue> {
+    match value {
+        ValueRef::Null => None,
+        ValueRef::Integer(n) => Some(n.to_string().into()),
+        ValueRef::Real(n) => Some(n.to_string().into()),
+        ValueRef::Text(t) => Some(String::from_utf8_lossy(t).into_owned().into()),
+        ValueRef::Blob(b) => Some(CellValue::binary(b)),
+    }
+}
+
 impl DatabaseConnection for SqliteConnection {
     fn info(&self) -> ConnectionInfo {
         ConnectionInfo {
@@ -31,42 +97,41 @@ impl DatabaseConnection for SqliteConnection {
             db_type: DatabaseType::Sqlite,
             version: "3".to_string(),
             connected: true,
+            read_only: self.read_only,
         }
     }
 
-    fn query(&self, sql: &str) -> Result<QueryResult, String> {
+    fn query_limited(&self, sql: &str, params: &[QueryParam], limits: &QueryLimits) -> Result<QueryResult, DbError> {
         let start = std::time::Instant::now();
         let mut stmt = self.conn.prepare(sql)
-            .map_err(|e| format!("Prepare error: {}", e))?;
+            .map_err(|e| DbError::from(e).locate(sql))?;
 
         let column_names: Vec<String> = stmt.column_names()
             .iter().map(|s| s.to_string()).collect();
 
-        let rows = stmt.query_map([], |row| {
+        let mut rows = stmt.query(params_from_iter(params))?;
+
+        let mut result_rows: Vec<Row> = Vec::new();
+        let mut truncated = false;
+        while let Some(row) = rows.next()? {
+            if limits.max_rows.is_some_and(|max| result_rows.len() >= max) {
+                truncated = true;
+                break;
+            }
             let mut values = Vec::new();
             for i in 0..column_names.len() {
-                let val: Option<String> = row.get(i).ok();
-                values.push(val);
+                values.push(row.get_ref(i).ok().and_then(to_cell));
             }
-            Ok(values)
-        }).map_err(|e| format!("Query error: {}", e))?;
-
-        let rows: Vec<Vec<Option<String>>> = rows
-            .filter_map(|r| r.ok())
-            .collect();
+            result_rows.push(values);
+        }
 
-        Ok(QueryResult {
-            columns: column_names,
-            row_count: rows.len(),
-            rows,
-            execution_time_ms: start.elapsed().as_millis() as u64,
-        })
+        Ok(QueryResult::new(column_names, result_rows, truncated, start))
     }
 
-    fn execute(&self, sql: &str) -> Result<ExecuteResult, String> {
+    fn execute_with_params(&self, sql: &str, params: &[QueryParam]) -> Result<ExecuteResult, DbError> {
         let start = std::time::Instant::now();
-        let result = self.conn.execute(sql, [])
-            .map_err(|e| format!("Execute error: {}", e))?;
+        let result = self.conn.execute(sql, params_from_iter(params))
+            .map_err(|e| DbError::from(e).locate(sql))?;
 
         Ok(ExecuteResult {
             rows_affected: result as u64,
@@ -75,10 +140,21 @@ impl DatabaseConnection for SqliteConnection {
         })
     }
 
-    fn get_tables(&self) -> Result<Vec<TableInfo>, String> {
+    fn query_bytes(&self, sql: &str, params: &[QueryParam]) -> Result<Option<Vec<u8>>, DbError> {
+        self.conn.query_row(sql, params_from_iter(params), |row| {
+            Ok(match row.get_ref(0)? {
+                ValueRef::Null => None,
+                ValueRef::Blob(b) | ValueRef::Text(b) => Some(b.to_vec()),
+                ValueRef::Integer(n) => Some(n.to_string().into_bytes()),
+                ValueRef::Real(n) => Some(n.to_string().into_bytes()),
+            })
+        }).map_err(|e| DbError::from(e).locate(sql))
+    }
+
+    fn get_tables(&self) -> Result<Vec<TableInfo>, DbError> {
         let sql = "SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name";
         let mut stmt = self.conn.prepare(sql)
-            .map_err(|e| format!("Error: {}", e))?;
+            ?;
 
         let tables = stmt.query_map([], |row| {
             Ok(TableInfo {
@@ -86,17 +162,17 @@ impl DatabaseConnection for SqliteConnection {
                 schema: None,
                 row_count: None,
             })
-        }).map_err(|e| format!("Error: {}", e))?;
+        })?;
 
         Ok(tables.filter_map(|t| t.ok()).collect())
     }
 
-    fn get_columns(&self, table: &str) -> Result<Vec<ColumnInfo>, String> {
+    fn get_columns(&self, table: &str) -> Result<Vec<ColumnInfo>, DbError> {
         // Quote identifier to prevent SQL injection
         let safe_table = table.replace('"', "\"\"");
         let sql = format!("PRAGMA table_info(\"{}\")", safe_table);
         let mut stmt = self.conn.prepare(&sql)
-            .map_err(|e| format!("Error: {}", e))?;
+            ?;
 
         let columns = stmt.query_map([], |row| {
             Ok(ColumnInfo {
@@ -106,13 +182,79 @@ impl DatabaseConnection for SqliteConnection {
                 default: row.get(4)?,
                 primary_key: row.get::<_, i32>(5)? == 1,
             })
-        }).map_err(|e| format!("Error: {}", e))?;
+        })?;
 
         Ok(columns.filter_map(|c| c.ok()).collect())
     }
 
+    fn get_catalog(&self) -> Result<Catalog, DbError> {
+        let mut builder = CatalogBuilder::default();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT m.name, p.name, p.type, p.\"notnull\", p.dflt_value, p.pk \
+             FROM sqlite_master m JOIN pragma_table_info(m.name) p \
+             WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite_%' \
+             ORDER BY m.name, p.cid"
+        )?;
+        let columns = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, ColumnInfo {
+                name: row.get(1)?,
+                data_type: row.get(2)?,
+                nullable: row.get::<_, i32>(3)? == 0,
+                default: row.get(4)?,
+                primary_key: row.get::<_, i32>(5)? > 0,
+            }))
+        })?;
+        for (table, column) in columns.filter_map(|c| c.ok()) {
+            builder.add_column(None, &table, column);
+        }
+
+        // SQLite foreign keys are unnamed; pragma rows are grouped by their per-table id.
+        let mut stmt = self.conn.prepare(
+            "SELECT m.name, f.id, f.\"table\", f.\"from\", f.\"to\" \
+             FROM sqlite_master m JOIN pragma_foreign_key_list(m.name) f \
+             WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite_%' \
+             ORDER BY m.name, f.id, f.seq"
+        )?;
+        let fks = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?;
+        let mut current: Option<(String, i64, ForeignKeyInfo)> = None;
+        for (table, id, parent, from, to) in fks.filter_map(|f| f.ok()) {
+            match current.as_mut() {
+                Some((t, i, fk)) if *t == table && *i == id => {
+                    fk.columns.push(from);
+                    fk.referenced_columns.extend(to);
+                }
+                _ => {
+                    if let Some((t, _, fk)) = current.take() {
+                        builder.add_foreign_key(None, &t, fk);
+                    }
+                    current = Some((table, id, ForeignKeyInfo {
+                        name: None,
+                        columns: vec![from],
+                        referenced_schema: None,
+                        referenced_table: parent,
+                        referenced_columns: to.into_iter().collect(),
+                    }));
+                }
+            }
+        }
+        if let Some((t, _, fk)) = current {
+            builder.add_foreign_key(None, &t, fk);
+        }
+
+        Ok(builder.build())
+    }
+
     fn is_alive(&self) -> bool {
-        self.conn.execute("SELECT 1", []).is_ok()
+        self.conn.query_row("SELECT 1", [], |_| Ok(())).is_ok()
     }
 
     fn close(&self) {
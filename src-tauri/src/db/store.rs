@@ -0,0 +1,43 @@
+use std::sync::Mutex;
+use rusqlite::Connection;
+use tauri::{AppHandle, Manager};
+
+/// Backend handle on the app's own `nexus.db`.
+///
+/// The schema is owned by the migrations in `lib.rs`; the SQL plugin runs them
+/// while preloading the database, before this store is opened.
+pub struct AppStore {
+    conn: Mutex<Connection>,
+}
+
+impl AppStore {
+    pub fn open(app: &AppHandle) -> Result<Self, String> {
+        // Same location tauri-plugin-sql resolves `sqlite:nexus.db` to.
+        let dir = app.path().app_config_dir()
+            .map_err(|e| format!("App config dir error: {}", e))?;
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("App config dir error: {}", e))?;
+
+        let conn = Connection::open(dir.join("nexus.db"))
+            .map_err(|e| format!("App store error: {}", e))?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))
+            .map_err(|e| format!("App store error: {}", e))?;
+
+        Ok(AppStore { conn: Mutex::new(conn) })
+    }
+
+    pub fn with_conn<T>(
+        &self,
+        f: impl FnOnce(&Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, String> {
+        let conn = self.conn.lock().map_err(|_| "Lock error")?;
+        f(&conn).map_err(|e| format!("App store error: {}", e))
+    }
+}
+
+pub fn now_millis() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
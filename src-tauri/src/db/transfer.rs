@@ -0,0 +1,413 @@
+use tauri::{AppHandle, Emitter};
+use super::connection::{with_connection, ConnectionPool};
+use super::dialect::{placeholder, quote_ident, quote_table, split_statements};
+use super::generate::{classify, max_length, TypeClass, MAX_PARAMS};
+use super::jobs::CancelToken;
+use super::masking;
+use super::types::*;
+
+pub const TRANSFER_PROGRESS_EVENT: &str = "db:transfer-progress";
+
+const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// Text column type in `target`; MySQL cannot index unbounded text, so key
+/// columns get a length.
+fn text_type(target: DatabaseType, length: Option<usize>, key: bool) -> String {
+    match (target, length) {
+        (DatabaseType::Sqlite, _) => "text".into(),
+        (_, Some(n)) => format!("varchar({})", n),
+        (DatabaseType::Mysql, None) if key => "varchar(255)".into(),
+        (DatabaseType::Mysql, None) => "longtext".into(),
+        (DatabaseType::Postgres | DatabaseType::Duckdb, None) => "text".into(),
+    }
+}
+
+/// Column type in the target dialect. Types pass through unchanged between
+/// connections of the same kind; otherwise they map by class, keeping lengths
+/// and precision where the target has an equivalent.
+fn map_type(column: &ColumnInfo, source: DatabaseType, target: DatabaseType) -> String {
+    use DatabaseType::*;
+    if source == target {
+        return column.data_type.clone();
+    }
+    let t = column.data_type.to_lowercase();
+    let args = t.split_once('(')
+        .and_then(|(_, rest)| rest.split_once(')'))
+        .map(|(inner, _)| inner.replace(' ', ""));
+    match classify(&t) {
+        TypeClass::Integer if target == Sqlite => "integer".into(),
+        TypeClass::Integer if t.contains("big") => "bigint".into(),
+        TypeClass::Integer if t.contains("small") || t.contains("tiny") => "smallint".into(),
+        TypeClass::Integer => "integer".into(),
+        TypeClass::Decimal if ["real", "double", "float"].iter().any(|k| t.contains(k)) => match target {
+            Postgres => "double precision".into(),
+            Sqlite => "real".into(),
+            Mysql | Duckdb => "double".into(),
+        },
+        TypeClass::Decimal => match (target, args) {
+            (Sqlite, _) => "numeric".into(),
+            _ if t.contains("money") => "numeric(19,2)".into(),
+            (_, Some(args)) => format!("numeric({})", args),
+            (Postgres, None) => "numeric".into(),
+            // Unbounded numerics: MySQL and DuckDB default to a scale that drops fractions.
+            (Mysql, None) => "decimal(65,30)".into(),
+            (Duckdb, None) => "decimal(38,10)".into(),
+        },
+        TypeClass::Boolean => match target {
+            Mysql => "tinyint(1)".into(),
+            Postgres | Sqlite | Duckdb => "boolean".into(),
+        },
+        TypeClass::Timestamp => {
+            let zoned = t.contains("with time zone") || t.contains("tz");
+            match target {
+                Postgres | Duckdb if zoned => "timestamptz".into(),
+                Postgres | Duckdb => "timestamp".into(),
+                Mysql => "datetime(6)".into(),
+                Sqlite => "text".into(),
+            }
+        }
+        TypeClass::Date | TypeClass::Time if target == Sqlite => "text".into(),
+        TypeClass::Date => "date".into(),
+        TypeClass::Time => "time".into(),
+        TypeClass::Uuid => match target {
+            Postgres | Duckdb => "uuid".into(),
+            Mysql => "char(36)".into(),
+            Sqlite => "text".into(),
+        },
+        TypeClass::Json => match target {
+            Postgres => "jsonb".into(),
+            Mysql | Duckdb => "json".into(),
+            Sqlite => "text".into(),
+        },
+        TypeClass::Binary => match target {
+            Postgres => "bytea".into(),
+            Mysql => "longblob".into(),
+            Sqlite | Duckdb => "blob".into(),
+        },
+        TypeClass::Text => text_type(target, max_length(&t), column.primary_key),
+    }
+}
+
+/// Select-list entry for one source column. Binary values come back hex-encoded
+/// because queries only return a preview of them.
+fn select_expr(db_type: DatabaseType, column: &str, class: TypeClass) -> String {
+    let col = quote_ident(db_type, column);
+    match (db_type, class) {
+        (DatabaseType::Postgres, TypeClass::Binary) => format!("encode({}, 'hex') AS {}", col, col),
+        // SQLite's hex() turns NULL into an empty string.
+        (DatabaseType::Sqlite, TypeClass::Binary) => format!("CASE WHEN {} IS NULL THEN NULL ELSE hex({}) END AS {}", col, col, col),
+        (DatabaseType::Mysql | DatabaseType::Duckdb, TypeClass::Binary) => format!("hex({}) AS {}", col, col),
+        _ => col,
+    }
+}
+
+/// Bound value for one source cell of a column of `class`.
+fn to_param(cell: Option<CellValue>, class: TypeClass, column: &str) -> Result<QueryParam, DbError> {
+    let text = match cell {
+        None => return Ok(QueryParam::Null),
+        Some(CellValue::Text(text)) => text,
+        // SQLite stores blobs in columns of any declared type.
+        Some(CellValue::Tagged(_)) => {
+            return Err(DbError::invalid_input(format!("Column {} holds binary data but is not declared as binary", column)));
+        }
+    };
+    Ok(match class {
+        // Masked values are not hex and are copied as they are.
+        TypeClass::Binary => QueryParam::Bytes(decode_hex(&text).unwrap_or_else(|| text.into_bytes())),
+        TypeClass::Integer => match text.parse() {
+            Ok(n) => QueryParam::Int(n),
+            Err(_) => QueryParam::Text(text),
+        },
+        TypeClass::Boolean => match text.to_lowercase().as_str() {
+            "t" | "true" | "1" => QueryParam::Bool(true),
+            "f" | "false" | "0" => QueryParam::Bool(false),
+            _ => QueryParam::Text(text),
+        },
+        _ => QueryParam::Text(text),
+    })
+}
+
+/// How one table is read from the source and written to the target.
+struct TablePlan {
+    source: String,
+    target: String,
+    create: Option<String>,
+    /// Copied columns with their source type class.
+    columns: Vec<(String, TypeClass)>,
+    /// Primary key columns, selected again after `columns` to page by.
+    keys: Vec<(String, TypeClass)>,
+    /// `SELECT` without conditions or paging.
+    select: String,
+    /// The filter as a condition, if any.
+    filter: Option<String>,
+    count: String,
+}
+
+fn find_table<'a>(catalog: &'a Catalog, name: &str) -> Option<&'a CatalogTable> {
+    catalog.tables.iter().find(|t| t.name == name).or_else(|| {
+        (!name.contains('.'))
+            .then(|| catalog.tables.iter().find(|t| t.name.rsplit('.').next() == Some(name)))
+            .flatten()
+    })
+}
+
+fn plan_table(
+    table: &TransferTable,
+    source: &Catalog,
+    target: &Catalog,
+    source_type: DatabaseType,
+    target_type: DatabaseType,
+    rules: &[MaskingRule],
+) -> Result<TablePlan, DbError> {
+    let from = source.tables.iter()
+        .find(|t| t.name == table.source)
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("Table {} not found", table.source)))?;
+    let filter = table.filter.as_deref().map(str::trim).filter(|f| !f.is_empty());
+    if filter.is_some_and(|f| split_statements(f, source_type).len() > 1) {
+        return Err(DbError::invalid_input("Filter must be a single condition"));
+    }
+    let target_name = table.target.clone().unwrap_or_else(|| match &from.schema {
+        Some(_) => from.name.split_once('.').map_or(from.name.as_str(), |(_, name)| name).to_string(),
+        None => from.name.clone(),
+    });
+
+    let (columns, create): (Vec<&ColumnInfo>, _) = match find_table(target, &target_name) {
+        Some(existing) => {
+            let columns: Vec<&ColumnInfo> = from.columns.iter()
+                .filter(|c| existing.columns.iter().any(|e| e.name.eq_ignore_ascii_case(&c.name)))
+                .collect();
+            if columns.is_empty() {
+                return Err(DbError::invalid_input(format!("No columns of {} match {}", from.name, existing.name)));
+            }
+            (columns, None)
+        }
+        None => {
+            let q = |name: &str| quote_ident(target_type, name);
+            let mut definitions: Vec<String> = from.columns.iter().map(|c| {
+                // Masked values no longer fit the original type.
+                let data_type = match masking::is_masked(rules, &from.name, &c.name) {
+                    true => text_type(target_type, None, c.primary_key),
+                    false => map_type(c, source_type, target_type),
+                };
+                format!("{} {}{}", q(&c.name), data_type, if c.nullable { "" } else { " NOT NULL" })
+            }).collect();
+            let keys: Vec<String> = from.columns.iter().filter(|c| c.primary_key).map(|c| q(&c.name)).collect();
+            if !keys.is_empty() {
+                definitions.push(format!("PRIMARY KEY ({})", keys.join(", ")));
+            }
+            let sql = format!(
+                "CREATE TABLE IF NOT EXISTS {} (\n  {}\n)",
+                quote_table(target_type, &target_name),
+                definitions.join(",\n  "),
+            );
+            (from.columns.iter().collect(), Some(sql))
+        }
+    };
+
+    let keys: Vec<(String, TypeClass)> = from.columns.iter()
+        .filter(|c| c.primary_key)
+        .map(|c| (c.name.clone(), classify(&c.data_type)))
+        .collect();
+    // Without a key there is no stable order to resume from between batches.
+    if keys.is_empty() {
+        return Err(DbError::invalid_input(format!("Table {} has no primary key to copy by", from.name)));
+    }
+    let columns: Vec<(String, TypeClass)> = columns.iter().map(|c| (c.name.clone(), classify(&c.data_type))).collect();
+    // The filter is wrapped on its own line so a trailing `--` comment cannot
+    // swallow the paging clauses.
+    let filter = filter.map(|f| format!("({}\n)", f));
+    let table_sql = quote_table(source_type, &from.name);
+    let exprs: Vec<String> = columns.iter().chain(&keys).map(|(name, class)| select_expr(source_type, name, *class)).collect();
+
+    Ok(TablePlan {
+        source: from.name.clone(),
+        target: target_name,
+        create,
+        select: format!("SELECT {} FROM {}", exprs.join(", "), table_sql),
+        count: format!("SELECT COUNT(*) FROM {}{}", table_sql, filter.as_ref().map(|f| format!(" WHERE {}", f)).unwrap_or_default()),
+        filter,
+        columns,
+        keys,
+    })
+}
+
+impl TablePlan {
+    /// Source column `name`, qualified so it cannot resolve to a select alias.
+    fn key_column(&self, db_type: DatabaseType, name: &str) -> String {
+        format!("{}.{}", quote_table(db_type, &self.source), quote_ident(db_type, name))
+    }
+
+    /// The next `limit` rows in key order, starting after the key `after`.
+    fn page(&self, db_type: DatabaseType, after: Option<&[QueryParam]>, limit: usize) -> String {
+        let list = |items: Vec<String>| match items.len() {
+            1 => items.concat(),
+            _ => format!("({})", items.join(", ")),
+        };
+        let columns: Vec<String> = self.keys.iter().map(|(name, _)| self.key_column(db_type, name)).collect();
+        let mut conditions: Vec<String> = self.filter.iter().cloned().collect();
+        if let Some(after) = after {
+            let marks = (1..=after.len()).map(|i| placeholder(db_type, i)).collect();
+            conditions.push(format!("{} > {}", list(columns.clone()), list(marks)));
+        }
+        let clause = match conditions.is_empty() {
+            true => String::new(),
+            false => format!(" WHERE {}", conditions.join(" AND ")),
+        };
+        format!("{}{} ORDER BY {} LIMIT {}", self.select, clause, columns.join(", "), limit)
+    }
+}
+
+/// One transfer between two pooled connections. Each batch is a separate
+/// round trip to either side, so neither connection is held for the whole copy.
+pub struct Transfer<'a> {
+    pub pool: &'a ConnectionPool,
+    pub source_id: &'a str,
+    pub target_id: &'a str,
+    /// The source's masking rules; empty unless masking was asked for.
+    pub rules: &'a [MaskingRule],
+    pub cancel: Option<&'a CancelToken>,
+    pub app: &'a AppHandle,
+}
+
+impl Transfer<'_> {
+    fn is_cancelled(&self) -> bool {
+        self.cancel.is_some_and(CancelToken::is_cancelled)
+    }
+
+    /// Copies every table in `options`, creating missing target tables with
+    /// mapped column types. Tables that fail are reported in `skipped`;
+    /// batches already inserted stay. Stops between batches once cancelled.
+    pub async fn run(&self, source: &Catalog, target: &Catalog, options: &TransferOptions) -> Result<TransferResult, DbError> {
+        let start = std::time::Instant::now();
+        let source_type = with_connection(self.pool, self.source_id, |conn| Ok(conn.info().db_type)).await?;
+        let target_type = with_connection(self.pool, self.target_id, |conn| Ok(conn.info().db_type)).await?;
+        let batch_size = options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+
+        let mut result = TransferResult { tables: Vec::new(), skipped: Vec::new(), cancelled: false, execution_time_ms: 0 };
+        for (i, table) in options.tables.iter().enumerate() {
+            if self.is_cancelled() {
+                result.cancelled = true;
+                break;
+            }
+            let planned = plan_table(table, source, target, source_type, target_type, self.rules).and_then(|plan| {
+                // Appending a table to itself would page through its own new rows forever.
+                let same = self.source_id == self.target_id
+                    && find_table(target, &plan.target).is_some_and(|t| t.name == plan.source);
+                match same {
+                    true => Err(DbError::invalid_input("Source and target are the same table")),
+                    false => Ok(plan),
+                }
+            });
+            let copied = match planned {
+                Ok(plan) => self.copy_table(plan, (source_type, target_type), batch_size, options.job_id.as_deref(), (i, options.tables.len())).await,
+                Err(error) => Err(error),
+            };
+            match copied {
+                Ok((done, stopped)) => {
+                    result.tables.push(done);
+                    if stopped {
+                        result.cancelled = true;
+                        break;
+                    }
+                }
+                Err(error) => result.skipped.push(SkippedTable { table: table.source.clone(), error }),
+            }
+        }
+        result.execution_time_ms = start.elapsed().as_millis() as u64;
+        Ok(result)
+    }
+
+    /// Copies one table; the flag is set when it stopped early on cancellation.
+    async fn copy_table(
+        &self,
+        plan: TablePlan,
+        (source_type, target_type): (DatabaseType, DatabaseType),
+        batch_size: usize,
+        job_id: Option<&str>,
+        (index, tables_total): (usize, usize),
+    ) -> Result<(TableTransfer, bool), DbError> {
+        if let Some(sql) = plan.create.clone() {
+            with_connection(self.pool, self.target_id, move |conn| conn.execute(&sql)).await?;
+        }
+        let count = plan.count.clone();
+        let rows_total = with_connection(self.pool, self.source_id, move |conn| {
+            Ok(conn.query(&count).ok()
+                .and_then(|r| r.rows.first()?.first()?.as_ref()?.as_text()?.parse::<u64>().ok()))
+        }).await?;
+
+        let names: Vec<String> = plan.columns.iter().map(|(name, _)| quote_ident(target_type, name)).collect();
+        let insert = format!("INSERT INTO {} ({}) VALUES ", quote_table(target_type, &plan.target), names.join(", "));
+        let rows_per_insert = (MAX_PARAMS / names.len()).clamp(1, batch_size);
+        let lineage = masking::Lineage::of_table(&plan.source);
+
+        let mut done = TableTransfer { source: plan.source.clone(), target: plan.target.clone(), created: plan.create.is_some(), rows: 0 };
+        let mut stopped = false;
+        let mut after: Option<Vec<QueryParam>> = None;
+        loop {
+            if self.is_cancelled() {
+                stopped = true;
+                break;
+            }
+            let sql = plan.page(source_type, after.as_deref(), batch_size);
+            let params = after.take().unwrap_or_default();
+            let mut batch = with_connection(self.pool, self.source_id, move |conn| {
+                conn.query_limited(&sql, &params, &QueryLimits::UNLIMITED)
+            }).await?;
+            let fetched = batch.rows.len();
+            if fetched == 0 {
+                break;
+            }
+            // The trailing key columns are read before masking can replace them.
+            let width = plan.columns.len();
+            if let Some(last) = batch.rows.last() {
+                after = Some(last[width..].iter().cloned()
+                    .zip(&plan.keys)
+                    .map(|(cell, (name, class))| match cell {
+                        None => Err(DbError::invalid_input(format!("Key column {} has NULL values", name))),
+                        cell => to_param(cell, *class, name),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?);
+            }
+            batch.columns.truncate(width);
+            for row in &mut batch.rows {
+                row.truncate(width);
+            }
+            masking::apply(self.rules, &mut batch, &lineage);
+
+            let rows = batch.rows.into_iter().map(|row| {
+                row.into_iter()
+                    .zip(&plan.columns)
+                    .map(|(cell, (name, class))| to_param(cell, *class, name))
+                    .collect::<Result<Vec<_>, _>>()
+            }).collect::<Result<Vec<_>, _>>()?;
+            let statements: Vec<(String, Vec<QueryParam>)> = rows.chunks(rows_per_insert).map(|chunk| {
+                let mut index = 0;
+                let tuples: Vec<String> = chunk.iter().map(|row| {
+                    let marks: Vec<String> = row.iter().map(|_| {
+                        index += 1;
+                        placeholder(target_type, index)
+                    }).collect();
+                    format!("({})", marks.join(", "))
+                }).collect();
+                (format!("{}{}", insert, tuples.join(", ")), chunk.concat())
+            }).collect();
+            with_connection(self.pool, self.target_id, move |conn| conn.execute_in_transaction(&statements)).await?;
+            done.rows += fetched as u64;
+
+            if let Some(job_id) = job_id {
+                let _ = self.app.emit(TRANSFER_PROGRESS_EVENT, TransferProgressEvent {
+                    job_id: job_id.to_string(),
+                    table: plan.source.clone(),
+                    tables_done: index,
+                    tables_total,
+                    rows_done: done.rows,
+                    rows_total,
+                });
+            }
+            if fetched < batch_size {
+                break;
+            }
+        }
+        Ok((done, stopped))
+    }
+}
@@ -1,10 +1,12 @@
+use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DatabaseType {
     Sqlite,
     Postgres,
     Mysql,
+    Duckdb,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +20,27 @@ pub struct ConnectionConfig {
     pub username: Option<String>,
     pub password: Option<String>,
     pub file_path: Option<String>,
+    /// Enforced by the driver session itself, not just by SQL validation.
+    #[serde(default)]
+    pub read_only: bool,
+    /// PostgreSQL schema put first on the `search_path`; see `db_use_database`.
+    #[serde(default)]
+    pub schema: Option<String>,
+}
+
+/// A vault entry as the webview sees it; the password never leaves the backend.
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedConnection {
+    #[serde(flatten)]
+    pub config: ConnectionConfig,
+    pub has_password: bool,
+}
+
+impl From<ConnectionConfig> for SavedConnection {
+    fn from(mut config: ConnectionConfig) -> Self {
+        let has_password = config.password.take().is_some_and(|p| !p.is_empty());
+        SavedConnection { config, has_password }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -27,14 +50,145 @@ pub struct ConnectionInfo {
     pub db_type: DatabaseType,
     pub version: String,
     pub connected: bool,
+    pub read_only: bool,
+}
+
+/// Bytes of binary data shown in a cell preview.
+pub const BINARY_PREVIEW_BYTES: usize = 32;
+
+/// A non-null result cell. Text serializes as a plain string; binary data as
+/// `{ "type": "binary", "length": .., "preview_hex": .. }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CellValue {
+    Text(String),
+    Tagged(TaggedValue),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TaggedValue {
+    Binary { length: usize, preview_hex: String },
+}
+
+/// Inverse of the hex encoding used for binary previews.
+pub fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    hex.as_bytes().chunks(2).map(|pair| {
+        let pair = std::str::from_utf8(pair).ok().filter(|p| p.len() == 2)?;
+        u8::from_str_radix(pair, 16).ok()
+    }).collect()
+}
+
+impl CellValue {
+    pub fn binary(bytes: &[u8]) -> Self {
+        let preview = &bytes[..bytes.len().min(BINARY_PREVIEW_BYTES)];
+        CellValue::Tagged(TaggedValue::Binary {
+            length: bytes.len(),
+            preview_hex: preview.iter().map(|b| format!("{:02x}", b)).collect(),
+        })
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            CellValue::Text(s) => Some(s),
+            CellValue::Tagged(_) => None,
+        }
+    }
+}
+
+impl From<String> for CellValue {
+    fn from(s: String) -> Self {
+        CellValue::Text(s)
+    }
+}
+
+pub type Row = Vec<Option<CellValue>>;
+
+/// Text of cell `i`; `None` for NULL, binary or missing cells.
+pub fn cell_text(row: &Row, i: usize) -> Option<String> {
+    row.get(i)?.as_ref()?.as_text().map(str::to_string)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QueryResult {
     pub columns: Vec<String>,
-    pub rows: Vec<Vec<Option<String>>>,
+    pub rows: Vec<Row>,
     pub row_count: usize,
     pub execution_time_ms: u64,
+    /// Fetching stopped at the row limit; the query has more rows.
+    #[serde(default)]
+    pub truncated: bool,
+    /// Cells shortened to the cell size limit; see `db_get_cell`.
+    #[serde(default)]
+    pub truncated_cells: usize,
+    /// Full row count, when it is known.
+    #[serde(default)]
+    pub total_rows: Option<u64>,
+    /// Handle for fetching truncated cells; set only when some were cut.
+    #[serde(default)]
+    pub result_id: Option<String>,
+}
+
+impl QueryResult {
+    pub fn new(
+        columns: Vec<String>,
+        rows: Vec<Row>,
+        truncated: bool,
+        start: std::time::Instant,
+    ) -> Self {
+        QueryResult {
+            columns,
+            row_count: rows.len(),
+            total_rows: if truncated { None } else { Some(rows.len() as u64) },
+            rows,
+            execution_time_ms: start.elapsed().as_millis() as u64,
+            truncated,
+            truncated_cells: 0,
+            result_id: None,
+        }
+    }
+}
+
+/// What `db_use_database` can switch to. MySQL has no schemas within a
+/// database, so `schemas` is empty there.
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseList {
+    pub databases: Vec<String>,
+    pub current_database: Option<String>,
+    /// Schemas of the current database.
+    pub schemas: Vec<String>,
+    pub current_schema: Option<String>,
+}
+
+/// One result set from `db_query_multi`. A procedure call can produce several
+/// for the same statement; sets without rows (a procedure's final status, an
+/// `UPDATE` inside it) have no columns and carry `rows_affected` instead.
+#[derive(Debug, Serialize)]
+pub struct ResultSet {
+    /// Index of the statement in the batch that produced this set.
+    pub statement: usize,
+    pub result: QueryResult,
+    pub rows_affected: u64,
+}
+
+/// Per-query result size guards.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct QueryLimits {
+    pub max_rows: Option<usize>,
+    pub max_cell_bytes: Option<usize>,
+}
+
+impl QueryLimits {
+    pub const UNLIMITED: QueryLimits = QueryLimits { max_rows: None, max_cell_bytes: None };
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        QueryLimits {
+            max_rows: Some(10_000),
+            max_cell_bytes: Some(64 * 1024),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,7 +205,7 @@ pub struct TableInfo {
     pub row_count: Option<i64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColumnInfo {
     pub name: String,
     pub data_type: String,
@@ -60,9 +214,875 @@ pub struct ColumnInfo {
     pub primary_key: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForeignKeyInfo {
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+    pub referenced_schema: Option<String>,
+    pub referenced_table: String,
+    pub referenced_columns: Vec<String>,
+}
+
+/// A table with everything autocompletion needs, loaded in bulk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogTable {
+    /// Same form as `TableInfo::name` (`schema.table` where schemas exist).
+    pub name: String,
+    pub schema: Option<String>,
+    pub columns: Vec<ColumnInfo>,
+    pub foreign_keys: Vec<ForeignKeyInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Catalog {
+    pub schemas: Vec<String>,
+    pub tables: Vec<CatalogTable>,
+    pub loaded_at: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct IndexInfo {
     pub name: String,
     pub columns: Vec<String>,
     pub unique: bool,
 }
+
+/// A value bound to a statement placeholder instead of being spliced into SQL text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum QueryParam {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl QueryParam {
+    /// Text form used by drivers that send parameters as strings. Bytes use
+    /// the `\x..` hex form Postgres accepts for `bytea`.
+    pub fn to_text(&self) -> Option<String> {
+        match self {
+            QueryParam::Null => None,
+            QueryParam::Bool(b) => Some(b.to_string()),
+            QueryParam::Int(n) => Some(n.to_string()),
+            QueryParam::Float(n) => Some(n.to_string()),
+            QueryParam::Text(s) => Some(s.clone()),
+            QueryParam::Bytes(b) => Some(format!("\\x{}", b.iter().map(|b| format!("{:02x}", b)).collect::<String>())),
+        }
+    }
+}
+
+/// One column of a row key, e.g. a primary key value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyValue {
+    pub column: String,
+    pub value: QueryParam,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableType {
+    Text,
+    Integer,
+    Number,
+    Boolean,
+    Date,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryVariable {
+    pub name: String,
+    pub var_type: VariableType,
+    pub default: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub id: String,
+    pub name: String,
+    /// `None` makes the query available on every connection.
+    pub connection_id: Option<String>,
+    pub folder: Option<String>,
+    pub tags: Vec<String>,
+    pub sql: String,
+    pub variables: Vec<QueryVariable>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", content = "result", rename_all = "snake_case")]
+pub enum SavedQueryOutput {
+    Query(QueryResult),
+    Execute(ExecuteResult),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+}
+
+/// One write statement recorded in the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: i64,
+    pub timestamp: i64,
+    pub conn_id: String,
+    pub connection_name: String,
+    pub host: Option<String>,
+    /// Which command ran the statement, e.g. `execute` or `saved_query`.
+    pub operation: String,
+    pub sql: String,
+    pub rows_affected: Option<u64>,
+    pub outcome: AuditOutcome,
+    pub error: Option<String>,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditFilter {
+    pub conn_id: Option<String>,
+    /// Inclusive lower bound, epoch milliseconds.
+    pub since: Option<i64>,
+    /// Exclusive upper bound, epoch milliseconds.
+    pub until: Option<i64>,
+    pub outcome: Option<AuditOutcome>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditVerification {
+    pub valid: bool,
+    pub checked: u64,
+    /// First entry whose hash does not follow from the one before it.
+    pub broken_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditExportFormat {
+    Csv,
+    Jsonl,
+}
+
+/// Broad class of a `DbError`, enough for the UI to pick a treatment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DbErrorKind {
+    Syntax,
+    /// Unknown database, schema, table, column or function.
+    NotFound,
+    Constraint,
+    Permission,
+    /// A value or argument was rejected, by the server or before reaching it.
+    InvalidInput,
+    /// Deadlock or serialization failure.
+    Conflict,
+    Timeout,
+    Cancelled,
+    /// The connection could not be opened or was lost.
+    Connection,
+    /// The statement is destructive and did not run; see `confirmation`.
+    ConfirmationRequired,
+    Other,
+}
+
+/// What a destructive statement would do, and the one-time token that runs it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfirmationRequest {
+    pub operation: String,
+    pub tables: Vec<String>,
+    pub estimated_rows: Option<u64>,
+    pub token: String,
+}
+
+/// Error returned by the drivers and every `db_*` command.
+#[derive(Debug, Clone, Serialize)]
+pub struct DbError {
+    pub kind: DbErrorKind,
+    /// SQLSTATE where the backend reports a specific one, otherwise the
+    /// vendor error code.
+    pub code: Option<String>,
+    pub message: String,
+    pub detail: Option<String>,
+    pub hint: Option<String>,
+    /// 1-based character offset of the failing token in the statement.
+    pub position: Option<u32>,
+    /// Running the same thing again may succeed (lost connection, deadlock,
+    /// lock timeout).
+    pub retryable: bool,
+    /// Set for `confirmation_required`; flattened into the error object.
+    #[serde(flatten)]
+    pub confirmation: Option<Box<ConfirmationRequest>>,
+}
+
+impl DbError {
+    pub fn new(kind: DbErrorKind, message: impl Into<String>) -> Self {
+        DbError {
+            kind,
+            code: None,
+            message: message.into(),
+            detail: None,
+            hint: None,
+            position: None,
+            retryable: false,
+            confirmation: None,
+        }
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(DbErrorKind::InvalidInput, message)
+    }
+
+    /// Classified by SQLSTATE, which PostgreSQL and MySQL (mostly) share.
+    pub fn from_sqlstate(code: &str, message: impl Into<String>) -> Self {
+        use DbErrorKind::*;
+        let (kind, retryable) = match code {
+            "40001" | "40P01" => (Conflict, true),
+            "55P03" => (Timeout, true),
+            "57014" | "70100" => (Cancelled, false),
+            "57P01" | "57P02" | "57P03" | "53300" => (Connection, true),
+            "42501" | "25006" => (Permission, false),
+            "42P01" | "42P02" | "42703" | "42704" | "42883" | "3D000" | "3F000" | "42S02" | "42S22" => (NotFound, false),
+            _ => match code.get(..2) {
+                Some("08") => (Connection, true),
+                Some("23") => (Constraint, false),
+                Some("28") => (Permission, false),
+                Some("22") => (InvalidInput, false),
+                Some("42") => (Syntax, false),
+                _ => (Other, false),
+            },
+        };
+        DbError { code: Some(code.to_string()), retryable, ..Self::new(kind, message) }
+    }
+
+    /// Lost or refused connections are always worth retrying once the health
+    /// checker has reconnected.
+    pub fn connection(message: impl Into<String>) -> Self {
+        DbError { retryable: true, ..Self::new(DbErrorKind::Connection, message) }
+    }
+
+    pub fn confirmation_required(request: ConfirmationRequest) -> Self {
+        let rows = request.estimated_rows.map(|n| format!(" (~{} rows)", n)).unwrap_or_default();
+        let message = format!("{}{} needs confirmation", request.operation, rows);
+        DbError { confirmation: Some(Box::new(request)), ..Self::new(DbErrorKind::ConfirmationRequired, message) }
+    }
+
+    /// Fills in `position` for backends that only point at the failing token
+    /// in the message text (`near 'FORM t'`, `near "FORM"`).
+    pub fn locate(mut self, sql: &str) -> Self {
+        if self.position.is_none() {
+            self.position = near_fragment(&self.message)
+                .and_then(|fragment| sql.find(fragment))
+                .map(|byte| sql[..byte].chars().count() as u32 + 1);
+        }
+        self
+    }
+}
+
+fn near_fragment(message: &str) -> Option<&str> {
+    let rest = &message[message.find("near ")? + 5..];
+    let fragment = if let Some(rest) = rest.strip_prefix('\'') {
+        // MySQL quotes the rest of the statement, which may itself contain quotes.
+        &rest[..rest.rfind("' at line").or_else(|| rest.find('\''))?]
+    } else {
+        let rest = rest.strip_prefix('"')?;
+        &rest[..rest.find('"')?]
+    };
+    (!fragment.is_empty()).then_some(fragment)
+}
+
+impl std::fmt::Display for DbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for DbError {
+    fn from(message: String) -> Self {
+        DbError::new(DbErrorKind::Other, message)
+    }
+}
+
+impl From<&str> for DbError {
+    fn from(message: &str) -> Self {
+        DbError::new(DbErrorKind::Other, message)
+    }
+}
+
+impl From<DbError> for String {
+    fn from(e: DbError) -> Self {
+        e.message
+    }
+}
+
+/// A `db_browse_table` filter condition on one column.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BrowseFilter {
+    pub column: String,
+    #[serde(flatten)]
+    pub op: FilterOp,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", content = "value", rename_all = "snake_case")]
+pub enum FilterOp {
+    Eq(QueryParam),
+    Like(String),
+    In(Vec<QueryParam>),
+    Between(QueryParam, QueryParam),
+    IsNull,
+    IsNotNull,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SortKey {
+    pub column: String,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum BrowsePage {
+    Offset { offset: u64, limit: u32 },
+    /// Rows after `after`, the sort column values of the last row seen (from
+    /// `next_cursor`). Sort keys should end in a unique, non-null column.
+    Keyset { after: Option<Vec<QueryParam>>, limit: u32 },
+}
+
+impl Default for BrowsePage {
+    fn default() -> Self {
+        BrowsePage::Offset { offset: 0, limit: 100 }
+    }
+}
+
+/// Structured table read; the backend builds and quotes the SQL.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BrowseSpec {
+    pub table: String,
+    /// Columns to return; all when empty.
+    #[serde(default)]
+    pub columns: Vec<String>,
+    #[serde(default)]
+    pub filters: Vec<BrowseFilter>,
+    #[serde(default)]
+    pub sort: Vec<SortKey>,
+    #[serde(default)]
+    pub page: BrowsePage,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BrowseResult {
+    #[serde(flatten)]
+    pub result: QueryResult,
+    /// Rows matching the filters, across all pages.
+    pub total: Option<u64>,
+    /// `total` comes from table statistics or hit the count cap.
+    pub total_is_estimate: bool,
+    /// Pass back as `after` for the next keyset page; `None` on the last page.
+    pub next_cursor: Option<Vec<QueryParam>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMode {
+    /// Substring match with LIKE/ILIKE.
+    #[default]
+    Contains,
+    Exact,
+    /// Dialect full-text search where available (PostgreSQL `tsvector`, MySQL
+    /// `MATCH ... AGAINST` on FULLTEXT-indexed columns); substring match elsewhere.
+    FullText,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SearchOptions {
+    /// Limit the search to these schemas; all when empty.
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(default)]
+    pub mode: SearchMode,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Rows returned per table; defaults to 20.
+    pub max_hits_per_table: Option<u32>,
+    /// Id for progress events and `db_cancel_job`.
+    pub job_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    pub table: String,
+    /// Columns the term was searched in; matching rows hit at least one.
+    pub columns: Vec<String>,
+    pub result: QueryResult,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkippedTable {
+    pub table: String,
+    pub error: DbError,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    pub hits: Vec<SearchHit>,
+    pub tables_searched: usize,
+    pub skipped: Vec<SkippedTable>,
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchProgressEvent {
+    pub job_id: String,
+    pub tables_done: usize,
+    pub tables_total: usize,
+    pub table: String,
+    pub hits: usize,
+}
+
+/// A `NOTIFY` received on a connection that is listening on `channel`.
+#[derive(Debug, Clone)]
+pub struct DbNotification {
+    pub channel: String,
+    pub payload: String,
+    pub process_id: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationEvent {
+    pub conn_id: String,
+    pub channel: String,
+    pub payload: String,
+    pub process_id: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaskType {
+    /// Replace the value with `***`.
+    Redact,
+    /// Replace the value with a short stable hash, so equal values still match.
+    Hash,
+    /// Keep the edges, e.g. `j***@x.com` or `a***z`.
+    Partial,
+    Null,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MaskTarget {
+    /// Column name glob (`*` and `?`, case-insensitive) in any table.
+    Pattern { pattern: String },
+    /// One column of one table (`table` may be schema-qualified).
+    Column { table: String, column: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaskingRule {
+    #[serde(default)]
+    pub id: String,
+    pub connection_id: String,
+    pub target: MaskTarget,
+    pub mask: MaskType,
+    #[serde(default)]
+    pub created_at: i64,
+}
+
+/// One server session, normalized across PostgreSQL and MySQL.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    /// Backend pid (PostgreSQL) or thread id (MySQL).
+    pub id: String,
+    pub user: Option<String>,
+    pub database: Option<String>,
+    pub client: Option<String>,
+    pub application: Option<String>,
+    /// `active`, `idle in transaction`, ... (PostgreSQL) or the command (MySQL).
+    pub state: Option<String>,
+    pub query: Option<String>,
+    pub duration_ms: Option<i64>,
+    /// What the session is waiting on, if anything.
+    pub wait: Option<String>,
+    /// Sessions holding locks this one waits for.
+    pub blocked_by: Vec<String>,
+    /// The session this app is using.
+    pub is_current: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillMode {
+    /// Cancel the running statement, keep the session.
+    Cancel,
+    /// Close the session.
+    #[default]
+    Terminate,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RoleInfo {
+    /// Role name (PostgreSQL) or `'user'@'host'` account (MySQL).
+    pub name: String,
+    pub can_login: bool,
+    pub superuser: bool,
+    /// Roles this one is a member of.
+    pub member_of: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrantLevel {
+    Global,
+    Database,
+    Table,
+    Column,
+    Routine,
+}
+
+/// One privilege held by one grantee on one object.
+#[derive(Debug, Clone, Serialize)]
+pub struct PrivilegeGrant {
+    pub grantee: String,
+    pub level: GrantLevel,
+    pub schema: Option<String>,
+    pub object: Option<String>,
+    pub column: Option<String>,
+    pub privilege: String,
+    pub grantable: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrivilegeReport {
+    pub roles: Vec<RoleInfo>,
+    pub grants: Vec<PrivilegeGrant>,
+    /// Grantees whose grants could not be read, with the reason.
+    pub skipped: Vec<String>,
+}
+
+/// A column as written in `ADD COLUMN`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnDef {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+    /// SQL expression, used verbatim.
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferentialAction {
+    NoAction,
+    Restrict,
+    Cascade,
+    SetNull,
+    SetDefault,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum AlterOp {
+    AddColumn { column: ColumnDef },
+    RenameColumn { from: String, to: String },
+    /// `nullable` left out keeps the current setting.
+    RetypeColumn {
+        column: String,
+        data_type: String,
+        #[serde(default)]
+        nullable: Option<bool>,
+    },
+    DropColumn { column: String },
+    AddIndex {
+        name: String,
+        columns: Vec<String>,
+        #[serde(default)]
+        unique: bool,
+    },
+    DropIndex { name: String },
+    AddForeignKey {
+        #[serde(default)]
+        name: Option<String>,
+        columns: Vec<String>,
+        referenced_table: String,
+        referenced_columns: Vec<String>,
+        #[serde(default)]
+        on_delete: Option<ReferentialAction>,
+    },
+    /// By constraint name, or by its columns where constraints are unnamed (SQLite).
+    DropForeignKey {
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        columns: Vec<String>,
+    },
+    RenameTable { to: String },
+}
+
+/// Changes to one table, applied in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableAlteration {
+    /// Same form as `TableInfo::name`.
+    pub table: String,
+    pub ops: Vec<AlterOp>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DdlPlan {
+    pub statements: Vec<String>,
+    /// Whether the statements run in one transaction. MySQL commits each DDL
+    /// statement implicitly, so there a failure leaves earlier ones applied.
+    pub transactional: bool,
+    /// SQLite only: the table is rebuilt (create, copy, drop, rename) with
+    /// foreign key enforcement paused.
+    pub rebuild: bool,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FakeKind {
+    Email,
+    FirstName,
+    LastName,
+    FullName,
+    Username,
+    Phone,
+    Company,
+    City,
+    Country,
+    Address,
+    Url,
+    Uuid,
+    Date,
+    Timestamp,
+    Amount,
+    Integer,
+    Boolean,
+    Word,
+    Sentence,
+}
+
+/// How to fill one column; columns without a rule are guessed from their name and type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GeneratorRule {
+    /// Leave the column out so the database default applies.
+    Skip,
+    Constant { value: Option<String> },
+    OneOf { values: Vec<String> },
+    /// Uniform in `[min, max]`, rounded to `decimals` places.
+    Range {
+        min: f64,
+        max: f64,
+        #[serde(default)]
+        decimals: u32,
+    },
+    Fake { fake: FakeKind },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GenerateResult {
+    pub inserted: u64,
+    /// Columns left to their database default.
+    pub skipped_columns: Vec<String>,
+    pub execution_time_ms: u64,
+}
+
+/// One table to copy. `target` defaults to the source table's name without
+/// its schema.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransferTable {
+    pub source: String,
+    pub target: Option<String>,
+    /// SQL condition on the source rows, used verbatim as a `WHERE` clause.
+    pub filter: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransferOptions {
+    pub tables: Vec<TransferTable>,
+    /// Rows read and inserted per round trip; defaults to 1000.
+    pub batch_size: Option<usize>,
+    /// Apply the source connection's masking rules to the copied rows.
+    #[serde(default)]
+    pub mask: bool,
+    /// Id for progress events and `db_cancel_job`.
+    pub job_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TableTransfer {
+    pub source: String,
+    pub target: String,
+    /// The target table did not exist and was created.
+    pub created: bool,
+    pub rows: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransferResult {
+    pub tables: Vec<TableTransfer>,
+    pub skipped: Vec<SkippedTable>,
+    pub cancelled: bool,
+    pub execution_time_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferProgressEvent {
+    pub job_id: String,
+    pub table: String,
+    pub tables_done: usize,
+    pub tables_total: usize,
+    pub rows_done: u64,
+    /// Rows matching the filter in the current table, when the count succeeded.
+    pub rows_total: Option<u64>,
+}
+
+/// One side of a data comparison: a table or a read-only query on a connection.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompareSource {
+    pub conn_id: String,
+    pub table: Option<String>,
+    pub sql: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CompareDataOptions {
+    /// Rows reported per category; defaults to 20.
+    pub max_samples: Option<usize>,
+    /// Also build statements that make the right side match the left. The
+    /// right side must be a table.
+    #[serde(default)]
+    pub sync_sql: bool,
+}
+
+/// A row present on one side only. `values` line up with `DataComparison::columns`.
+#[derive(Debug, Serialize)]
+pub struct RowSample {
+    pub key: Vec<KeyValue>,
+    pub values: Vec<Option<CellValue>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CellDiff {
+    pub column: String,
+    pub left: Option<CellValue>,
+    pub right: Option<CellValue>,
+}
+
+/// A key present on both sides whose rows differ in `columns`.
+#[derive(Debug, Serialize)]
+pub struct RowDiff {
+    pub key: Vec<KeyValue>,
+    pub columns: Vec<CellDiff>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DataComparison {
+    /// Key columns followed by the compared columns present on both sides.
+    pub columns: Vec<String>,
+    pub left_only_columns: Vec<String>,
+    pub right_only_columns: Vec<String>,
+    pub left_rows: u64,
+    pub right_rows: u64,
+    pub matching: u64,
+    pub only_left: u64,
+    pub only_right: u64,
+    pub different: u64,
+    pub only_left_samples: Vec<RowSample>,
+    pub only_right_samples: Vec<RowSample>,
+    pub different_samples: Vec<RowDiff>,
+    /// Statements for the right side: inserts, updates, then deletes.
+    pub sync_sql: Option<Vec<String>>,
+    pub execution_time_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+/// A condition checked after each scheduled run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlertRule {
+    /// The number of rows returned, e.g. `> 0`.
+    RowCount { op: Comparison, value: f64 },
+    /// A numeric cell of the first row; the first column when `column` is unset.
+    Value {
+        #[serde(default)]
+        column: Option<String>,
+        op: Comparison,
+        value: f64,
+    },
+    /// The result differs from the last successful run.
+    Changed,
+}
+
+/// A saved query run on a saved connection on a cron schedule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledQuery {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub query_id: String,
+    /// Id of a saved connection; runs open their own connection to it.
+    pub connection_id: String,
+    /// Five-field cron expression in local time, e.g. `*/15 * * * *`.
+    pub schedule: String,
+    /// Values for the saved query's variables.
+    #[serde(default)]
+    pub values: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+    /// Board to add a card to when a rule fires.
+    pub board_id: Option<String>,
+    pub enabled: bool,
+    #[serde(default)]
+    pub last_run_at: Option<i64>,
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+/// The stored outcome of one scheduled run.
+#[derive(Debug, Serialize)]
+pub struct QuerySnapshot {
+    pub id: i64,
+    pub schedule_id: String,
+    pub run_at: i64,
+    pub row_count: Option<u64>,
+    /// Masked, and cut to the first rows of the result.
+    pub result: Option<QueryResult>,
+    pub error: Option<String>,
+    /// Descriptions of the rules that fired.
+    pub fired: Vec<String>,
+    pub card_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleRunEvent {
+    pub schedule_id: String,
+    pub name: String,
+    pub snapshot_id: i64,
+    pub row_count: Option<u64>,
+    pub error: Option<String>,
+    pub fired: Vec<String>,
+    pub card_id: Option<String>,
+}
@@ -0,0 +1,251 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce};
+use aes_gcm::aead::{Aead, OsRng, Payload, rand_core::RngCore};
+use argon2::{Algorithm, Argon2, Params, Version};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use super::types::{ConnectionConfig, DatabaseType, SavedConnection};
+
+/// File layout: magic, Argon2id cost parameters (u32 LE each), salt, nonce,
+/// then the AES-256-GCM ciphertext of the JSON connection list. Everything
+/// before the ciphertext is authenticated as associated data.
+const MAGIC: &[u8; 8] = b"NXVAULT1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + 3 * 4 + SALT_LEN + NONCE_LEN;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultStatus {
+    pub exists: bool,
+    pub unlocked: bool,
+}
+
+struct Unlocked {
+    cipher: Aes256Gcm,
+    params: Params,
+    salt: [u8; SALT_LEN],
+    connections: Vec<ConnectionConfig>,
+}
+
+/// Saved connections, passwords included, encrypted under a master passphrase.
+/// Stays locked until `unlock`; the key only lives in memory while unlocked.
+pub struct Vault {
+    path: PathBuf,
+    unlocked: Mutex<Option<Unlocked>>,
+}
+
+impl Vault {
+    pub fn open(app: &AppHandle) -> Result<Self, String> {
+        let dir = app.path().app_data_dir()
+            .map_err(|e| format!("App data dir error: {}", e))?;
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("App data dir error: {}", e))?;
+        Ok(Vault { path: dir.join("connections.vault"), unlocked: Mutex::new(None) })
+    }
+
+    pub fn status(&self) -> VaultStatus {
+        VaultStatus {
+            exists: self.path.exists(),
+            unlocked: self.unlocked.lock().map(|u| u.is_some()).unwrap_or(false),
+        }
+    }
+
+    /// Decrypts the vault, or creates an empty one under `passphrase` on first use.
+    pub fn unlock(&self, passphrase: &str) -> Result<(), String> {
+        if passphrase.is_empty() {
+            return Err("Passphrase must not be empty".into());
+        }
+        let unlocked = if self.path.exists() {
+            let data = std::fs::read(&self.path).map_err(|e| format!("Vault read error: {}", e))?;
+            decrypt(&data, passphrase)?
+        } else {
+            let mut salt = [0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            let params = Params::default();
+            let unlocked = Unlocked {
+                cipher: derive_cipher(passphrase, &salt, &params)?,
+                params,
+                salt,
+                connections: Vec::new(),
+            };
+            self.write(&unlocked)?;
+            unlocked
+        };
+        *self.unlocked.lock().map_err(|_| "Lock error")? = Some(unlocked);
+        Ok(())
+    }
+
+    pub fn lock(&self) {
+        if let Ok(mut unlocked) = self.unlocked.lock() {
+            *unlocked = None;
+        }
+    }
+
+    fn with_unlocked<T>(&self, f: impl FnOnce(&mut Unlocked) -> Result<T, String>) -> Result<T, String> {
+        let mut guard = self.unlocked.lock().map_err(|_| "Lock error")?;
+        let unlocked = guard.as_mut().ok_or("Vault is locked")?;
+        f(unlocked)
+    }
+
+    /// Saved connections with passwords stripped, for the webview.
+    pub fn list(&self) -> Result<Vec<SavedConnection>, String> {
+        self.with_unlocked(|u| {
+            let mut list: Vec<SavedConnection> = u.connections.iter().cloned().map(SavedConnection::from).collect();
+            list.sort_by_key(|c| c.config.name.to_lowercase());
+            Ok(list)
+        })
+    }
+
+    /// Full config, password included; never returned to the webview.
+    pub fn get(&self, id: &str) -> Result<ConnectionConfig, String> {
+        self.with_unlocked(|u| {
+            u.connections.iter()
+                .find(|c| c.id == id)
+                .cloned()
+                .ok_or_else(|| "Saved connection not found".into())
+        })
+    }
+
+    /// Inserts or replaces by id. An empty password keeps the stored one, since
+    /// the webview never has it to send back.
+    pub fn save(&self, mut config: ConnectionConfig) -> Result<SavedConnection, String> {
+        self.with_unlocked(|u| {
+            let existing = u.connections.iter().position(|c| c.id == config.id);
+            if config.password.as_deref().filter(|p| !p.is_empty()).is_none() {
+                config.password = existing.and_then(|i| u.connections[i].password.clone());
+            }
+            match existing {
+                Some(i) => u.connections[i] = config.clone(),
+                None => u.connections.push(config.clone()),
+            }
+            self.write(u)?;
+            Ok(SavedConnection::from(config))
+        })
+    }
+
+    /// Adds connections whose ids are not in the vault yet; returns how many.
+    pub fn import(&self, configs: Vec<ConnectionConfig>) -> Result<usize, String> {
+        self.with_unlocked(|u| {
+            let before = u.connections.len();
+            for config in configs {
+                if !u.connections.iter().any(|c| c.id == config.id) {
+                    u.connections.push(config);
+                }
+            }
+            let added = u.connections.len() - before;
+            if added > 0 {
+                self.write(u)?;
+            }
+            Ok(added)
+        })
+    }
+
+    pub fn delete(&self, id: &str) -> Result<(), String> {
+        self.with_unlocked(|u| {
+            let before = u.connections.len();
+            u.connections.retain(|c| c.id != id);
+            if u.connections.len() == before {
+                return Err("Saved connection not found".into());
+            }
+            self.write(u)
+        })
+    }
+
+    fn write(&self, unlocked: &Unlocked) -> Result<(), String> {
+        let plaintext = serde_json::to_vec(&unlocked.connections)
+            .map_err(|e| format!("Vault write error: {}", e))?;
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        let mut data = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&unlocked.params.m_cost().to_le_bytes());
+        data.extend_from_slice(&unlocked.params.t_cost().to_le_bytes());
+        data.extend_from_slice(&unlocked.params.p_cost().to_le_bytes());
+        data.extend_from_slice(&unlocked.salt);
+        data.extend_from_slice(&nonce);
+        let ciphertext = unlocked.cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &plaintext, aad: &data })
+            .map_err(|_| "Vault encryption failed")?;
+        data.extend_from_slice(&ciphertext);
+
+        // Write-then-rename so a crash never leaves a half-written vault.
+        let tmp = self.path.with_extension("vault.tmp");
+        std::fs::write(&tmp, &data).map_err(|e| format!("Vault write error: {}", e))?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| format!("Vault write error: {}", e))
+    }
+}
+
+fn derive_cipher(passphrase: &str, salt: &[u8], params: &Params) -> Result<Aes256Gcm, String> {
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params.clone())
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+fn decrypt(data: &[u8], passphrase: &str) -> Result<Unlocked, String> {
+    if data.len() < HEADER_LEN || &data[..MAGIC.len()] != MAGIC {
+        return Err("Not a connection vault file".into());
+    }
+    let (header, ciphertext) = data.split_at(HEADER_LEN);
+    let u32_at = |offset: usize| u32::from_le_bytes(header[offset..offset + 4].try_into().unwrap_or_default());
+    let params = Params::new(u32_at(8), u32_at(12), u32_at(16), None)
+        .map_err(|e| format!("Invalid vault parameters: {}", e))?;
+    let salt: [u8; SALT_LEN] = header[20..20 + SALT_LEN].try_into().map_err(|_| "Invalid vault header")?;
+    let nonce = &header[20 + SALT_LEN..];
+
+    let cipher = derive_cipher(passphrase, &salt, &params)?;
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: header })
+        .map_err(|_| "Wrong passphrase or corrupted vault")?;
+    let connections = serde_json::from_slice(&plaintext)
+        .map_err(|e| format!("Vault read error: {}", e))?;
+
+    Ok(Unlocked { cipher, params, salt, connections })
+}
+
+/// Connections the webview used to keep in plain text in `tp_connections`.
+pub fn legacy_connections(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<ConnectionConfig>> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'tp_connections')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, name, db_type, host, port, database, username, password_enc, file_path FROM tp_connections"
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(2)?, ConnectionConfig {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            db_type: DatabaseType::Sqlite,
+            host: row.get(3)?,
+            port: row.get(4)?,
+            database: row.get(5)?,
+            username: row.get(6)?,
+            password: row.get(7)?,
+            file_path: row.get(8)?,
+            read_only: false,
+            schema: None,
+        }))
+    })?;
+    let configs = rows
+        .filter_map(|r| r.ok())
+        .filter_map(|(db_type, mut config)| {
+            config.db_type = serde_json::from_value(serde_json::Value::String(db_type)).ok()?;
+            Some(config)
+        })
+        .collect();
+    Ok(configs)
+}
+
+/// Drops the plain-text copies once they are safely in the vault.
+pub fn clear_legacy_connections(conn: &rusqlite::Connection) -> rusqlite::Result<()> {
+    conn.execute("DROP TABLE IF EXISTS tp_connections", []).map(|_| ())
+}
@@ -1,3 +1,4 @@
+use tauri::Manager;
 use tauri_plugin_sql::{Migration, MigrationKind};
 
 mod db;
@@ -258,6 +259,103 @@ pub fn run() {
             ",
             kind: MigrationKind::Up,
         },
+        Migration {
+            version: 8,
+            description: "create_saved_queries_table",
+            sql: "
+                CREATE TABLE IF NOT EXISTS saved_queries (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    connection_id TEXT,
+                    folder TEXT,
+                    tags TEXT DEFAULT '[]',
+                    sql TEXT NOT NULL,
+                    variables TEXT DEFAULT '[]',
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_saved_queries_connection ON saved_queries(connection_id);
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 9,
+            description: "create_audit_log_table",
+            sql: "
+                CREATE TABLE IF NOT EXISTS audit_log (
+                    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                    timestamp INTEGER NOT NULL,
+                    conn_id TEXT NOT NULL,
+                    connection_name TEXT NOT NULL,
+                    host TEXT,
+                    operation TEXT NOT NULL,
+                    sql TEXT NOT NULL,
+                    rows_affected INTEGER,
+                    outcome TEXT NOT NULL,
+                    error TEXT,
+                    prev_hash TEXT NOT NULL,
+                    hash TEXT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_audit_log_connection ON audit_log(conn_id, timestamp);
+                CREATE TRIGGER IF NOT EXISTS audit_log_no_update BEFORE UPDATE ON audit_log
+                BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END;
+                CREATE TRIGGER IF NOT EXISTS audit_log_no_delete BEFORE DELETE ON audit_log
+                BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END;
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 10,
+            description: "create_masking_rules_table",
+            sql: "
+                CREATE TABLE IF NOT EXISTS masking_rules (
+                    id TEXT PRIMARY KEY,
+                    connection_id TEXT NOT NULL,
+                    kind TEXT NOT NULL,
+                    pattern TEXT,
+                    table_name TEXT,
+                    column_name TEXT,
+                    mask TEXT NOT NULL,
+                    created_at INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_masking_rules_connection ON masking_rules(connection_id);
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 11,
+            description: "create_scheduled_queries_tables",
+            sql: "
+                CREATE TABLE IF NOT EXISTS scheduled_queries (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    query_id TEXT NOT NULL,
+                    connection_id TEXT NOT NULL,
+                    schedule TEXT NOT NULL,
+                    variable_values TEXT DEFAULT '{}',
+                    rules TEXT DEFAULT '[]',
+                    board_id TEXT,
+                    enabled INTEGER NOT NULL DEFAULT 1,
+                    last_run_at INTEGER,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS query_snapshots (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    schedule_id TEXT NOT NULL,
+                    run_at INTEGER NOT NULL,
+                    row_count INTEGER,
+                    result TEXT,
+                    result_hash TEXT,
+                    error TEXT,
+                    fired TEXT DEFAULT '[]',
+                    card_id TEXT,
+                    FOREIGN KEY (schedule_id) REFERENCES scheduled_queries(id) ON DELETE CASCADE
+                );
+                CREATE INDEX IF NOT EXISTS idx_query_snapshots_schedule ON query_snapshots(schedule_id, run_at);
+            ",
+            kind: MigrationKind::Up,
+        },
     ];
 
     tauri::Builder::default()
@@ -271,15 +369,69 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
         .manage(db::get_connection_pool())
+        .manage(db::CatalogCache::default())
+        .manage(db::HealthMonitor::default())
+        .manage(db::ResultCache::default())
+        .manage(db::ConfirmationRegistry::default())
+        .manage(db::CancelRegistry::default())
+        .manage(db::ListenRegistry::default())
         .invoke_handler(tauri::generate_handler![
             db::db_connect,
             db::db_disconnect,
             db::db_query,
+            db::db_query_multi,
+            db::db_get_cell,
+            db::db_browse_table,
+            db::db_export_blob,
+            db::db_import_blob,
             db::db_execute,
             db::db_get_tables,
             db::db_get_columns,
+            db::db_get_catalog,
+            db::db_list_databases,
+            db::db_use_database,
+            db::db_search_all,
+            db::db_cancel_job,
+            db::db_listen,
+            db::db_unlisten,
+            db::db_get_sessions,
+            db::db_kill_session,
+            db::db_get_privileges,
+            db::db_plan_alter_table,
+            db::db_alter_table,
+            db::db_generate_rows,
+            db::db_transfer,
+            db::db_compare_data,
+            db::db_get_connection_states,
+            db::db_list_saved_queries,
+            db::db_save_query,
+            db::db_delete_saved_query,
+            db::db_run_saved_query,
+            db::db_vault_status,
+            db::db_vault_unlock,
+            db::db_vault_lock,
+            db::db_list_connections,
+            db::db_save_connection,
+            db::db_delete_connection,
+            db::db_connect_saved,
+            db::db_audit_list,
+            db::db_audit_verify,
+            db::db_audit_export,
+            db::db_list_masking_rules,
+            db::db_save_masking_rule,
+            db::db_delete_masking_rule,
+            db::db_list_schedules,
+            db::db_save_schedule,
+            db::db_delete_schedule,
+            db::db_run_schedule,
+            db::db_list_snapshots,
         ])
         .setup(|app| {
+            app.manage(db::AppStore::open(app.handle())?);
+            app.manage(db::Vault::open(app.handle())?);
+            db::spawn_health_checker(app.handle().clone());
+            db::spawn_scheduler(app.handle().clone());
+
             if cfg!(debug_assertions) {
                 app.handle().plugin(
                     tauri_plugin_log::Builder::default()
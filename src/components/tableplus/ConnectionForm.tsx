@@ -1,17 +1,18 @@
 import { useState, useEffect } from 'react';
 import { X } from 'lucide-react';
-import type { ConnectionConfig, DatabaseType } from '../../lib/tableplus-db';
+import type { ConnectionConfig, DatabaseType, SavedConnection } from '../../lib/tableplus-db';
 
 interface Props {
   onSave: (conn: ConnectionConfig) => void;
   onCancel: () => void;
-  initial?: ConnectionConfig;
+  initial?: SavedConnection;
 }
 
 const DB_DEFAULTS: Record<DatabaseType, { host: string; port: number }> = {
   Postgres: { host: 'localhost', port: 5432 },
   Mysql: { host: 'localhost', port: 3306 },
   Sqlite: { host: '', port: 0 },
+  Duckdb: { host: '', port: 0 },
 };
 
 export function ConnectionForm({ onSave, onCancel, initial }: Props) {
@@ -21,7 +22,7 @@ export function ConnectionForm({ onSave, onCancel, initial }: Props) {
   const [port, setPort] = useState(initial?.port || 5432);
   const [database, setDatabase] = useState(initial?.database || '');
   const [username, setUsername] = useState(initial?.username || '');
-  const [password, setPassword] = useState(initial?.password || '');
+  const [password, setPassword] = useState('');
   const [filePath, setFilePath] = useState(initial?.file_path || '');
 
   // Close on Escape
@@ -31,22 +32,23 @@ export function ConnectionForm({ onSave, onCancel, initial }: Props) {
     return () => document.removeEventListener('keydown', handler);
   }, [onCancel]);
 
+  const isFileDb = dbType === 'Sqlite' || dbType === 'Duckdb';
+
   const handleSubmit = (e: React.FormEvent) => {
     e.preventDefault();
-    const finalPassword = dbType === 'Sqlite'
-      ? undefined
-      : ((password === '' && initial?.password) ? initial.password : password);
+    // Left blank, the vault keeps the stored password.
+    const finalPassword = isFileDb ? undefined : password;
 
     onSave({
       id: initial?.id || crypto.randomUUID(),
       name,
       db_type: dbType,
-      host: dbType === 'Sqlite' ? undefined : host,
-      port: dbType === 'Sqlite' ? undefined : port,
-      database: dbType === 'Sqlite' ? undefined : database,
-      username: dbType === 'Sqlite' ? undefined : username,
+      host: isFileDb ? undefined : host,
+      port: isFileDb ? undefined : port,
+      database: isFileDb ? undefined : database,
+      username: isFileDb ? undefined : username,
       password: finalPassword,
-      file_path: dbType === 'Sqlite' ? filePath : undefined,
+      file_path: isFileDb ? filePath : undefined,
     });
   };
 
@@ -81,7 +83,7 @@ export function ConnectionForm({ onSave, onCancel, initial }: Props) {
                 onChange={e => {
                   const t = e.target.value as DatabaseType;
                   setDbType(t);
-                  if (t !== 'Sqlite') {
+                  if (t !== 'Sqlite' && t !== 'Duckdb') {
                     setHost(DB_DEFAULTS[t].host);
                     setPort(DB_DEFAULTS[t].port);
                   }
@@ -91,21 +93,24 @@ export function ConnectionForm({ onSave, onCancel, initial }: Props) {
                 <option value="Sqlite">SQLite</option>
                 <option value="Postgres">PostgreSQL</option>
                 <option value="Mysql">MySQL</option>
+                <option value="Duckdb">DuckDB</option>
               </select>
             </div>
 
-            {dbType === 'Sqlite' ? (
+            {isFileDb ? (
               <div className="form-group">
                 <label>File Path</label>
                 <input
                   value={filePath}
                   onChange={e => setFilePath(e.target.value)}
-                  placeholder="/path/to/database.db"
-                  required
+                  placeholder={dbType === 'Duckdb' ? '/path/to/data.duckdb or /path/to/folder' : '/path/to/database.db'}
+                  required={dbType === 'Sqlite'}
                   className="input"
                 />
                 <small style={{ color: 'var(--text-muted)', fontSize: 11 }}>
-                  Path to SQLite database file (will be created if not exists)
+                  {dbType === 'Duckdb'
+                    ? 'DuckDB file, a folder of CSV/Parquet files (one view per file), or empty for in-memory'
+                    : 'Path to SQLite database file (will be created if not exists)'}
                 </small>
               </div>
             ) : (
@@ -161,6 +166,7 @@ export function ConnectionForm({ onSave, onCancel, initial }: Props) {
                     type="password"
                     value={password}
                     onChange={e => setPassword(e.target.value)}
+                    placeholder={initial?.has_password ? 'Saved (leave blank to keep)' : ''}
                     className="input"
                   />
                 </div>
@@ -1,12 +1,12 @@
 import { Database, Server, FileCode2, Trash2, Plug, Pencil } from 'lucide-react';
-import type { ConnectionConfig, ConnectionInfo } from '../../lib/tableplus-db';
+import type { ConnectionInfo, SavedConnection } from '../../lib/tableplus-db';
 
 interface Props {
-  connections: ConnectionConfig[];
+  connections: SavedConnection[];
   activeConnection: ConnectionInfo | null;
-  onConnect: (conn: ConnectionConfig) => void;
+  onConnect: (conn: SavedConnection) => void;
   onDisconnect: () => void;
-  onEdit: (conn: ConnectionConfig) => void;
+  onEdit: (conn: SavedConnection) => void;
   onDelete: (id: string) => void;
 }
 
@@ -14,12 +14,14 @@ const DB_ICONS = {
   Sqlite: FileCode2,
   Postgres: Server,
   Mysql: Database,
+  Duckdb: FileCode2,
 };
 
 const DB_COLORS = {
   Sqlite: '#22c55e',
   Postgres: '#3b82f6',
   Mysql: '#f59e0b',
+  Duckdb: '#eab308',
 };
 
 export function ConnectionList({
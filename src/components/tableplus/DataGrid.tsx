@@ -1,6 +1,6 @@
 import { useState, useEffect, useCallback, useMemo } from 'react';
 import { Plus, Trash2, Save, X, RefreshCw } from 'lucide-react';
-import { dbQuery, dbExecuteWithParams, type ColumnInfo, type DatabaseType } from '../../lib/tableplus-db';
+import { dbBrowseTable, dbExecuteWithParams, errorMessage, formatCell, type CellValue, type ColumnInfo, type DatabaseType } from '../../lib/tableplus-db';
 
 interface Props {
   connId: string;
@@ -37,10 +37,10 @@ export function DataGrid({ connId, tableName, columns, dbType, refreshKey }: Pro
 
   const quotedTableName = useMemo(() => (tableName ? quoteIdentifier(tableName) : ''), [tableName, quoteIdentifier]);
 
-  const mapRows = (cols: string[], rows: (string | null)[][]): RowData[] => rows.map(row => {
+  const mapRows = (cols: string[], rows: (CellValue | null)[][]): RowData[] => rows.map(row => {
     const obj: RowData = {};
     cols.forEach((col, i) => {
-      obj[col] = row[i];
+      obj[col] = formatCell(row[i]);
     });
     return obj;
   });
@@ -52,10 +52,10 @@ export function DataGrid({ connId, tableName, columns, dbType, refreshKey }: Pro
     else setLoading(true);
 
     try {
-      const result = await dbQuery(
-        connId,
-        `SELECT * FROM ${quotedTableName} LIMIT ${PAGE_SIZE} OFFSET ${startOffset}`,
-      );
+      const result = await dbBrowseTable(connId, {
+        table: tableName,
+        page: { mode: 'offset', offset: startOffset, limit: PAGE_SIZE },
+      });
       const rows = mapRows(result.columns, result.rows);
       setData(prev => append ? [...prev, ...rows] : rows);
       setOffset(startOffset + rows.length);
@@ -66,7 +66,7 @@ export function DataGrid({ connId, tableName, columns, dbType, refreshKey }: Pro
       if (append) setLoadingMore(false);
       else setLoading(false);
     }
-  }, [PAGE_SIZE, connId, quotedTableName, tableName]);
+  }, [PAGE_SIZE, connId, tableName]);
 
   const loadData = useCallback(async () => {
     setSelectedRows(new Set());
@@ -124,7 +124,7 @@ export function DataGrid({ connId, tableName, columns, dbType, refreshKey }: Pro
       }
       await loadData();
     } catch (e) {
-      alert(`Error: ${e}`);
+      alert(`Error: ${errorMessage(e)}`);
     }
     cancelEdit();
   };
@@ -146,7 +146,7 @@ export function DataGrid({ connId, tableName, columns, dbType, refreshKey }: Pro
       setSelectedRows(new Set());
       await loadData();
     } catch (e) {
-      alert(`Error: ${e}`);
+      alert(`Error: ${errorMessage(e)}`);
     }
   };
 
@@ -1,8 +1,7 @@
 import { invoke } from '@tauri-apps/api/core';
-import Database from '@tauri-apps/plugin-sql';
 
 // Types matching Rust types
-export type DatabaseType = 'Sqlite' | 'Postgres' | 'Mysql';
+export type DatabaseType = 'Sqlite' | 'Postgres' | 'Mysql' | 'Duckdb';
 
 export interface ConnectionConfig {
   id: string;
@@ -14,6 +13,19 @@ export interface ConnectionConfig {
   username?: string;
   password?: string;
   file_path?: string;
+  read_only?: boolean;
+  /** PostgreSQL schema to put first on the search path. */
+  schema?: string;
+}
+
+/** A vault entry; stored passwords are never sent to the webview. */
+export interface SavedConnection extends Omit<ConnectionConfig, 'password'> {
+  has_password: boolean;
+}
+
+export interface VaultStatus {
+  exists: boolean;
+  unlocked: boolean;
 }
 
 export interface ConnectionInfo {
@@ -22,13 +34,52 @@ export interface ConnectionInfo {
   db_type: DatabaseType;
   version: string;
   connected: boolean;
+  read_only: boolean;
+}
+
+export type HealthState = 'connected' | 'degraded' | 'lost';
+
+/** Payload of the `db:connection-state` event. */
+export interface ConnectionStateEvent {
+  conn_id: string;
+  state: HealthState;
+  message: string | null;
+}
+
+/** Binary cells arrive as a tagged preview instead of text. */
+export type CellValue =
+  | string
+  | { type: 'binary'; length: number; preview_hex: string };
+
+/** Display text for a cell; binary cells render as a hex preview. */
+export function formatCell(cell: CellValue | null): string | null {
+  if (cell === null || typeof cell === 'string') return cell;
+  const more = cell.length > cell.preview_hex.length / 2 ? '…' : '';
+  return `0x${cell.preview_hex}${more} (${cell.length} bytes)`;
 }
 
 export interface QueryResult {
   columns: string[];
-  rows: (string | null)[][];
+  rows: (CellValue | null)[][];
   row_count: number;
   execution_time_ms: number;
+  truncated: boolean;
+  truncated_cells: number;
+  total_rows: number | null;
+  result_id: string | null;
+}
+
+/** One result set of a batch; `statement` indexes the batch's statements. */
+export interface ResultSet {
+  statement: number;
+  result: QueryResult;
+  rows_affected: number;
+}
+
+/** Omitted fields are unlimited; omit the whole object for the backend defaults. */
+export interface QueryLimits {
+  max_rows?: number;
+  max_cell_bytes?: number;
 }
 
 export interface ExecuteResult {
@@ -51,6 +102,67 @@ export interface ColumnInfo {
   primary_key: boolean;
 }
 
+export interface ForeignKeyInfo {
+  name: string | null;
+  columns: string[];
+  referenced_schema: string | null;
+  referenced_table: string;
+  referenced_columns: string[];
+}
+
+export interface CatalogTable {
+  name: string;
+  schema: string | null;
+  columns: ColumnInfo[];
+  foreign_keys: ForeignKeyInfo[];
+}
+
+/** MySQL databases are its schemas, so `schemas` is empty there. */
+export interface DatabaseList {
+  databases: string[];
+  current_database: string | null;
+  schemas: string[];
+  current_schema: string | null;
+}
+
+export interface Catalog {
+  schemas: string[];
+  tables: CatalogTable[];
+  loaded_at: number;
+}
+
+/** Byte arrays bind as binary (BLOB/bytea). */
+export type QueryParam = null | boolean | number | string | number[];
+
+export interface KeyValue {
+  column: string;
+  value: QueryParam;
+}
+
+export type VariableType = 'text' | 'integer' | 'number' | 'boolean' | 'date';
+
+export interface QueryVariable {
+  name: string;
+  var_type: VariableType;
+  default: QueryParam | null;
+}
+
+export interface SavedQuery {
+  id: string;
+  name: string;
+  connection_id: string | null;
+  folder: string | null;
+  tags: string[];
+  sql: string;
+  variables: QueryVariable[];
+  created_at: number;
+  updated_at: number;
+}
+
+export type SavedQueryOutput =
+  | { kind: 'query'; result: QueryResult }
+  | { kind: 'execute'; result: ExecuteResult };
+
 // Tauri command wrappers
 export async function dbConnect(config: ConnectionConfig): Promise<ConnectionInfo> {
   return invoke('db_connect', { config });
@@ -60,12 +172,120 @@ export async function dbDisconnect(connId: string): Promise<void> {
   return invoke('db_disconnect', { connId });
 }
 
-export async function dbQuery(connId: string, sql: string): Promise<QueryResult> {
-  return invoke('db_query', { connId, sql });
+export async function dbGetConnectionStates(): Promise<ConnectionStateEvent[]> {
+  return invoke('db_get_connection_states');
+}
+
+export async function dbQuery(connId: string, sql: string, limits?: QueryLimits): Promise<QueryResult> {
+  return invoke('db_query', { connId, sql, limits: limits ?? null });
+}
+
+/** Read-only statements and `CALL`s separated by `;`; every result set, in order. */
+export async function dbQueryMulti(connId: string, sql: string, limits?: QueryLimits): Promise<ResultSet[]> {
+  return invoke('db_query_multi', { connId, sql, limits: limits ?? null });
+}
+
+export async function dbGetCell(resultId: string, row: number, column: number): Promise<string> {
+  return invoke('db_get_cell', { resultId, row, column });
+}
+
+export async function dbExportBlob(
+  connId: string,
+  table: string,
+  column: string,
+  key: KeyValue[],
+  path: string,
+): Promise<number> {
+  return invoke('db_export_blob', { connId, table, column, key, path });
+}
+
+export async function dbImportBlob(
+  connId: string,
+  table: string,
+  column: string,
+  key: KeyValue[],
+  path: string,
+): Promise<ExecuteResult> {
+  return invoke('db_import_blob', { connId, table, column, key, path });
+}
+
+export type DbErrorKind =
+  | 'syntax'
+  | 'not_found'
+  | 'constraint'
+  | 'permission'
+  | 'invalid_input'
+  | 'conflict'
+  | 'timeout'
+  | 'cancelled'
+  | 'connection'
+  | 'confirmation_required'
+  | 'other';
+
+/** Rejection from every `db_*` command. */
+export interface DbError {
+  kind: DbErrorKind;
+  /** SQLSTATE, or the vendor code where the backend has no specific one. */
+  code: string | null;
+  message: string;
+  detail: string | null;
+  hint: string | null;
+  /** 1-based character offset of the failing token in the statement. */
+  position: number | null;
+  retryable: boolean;
+}
+
+export interface ConfirmationRequired extends DbError {
+  kind: 'confirmation_required';
+  operation: string;
+  tables: string[];
+  estimated_rows: number | null;
+  token: string;
+}
+
+export function isDbError(e: unknown): e is DbError {
+  return typeof e === 'object' && e !== null && 'kind' in e && 'message' in e;
+}
+
+export function isConfirmationRequired(e: unknown): e is ConfirmationRequired {
+  return isDbError(e) && e.kind === 'confirmation_required';
 }
 
-export async function dbExecute(connId: string, sql: string): Promise<ExecuteResult> {
-  return invoke('db_execute', { connId, sql });
+/** Readable text for any command rejection, string or `DbError`. */
+export function errorMessage(e: unknown): string {
+  if (typeof e === 'string') return e;
+  if (isDbError(e)) return e.detail ? `${e.message}\n${e.detail}` : e.message;
+  if (e instanceof Error) return e.message;
+  return String(e);
+}
+
+/**
+ * Runs a write statement. Destructive statements reject with
+ * `confirmation_required`; pass its `token` back with the same SQL to run it.
+ */
+export async function dbExecute(
+  connId: string,
+  sql: string,
+  confirmationToken?: string,
+): Promise<ExecuteResult> {
+  return invoke('db_execute', { connId, sql, confirmationToken: confirmationToken ?? null });
+}
+
+export async function dbGetCatalog(connId: string, refresh = false): Promise<Catalog> {
+  return invoke('db_get_catalog', { connId, refresh });
+}
+
+export async function dbListDatabases(connId: string): Promise<DatabaseList> {
+  return invoke('db_list_databases', { connId });
+}
+
+/** Scopes `dbGetTables` to `database` and/or `schema`; PostgreSQL reconnects to change database. */
+export async function dbUseDatabase(
+  connId: string,
+  database?: string,
+  schema?: string,
+): Promise<ConnectionInfo> {
+  return invoke('db_use_database', { connId, database: database ?? null, schema: schema ?? null });
 }
 
 function sqlLiteral(value: unknown): string {
@@ -94,6 +314,355 @@ export async function dbExecuteWithParams(
   return invoke('db_execute', { connId, sql: finalSql });
 }
 
+export type FilterOp =
+  | { op: 'eq'; value: QueryParam }
+  | { op: 'like'; value: string }
+  | { op: 'in'; value: QueryParam[] }
+  | { op: 'between'; value: [QueryParam, QueryParam] }
+  | { op: 'is_null' }
+  | { op: 'is_not_null' };
+
+export type BrowseFilter = { column: string } & FilterOp;
+
+export interface SortKey {
+  column: string;
+  descending?: boolean;
+}
+
+export type BrowsePage =
+  | { mode: 'offset'; offset: number; limit: number }
+  | { mode: 'keyset'; after: QueryParam[] | null; limit: number };
+
+export interface BrowseSpec {
+  table: string;
+  columns?: string[];
+  filters?: BrowseFilter[];
+  sort?: SortKey[];
+  page?: BrowsePage;
+}
+
+export interface BrowseResult extends QueryResult {
+  total: number | null;
+  total_is_estimate: boolean;
+  next_cursor: QueryParam[] | null;
+}
+
+export async function dbBrowseTable(connId: string, spec: BrowseSpec): Promise<BrowseResult> {
+  return invoke('db_browse_table', { connId, spec });
+}
+
+export type SearchMode = 'contains' | 'exact' | 'full_text';
+
+export interface SearchOptions {
+  schemas?: string[];
+  mode?: SearchMode;
+  case_sensitive?: boolean;
+  max_hits_per_table?: number;
+  /** Enables `db:search-progress` events and cancellation via `dbCancelJob`. */
+  job_id?: string;
+}
+
+export interface SearchHit {
+  table: string;
+  columns: string[];
+  result: QueryResult;
+}
+
+export interface SearchResult {
+  hits: SearchHit[];
+  tables_searched: number;
+  skipped: { table: string; error: DbError }[];
+  cancelled: boolean;
+}
+
+export interface SearchProgressEvent {
+  job_id: string;
+  tables_done: number;
+  tables_total: number;
+  table: string;
+  hits: number;
+}
+
+export async function dbSearchAll(
+  connId: string,
+  term: string,
+  options?: SearchOptions,
+): Promise<SearchResult> {
+  return invoke('db_search_all', { connId, term, options: options ?? null });
+}
+
+export async function dbCancelJob(jobId: string): Promise<boolean> {
+  return invoke('db_cancel_job', { jobId });
+}
+
+export interface ColumnDef {
+  name: string;
+  data_type: string;
+  nullable: boolean;
+  /** SQL expression, used verbatim. */
+  default?: string | null;
+}
+
+export type ReferentialAction = 'no_action' | 'restrict' | 'cascade' | 'set_null' | 'set_default';
+
+export type AlterOp =
+  | { op: 'add_column'; column: ColumnDef }
+  | { op: 'rename_column'; from: string; to: string }
+  | { op: 'retype_column'; column: string; data_type: string; nullable?: boolean | null }
+  | { op: 'drop_column'; column: string }
+  | { op: 'add_index'; name: string; columns: string[]; unique?: boolean }
+  | { op: 'drop_index'; name: string }
+  | {
+      op: 'add_foreign_key';
+      name?: string | null;
+      columns: string[];
+      referenced_table: string;
+      referenced_columns: string[];
+      on_delete?: ReferentialAction | null;
+    }
+  | { op: 'drop_foreign_key'; name?: string | null; columns?: string[] }
+  | { op: 'rename_table'; to: string };
+
+export interface TableAlteration {
+  table: string;
+  ops: AlterOp[];
+}
+
+export interface DdlPlan {
+  statements: string[];
+  transactional: boolean;
+  rebuild: boolean;
+  warnings: string[];
+}
+
+export async function dbPlanAlterTable(connId: string, alteration: TableAlteration): Promise<DdlPlan> {
+  return invoke('db_plan_alter_table', { connId, alteration });
+}
+
+/** Dropping columns rejects with `confirmation_required`; see `dbExecute`. */
+export async function dbAlterTable(
+  connId: string,
+  alteration: TableAlteration,
+  confirmationToken?: string,
+): Promise<DdlPlan> {
+  return invoke('db_alter_table', { connId, alteration, confirmationToken: confirmationToken ?? null });
+}
+
+export type FakeKind =
+  | 'email'
+  | 'first_name'
+  | 'last_name'
+  | 'full_name'
+  | 'username'
+  | 'phone'
+  | 'company'
+  | 'city'
+  | 'country'
+  | 'address'
+  | 'url'
+  | 'uuid'
+  | 'date'
+  | 'timestamp'
+  | 'amount'
+  | 'integer'
+  | 'boolean'
+  | 'word'
+  | 'sentence';
+
+/** Per-column fill rule; columns without one are guessed from name and type. */
+export type GeneratorRule =
+  | { kind: 'skip' }
+  | { kind: 'constant'; value: string | null }
+  | { kind: 'one_of'; values: string[] }
+  | { kind: 'range'; min: number; max: number; decimals?: number }
+  | { kind: 'fake'; fake: FakeKind };
+
+export interface GenerateResult {
+  inserted: number;
+  skipped_columns: string[];
+  execution_time_ms: number;
+}
+
+export async function dbGenerateRows(
+  connId: string,
+  table: string,
+  count: number,
+  rules?: Record<string, GeneratorRule>,
+): Promise<GenerateResult> {
+  return invoke('db_generate_rows', { connId, table, count, rules: rules ?? null });
+}
+
+export interface TransferTable {
+  source: string;
+  /** Defaults to the source table's name without its schema. */
+  target?: string;
+  /** SQL condition on the source rows. */
+  filter?: string;
+}
+
+export interface TransferOptions {
+  tables: TransferTable[];
+  batch_size?: number;
+  /** Apply the source connection's masking rules to the copied rows. */
+  mask?: boolean;
+  /** Enables `db:transfer-progress` events and cancellation via `dbCancelJob`. */
+  job_id?: string;
+}
+
+export interface TableTransfer {
+  source: string;
+  target: string;
+  created: boolean;
+  rows: number;
+}
+
+export interface TransferResult {
+  tables: TableTransfer[];
+  skipped: { table: string; error: DbError }[];
+  cancelled: boolean;
+  execution_time_ms: number;
+}
+
+export interface TransferProgressEvent {
+  job_id: string;
+  table: string;
+  tables_done: number;
+  tables_total: number;
+  rows_done: number;
+  rows_total: number | null;
+}
+
+export async function dbTransfer(
+  sourceConnId: string,
+  targetConnId: string,
+  options: TransferOptions,
+): Promise<TransferResult> {
+  return invoke('db_transfer', { sourceConnId, targetConnId, options });
+}
+
+/** One side of `dbCompareData`: a table or a read-only query. */
+export interface CompareSource {
+  conn_id: string;
+  table?: string;
+  sql?: string;
+}
+
+export interface CompareDataOptions {
+  max_samples?: number;
+  /** Also build statements that make the right table match the left. */
+  sync_sql?: boolean;
+}
+
+export interface RowSample {
+  key: KeyValue[];
+  values: (CellValue | null)[];
+}
+
+export interface RowDiff {
+  key: KeyValue[];
+  columns: { column: string; left: CellValue | null; right: CellValue | null }[];
+}
+
+export interface DataComparison {
+  columns: string[];
+  left_only_columns: string[];
+  right_only_columns: string[];
+  left_rows: number;
+  right_rows: number;
+  matching: number;
+  only_left: number;
+  only_right: number;
+  different: number;
+  only_left_samples: RowSample[];
+  only_right_samples: RowSample[];
+  different_samples: RowDiff[];
+  sync_sql: string[] | null;
+  execution_time_ms: number;
+}
+
+export async function dbCompareData(
+  left: CompareSource,
+  right: CompareSource,
+  keyColumns: string[],
+  options?: CompareDataOptions,
+): Promise<DataComparison> {
+  return invoke('db_compare_data', { left, right, keyColumns, options: options ?? null });
+}
+
+export interface SessionInfo {
+  id: string;
+  user: string | null;
+  database: string | null;
+  client: string | null;
+  application: string | null;
+  state: string | null;
+  query: string | null;
+  duration_ms: number | null;
+  wait: string | null;
+  blocked_by: string[];
+  is_current: boolean;
+}
+
+export type KillMode = 'cancel' | 'terminate';
+
+export async function dbGetSessions(connId: string): Promise<SessionInfo[]> {
+  return invoke('db_get_sessions', { connId });
+}
+
+export async function dbKillSession(
+  connId: string,
+  sessionId: string,
+  mode: KillMode = 'terminate',
+): Promise<ExecuteResult> {
+  return invoke('db_kill_session', { connId, sessionId, mode });
+}
+
+export interface RoleInfo {
+  name: string;
+  can_login: boolean;
+  superuser: boolean;
+  member_of: string[];
+}
+
+export type GrantLevel = 'global' | 'database' | 'table' | 'column' | 'routine';
+
+export interface PrivilegeGrant {
+  grantee: string;
+  level: GrantLevel;
+  schema: string | null;
+  object: string | null;
+  column: string | null;
+  privilege: string;
+  grantable: boolean;
+}
+
+export interface PrivilegeReport {
+  roles: RoleInfo[];
+  grants: PrivilegeGrant[];
+  skipped: string[];
+}
+
+export async function dbGetPrivileges(connId: string): Promise<PrivilegeReport> {
+  return invoke('db_get_privileges', { connId });
+}
+
+/** Payload of the `db:notification` event (PostgreSQL NOTIFY). */
+export interface NotificationEvent {
+  conn_id: string;
+  channel: string;
+  payload: string;
+  process_id: number;
+}
+
+export async function dbListen(connId: string, channel: string): Promise<void> {
+  return invoke('db_listen', { connId, channel });
+}
+
+/** Stops listening on `channel`, or on all channels when omitted. */
+export async function dbUnlisten(connId: string, channel?: string): Promise<void> {
+  return invoke('db_unlisten', { connId, channel: channel ?? null });
+}
+
 export async function dbGetTables(connId: string): Promise<TableInfo[]> {
   return invoke('db_get_tables', { connId });
 }
@@ -102,85 +671,198 @@ export async function dbGetColumns(connId: string, table: string): Promise<Colum
   return invoke('db_get_columns', { connId, table });
 }
 
-// Saved connections storage
-let _tpTablesReady = false;
-
-async function ensureTables() {
-  if (_tpTablesReady) return;
-  const db = await Database.load('sqlite:nexus.db');
-  await db.execute(`
-    CREATE TABLE IF NOT EXISTS tp_connections (
-      id TEXT PRIMARY KEY,
-      name TEXT NOT NULL,
-      db_type TEXT NOT NULL,
-      host TEXT,
-      port INTEGER,
-      database TEXT,
-      username TEXT,
-      password_enc TEXT,
-      file_path TEXT,
-      color TEXT DEFAULT '#3b82f6',
-      created_at INTEGER,
-      last_connected_at INTEGER
-    )
-  `);
-  _tpTablesReady = true;
-}
-
-export async function getSavedConnections(): Promise<ConnectionConfig[]> {
-  await ensureTables();
-  const db = await Database.load('sqlite:nexus.db');
-  type TpConnectionRow = {
-    id: string;
-    name: string;
-    db_type: DatabaseType;
-    host: string | null;
-    port: number | null;
-    database: string | null;
-    username: string | null;
-    password_enc: string | null;
-    file_path: string | null;
-  };
-  const rows = await db.select<TpConnectionRow[]>('SELECT * FROM tp_connections ORDER BY name');
-  return rows.map(r => ({
-    id: r.id,
-    name: r.name,
-    db_type: r.db_type as DatabaseType,
-    host: r.host || undefined,
-    port: r.port || undefined,
-    database: r.database || undefined,
-    username: r.username || undefined,
-    password: r.password_enc || undefined,
-    file_path: r.file_path || undefined,
-  }));
-}
-
-export async function saveConnection(conn: ConnectionConfig): Promise<void> {
-  await ensureTables();
-  const db = await Database.load('sqlite:nexus.db');
-  await db.execute(
-    `INSERT OR REPLACE INTO tp_connections
-     (id, name, db_type, host, port, database, username, password_enc, file_path, created_at)
-     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)`,
-    [conn.id, conn.name, conn.db_type, conn.host || null, conn.port || null,
-     conn.database || null, conn.username || null, conn.password || null,
-     conn.file_path || null, Date.now()]
-  );
+// Saved queries (stored by the backend in nexus.db)
+export async function listSavedQueries(connectionId?: string): Promise<SavedQuery[]> {
+  return invoke('db_list_saved_queries', { connectionId: connectionId ?? null });
+}
+
+export async function saveQuery(query: SavedQuery): Promise<SavedQuery> {
+  return invoke('db_save_query', { query });
+}
+
+export async function deleteSavedQuery(id: string): Promise<void> {
+  return invoke('db_delete_saved_query', { id });
+}
+
+export async function runSavedQuery(
+  connId: string,
+  queryId: string,
+  values: Record<string, QueryParam> = {},
+): Promise<SavedQueryOutput> {
+  return invoke('db_run_saved_query', { connId, queryId, values });
+}
+
+// Saved connections vault (encrypted on the Rust side)
+export async function vaultStatus(): Promise<VaultStatus> {
+  return invoke('db_vault_status');
+}
+
+/** Unlocks the vault, creating it under this passphrase on first use. */
+export async function unlockVault(passphrase: string): Promise<VaultStatus> {
+  return invoke('db_vault_unlock', { passphrase });
+}
+
+export async function lockVault(): Promise<VaultStatus> {
+  return invoke('db_vault_lock');
+}
+
+export async function getSavedConnections(): Promise<SavedConnection[]> {
+  return invoke('db_list_connections');
+}
+
+/** An empty password keeps the one already stored for this connection. */
+export async function saveConnection(config: ConnectionConfig): Promise<SavedConnection> {
+  return invoke('db_save_connection', { config });
 }
 
 export async function deleteConnection(id: string): Promise<void> {
-  await ensureTables();
-  const db = await Database.load('sqlite:nexus.db');
-  const before = await db.select<Array<{ c: number }>>(
-    'SELECT COUNT(*) as c FROM tp_connections WHERE id = ?',
-    [id],
-  );
-  await db.execute('DELETE FROM tp_connections WHERE id = ?', [id]);
-  const after = await db.select<Array<{ c: number }>>(
-    'SELECT COUNT(*) as c FROM tp_connections WHERE id = ?',
-    [id],
-  );
-  if ((before[0]?.c ?? 0) > 0 && (after[0]?.c ?? 0) > 0) {
-    throw new Error('Delete did not remove the connection record.');
-  }
+  return invoke('db_delete_connection', { id });
+}
+
+export async function dbConnectSaved(id: string): Promise<ConnectionInfo> {
+  return invoke('db_connect_saved', { id });
+}
+
+// Audit log of write statements (hash-chained, append-only)
+export type AuditOutcome = 'success' | 'failure';
+
+export interface AuditEntry {
+  seq: number;
+  timestamp: number;
+  conn_id: string;
+  connection_name: string;
+  host: string | null;
+  operation: string;
+  sql: string;
+  rows_affected: number | null;
+  outcome: AuditOutcome;
+  error: string | null;
+  prev_hash: string;
+  hash: string;
+}
+
+export interface AuditFilter {
+  conn_id?: string;
+  since?: number;
+  until?: number;
+  outcome?: AuditOutcome;
+  limit?: number;
+  offset?: number;
+}
+
+export interface AuditVerification {
+  valid: boolean;
+  checked: number;
+  broken_at: number | null;
+}
+
+export async function listAuditLog(filter?: AuditFilter): Promise<AuditEntry[]> {
+  return invoke('db_audit_list', { filter: filter ?? null });
+}
+
+export async function verifyAuditLog(): Promise<AuditVerification> {
+  return invoke('db_audit_verify');
+}
+
+export async function exportAuditLog(
+  path: string,
+  format: 'csv' | 'jsonl',
+  filter?: AuditFilter,
+): Promise<number> {
+  return invoke('db_audit_export', { path, format, filter: filter ?? null });
+}
+
+// Column masking rules, applied by the backend before results reach the UI
+export type MaskType = 'redact' | 'hash' | 'partial' | 'null';
+
+export type MaskTarget =
+  | { kind: 'pattern'; pattern: string }
+  | { kind: 'column'; table: string; column: string };
+
+export interface MaskingRule {
+  id: string;
+  connection_id: string;
+  target: MaskTarget;
+  mask: MaskType;
+  created_at: number;
+}
+
+export async function listMaskingRules(connectionId: string): Promise<MaskingRule[]> {
+  return invoke('db_list_masking_rules', { connectionId });
+}
+
+export async function saveMaskingRule(rule: MaskingRule): Promise<MaskingRule> {
+  return invoke('db_save_masking_rule', { rule });
+}
+
+export async function deleteMaskingRule(id: string): Promise<void> {
+  return invoke('db_delete_masking_rule', { id });
+}
+
+export type Comparison = 'gt' | 'ge' | 'lt' | 'le' | 'eq' | 'ne';
+
+export type AlertRule =
+  | { kind: 'row_count'; op: Comparison; value: number }
+  /** A numeric cell of the first row; the first column when `column` is unset. */
+  | { kind: 'value'; column?: string | null; op: Comparison; value: number }
+  | { kind: 'changed' };
+
+export interface ScheduledQuery {
+  id: string;
+  name: string;
+  query_id: string;
+  /** Id of a saved connection. */
+  connection_id: string;
+  /** Five-field cron expression in local time, e.g. `*\/15 * * * *`. */
+  schedule: string;
+  values: Record<string, unknown>;
+  rules: AlertRule[];
+  /** Board that gets a card when a rule fires. */
+  board_id: string | null;
+  enabled: boolean;
+  last_run_at: number | null;
+  created_at: number;
+  updated_at: number;
+}
+
+export interface QuerySnapshot {
+  id: number;
+  schedule_id: string;
+  run_at: number;
+  row_count: number | null;
+  result: QueryResult | null;
+  error: string | null;
+  fired: string[];
+  card_id: string | null;
+}
+
+/** Payload of `db:schedule-run`, and of `db:schedule-alert` when rules fired. */
+export interface ScheduleRunEvent {
+  schedule_id: string;
+  name: string;
+  snapshot_id: number;
+  row_count: number | null;
+  error: string | null;
+  fired: string[];
+  card_id: string | null;
+}
+
+export async function listSchedules(): Promise<ScheduledQuery[]> {
+  return invoke('db_list_schedules');
+}
+
+export async function saveSchedule(schedule: ScheduledQuery): Promise<ScheduledQuery> {
+  return invoke('db_save_schedule', { schedule });
+}
+
+export async function deleteSchedule(id: string): Promise<void> {
+  return invoke('db_delete_schedule', { id });
+}
+
+export async function runSchedule(id: string): Promise<QuerySnapshot> {
+  return invoke('db_run_schedule', { id });
+}
+
+export async function listSnapshots(scheduleId: string, limit?: number): Promise<QuerySnapshot[]> {
+  return invoke('db_list_snapshots', { scheduleId, limit: limit ?? null });
 }
@@ -8,12 +8,17 @@ import {
   getSavedConnections,
   saveConnection,
   deleteConnection,
-  dbConnect,
+  vaultStatus,
+  unlockVault,
+  dbConnectSaved,
   dbDisconnect,
   dbQuery,
   dbGetTables,
   dbGetColumns,
+  formatCell,
+  errorMessage,
   type ConnectionConfig,
+  type SavedConnection,
   type ConnectionInfo,
   type TableInfo,
   type ColumnInfo,
@@ -24,10 +29,10 @@ type TabType = 'query' | 'data';
 
 export function TablePlus() {
   const QUERY_PAGE_SIZE = 100;
-  const [connections, setConnections] = useState<ConnectionConfig[]>([]);
+  const [connections, setConnections] = useState<SavedConnection[]>([]);
   const [activeConnection, setActiveConnection] = useState<ConnectionInfo | null>(null);
   const [showForm, setShowForm] = useState(false);
-  const [editingConn, setEditingConn] = useState<ConnectionConfig | null>(null);
+  const [editingConn, setEditingConn] = useState<SavedConnection | null>(null);
 
   // Schema browser state
   const [tables, setTables] = useState<TableInfo[]>([]);
@@ -50,15 +55,44 @@ export function TablePlus() {
   const [queryHasMore, setQueryHasMore] = useState(false);
   const [queryAutoLimited, setQueryAutoLimited] = useState(false);
 
-  // Load saved connections
+  // Connection vault state
+  const [vaultUnlocked, setVaultUnlocked] = useState(false);
+  const [vaultExists, setVaultExists] = useState(true);
+  const [passphrase, setPassphrase] = useState('');
+  const [vaultError, setVaultError] = useState<string | null>(null);
+
+  // Load saved connections once the vault is unlocked
   useEffect(() => {
-    getSavedConnections().then(setConnections).catch(console.error);
+    vaultStatus()
+      .then(status => {
+        setVaultExists(status.exists);
+        setVaultUnlocked(status.unlocked);
+      })
+      .catch(console.error);
   }, []);
 
+  useEffect(() => {
+    if (!vaultUnlocked) return;
+    getSavedConnections().then(setConnections).catch(console.error);
+  }, [vaultUnlocked]);
+
+  const handleUnlock = async (e: React.FormEvent) => {
+    e.preventDefault();
+    try {
+      const status = await unlockVault(passphrase);
+      setPassphrase('');
+      setVaultError(null);
+      setVaultExists(status.exists);
+      setVaultUnlocked(status.unlocked);
+    } catch (err) {
+      setVaultError(errorMessage(err));
+    }
+  };
+
   // Connect to database
-  const handleConnect = async (conn: ConnectionConfig) => {
+  const handleConnect = async (conn: SavedConnection) => {
     try {
-      const info = await dbConnect(conn);
+      const info = await dbConnectSaved(conn.id);
       setActiveConnection(info);
       setExpandedTables(new Set());
       setColumns({});
@@ -72,11 +106,11 @@ export function TablePlus() {
         setTables(tbls);
       } catch (tableErr) {
         setTables([]);
-        const message = tableErr instanceof Error ? tableErr.message : String(tableErr);
+        const message = errorMessage(tableErr);
         alert(`Connected but failed to load tables: ${message}`);
       }
     } catch (e) {
-      const message = e instanceof Error ? e.message : String(e);
+      const message = errorMessage(e);
       alert(`Connection failed: ${message}`);
     }
   };
@@ -107,12 +141,12 @@ export function TablePlus() {
       await deleteConnection(id);
       setConnections(await getSavedConnections());
     } catch (e) {
-      const message = e instanceof Error ? e.message : String(e);
+      const message = errorMessage(e);
       alert(`Delete failed: ${message}`);
     }
   };
 
-  const handleEdit = (conn: ConnectionConfig) => {
+  const handleEdit = (conn: SavedConnection) => {
     setEditingConn(conn);
     setShowForm(true);
   };
@@ -178,7 +212,7 @@ export function TablePlus() {
         setQueryAutoLimited(false);
       }
     } catch (e) {
-      setQueryError(e instanceof Error ? e.message : String(e));
+      setQueryError(errorMessage(e));
       setQueryResult(null);
       setQueryBaseSql(null);
       setQueryOffset(0);
@@ -206,7 +240,7 @@ export function TablePlus() {
       setQueryOffset(prev => prev + result.rows.length);
       setQueryHasMore(result.rows.length === QUERY_PAGE_SIZE);
     } catch (e) {
-      setQueryError(e instanceof Error ? e.message : String(e));
+      setQueryError(errorMessage(e));
     } finally {
       setLoadingMoreQuery(false);
     }
@@ -238,20 +272,40 @@ export function TablePlus() {
           <button
             className="btn-icon-sm"
             onClick={() => { setEditingConn(null); setShowForm(true); }}
+            disabled={!vaultUnlocked}
             title="New connection"
           >
             <Plus size={14} />
           </button>
         </div>
 
-        <ConnectionList
-          connections={connections}
-          activeConnection={activeConnection}
-          onConnect={handleConnect}
-          onDisconnect={handleDisconnect}
-          onEdit={handleEdit}
-          onDelete={handleDelete}
-        />
+        {vaultUnlocked ? (
+          <ConnectionList
+            connections={connections}
+            activeConnection={activeConnection}
+            onConnect={handleConnect}
+            onDisconnect={handleDisconnect}
+            onEdit={handleEdit}
+            onDelete={handleDelete}
+          />
+        ) : (
+          <form className="tp-vault-unlock" onSubmit={handleUnlock} style={{ padding: 12 }}>
+            <div className="form-group">
+              <label>{vaultExists ? 'Master passphrase' : 'Choose a master passphrase'}</label>
+              <input
+                type="password"
+                value={passphrase}
+                onChange={e => setPassphrase(e.target.value)}
+                required
+                className="input"
+              />
+            </div>
+            {vaultError && <div className="error-text" style={{ fontSize: 12 }}>{vaultError}</div>}
+            <button type="submit" className="btn btn-primary" style={{ marginTop: 8 }}>
+              {vaultExists ? 'Unlock' : 'Create vault'}
+            </button>
+          </form>
+        )}
       </div>
 
       {/* Schema Browser */}
@@ -395,7 +449,7 @@ export function TablePlus() {
                           <tr key={i}>
                             {row.map((cell, j) => (
                               <td key={j} className={cell === null ? 'null' : ''}>
-                                {cell === null ? <span className="null-text">NULL</span> : cell}
+                                {cell === null ? <span className="null-text">NULL</span> : formatCell(cell)}
                               </td>
                             ))}
                           </tr>